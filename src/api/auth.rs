@@ -0,0 +1,209 @@
+//! Bearer token authentication for `ApiClient`
+//!
+//! The session is shared between all clones of a client, so a login on the
+//! UI thread is immediately visible to requests issued by spawned tasks.
+
+use crate::api::client::ApiClient;
+use crate::api::error::ApiError;
+use crate::api::models::*;
+use reqwest::StatusCode;
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub user: AuthUser,
+}
+
+#[allow(dead_code)]
+impl ApiClient {
+    /// Exchange credentials for a token pair and store the session
+    pub async fn login(&self, email: &str, password: &str) -> Result<AuthUser, ApiError> {
+        let config = &self.config;
+        let url = format!("{}{}", config.api_url(), config.route_auth_login);
+        let req = LoginRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+        };
+        let resp = self.client.post(&url).json(&req).send().await?;
+        let tokens: TokenResponse = Self::decode(Self::check(resp).await?).await?;
+
+        let user = tokens.user.clone().unwrap_or_else(|| AuthUser {
+            email: email.to_string(),
+            ..Default::default()
+        });
+        self.store_session(tokens, user.clone());
+        Ok(user)
+    }
+
+    /// Use the refresh token to obtain a new access token
+    pub async fn refresh_session(&self) -> Result<(), ApiError> {
+        let Some(session) = self.session() else {
+            return Err(ApiError::from_status(
+                StatusCode::UNAUTHORIZED,
+                String::new(),
+            ));
+        };
+        let Some(refresh_token) = session.refresh_token.clone() else {
+            return Err(ApiError::from_status(
+                StatusCode::UNAUTHORIZED,
+                String::new(),
+            ));
+        };
+
+        let config = &self.config;
+        let url = format!("{}{}", config.api_url(), config.route_auth_refresh);
+        let resp = self
+            .client
+            .post(&url)
+            .json(&RefreshTokenRequest { refresh_token })
+            .send()
+            .await?;
+        let tokens: TokenResponse = Self::decode(Self::check(resp).await?).await?;
+
+        let user = tokens.user.clone().unwrap_or(session.user);
+        self.store_session(tokens, user);
+        Ok(())
+    }
+
+    /// Called after a request was rejected with 401 using `stale_token`.
+    /// Refreshes the session unless another request already did, and signs
+    /// the user out if the refresh fails.
+    pub(super) async fn recover_from_unauthorized(
+        &self,
+        stale_token: &str,
+    ) -> Result<(), ApiError> {
+        if self.access_token().is_some_and(|t| t != stale_token) {
+            return Ok(());
+        }
+        self.refresh_session().await.inspect_err(|_| self.logout())
+    }
+
+    pub fn logout(&self) {
+        *self.session.write().unwrap() = None;
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.session.read().unwrap().is_some()
+    }
+
+    pub fn current_user(&self) -> Option<AuthUser> {
+        self.session().map(|s| s.user)
+    }
+
+    pub fn session(&self) -> Option<Session> {
+        self.session.read().unwrap().clone()
+    }
+
+    pub(super) fn access_token(&self) -> Option<String> {
+        self.session
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|s| s.access_token.clone())
+    }
+
+    fn store_session(&self, tokens: TokenResponse, user: AuthUser) {
+        let previous_refresh = self.session().and_then(|s| s.refresh_token);
+        *self.session.write().unwrap() = Some(Session {
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token.or(previous_refresh),
+            user,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer, RecordedRequest};
+
+    const LOGIN_RESPONSE: &str = r#"{
+        "access_token": "access-1",
+        "refresh_token": "refresh-1",
+        "expires_in": 900,
+        "user": {
+            "id": "emp-1",
+            "email": "hr@example.com",
+            "first_name": "Hanna",
+            "last_name": "Reyes",
+            "role": "Admin",
+            "department_id": null
+        }
+    }"#;
+
+    /// Stand-in backend: issues "access-1" on login and "access-2" on refresh,
+    /// and only accepts the token given in `valid_token` on data routes
+    fn auth_backend(
+        valid_token: &'static str,
+        refresh_ok: bool,
+    ) -> impl Fn(&RecordedRequest, usize) -> MockResponse {
+        move |req, _| match req.path.as_str() {
+            "/auth/login" if req.body.contains("secret") => MockResponse::new(200, LOGIN_RESPONSE),
+            "/auth/login" => MockResponse::new(401, r#"{"error":"invalid credentials"}"#),
+            "/auth/refresh" if refresh_ok => {
+                MockResponse::new(200, r#"{"access_token":"access-2","refresh_token":null}"#)
+            }
+            "/auth/refresh" => MockResponse::new(401, r#"{"error":"refresh token expired"}"#),
+            _ if req.header("authorization") == Some(&format!("Bearer {}", valid_token)) => {
+                MockResponse::new(200, "[]")
+            }
+            _ => MockResponse::new(401, ""),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_stores_session_and_attaches_bearer_token() {
+        let server = MockServer::start(auth_backend("access-1", true)).await;
+        let api = ApiClient::with_config(server.config());
+        assert!(!api.is_authenticated());
+
+        let user = api.login("hr@example.com", "secret").await.unwrap();
+        assert_eq!(user.role, "Admin");
+        assert!(api.clone().is_authenticated());
+
+        api.get_departments().await.unwrap();
+        let recorded = server.recorded();
+        assert_eq!(recorded[1].header("authorization"), Some("Bearer access-1"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_credentials() {
+        let server = MockServer::start(auth_backend("access-1", true)).await;
+        let api = ApiClient::with_config(server.config());
+
+        let err = api.login("hr@example.com", "wrong").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+        assert!(!api.is_authenticated());
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_triggers_refresh_and_retry() {
+        let server = MockServer::start(auth_backend("access-2", true)).await;
+        let api = ApiClient::with_config(server.config());
+        api.login("hr@example.com", "secret").await.unwrap();
+
+        api.get_employees(false).await.unwrap();
+
+        let session = api.session().unwrap();
+        assert_eq!(session.access_token, "access-2");
+        // The refresh response omitted the refresh token, so the old one is kept
+        assert_eq!(session.refresh_token.as_deref(), Some("refresh-1"));
+        let paths: Vec<String> = server.recorded().into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            paths,
+            ["/auth/login", "/employees", "/auth/refresh", "/employees"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_signs_out() {
+        let server = MockServer::start(auth_backend("access-2", false)).await;
+        let api = ApiClient::with_config(server.config());
+        api.login("hr@example.com", "secret").await.unwrap();
+
+        let err = api.get_salary_grades().await.unwrap_err();
+        assert!(err.is_unauthorized());
+        assert!(!api.is_authenticated());
+    }
+}
@@ -1,80 +1,220 @@
+use crate::api::auth::Session;
+use crate::api::error::ApiError;
 use crate::api::models::*;
+use crate::api::retry::RetryPolicy;
 use crate::config::Config;
-use reqwest::Client;
-use std::error::Error;
+use reqwest::header::IF_MATCH;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Body of a list endpoint that may or may not be paginated
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PageResponse<T> {
+    Page(Page<T>),
+    List(Vec<T>),
+}
 
 #[derive(Clone)]
 pub struct ApiClient {
-    client: Client,
+    pub(super) client: Client,
+    pub(super) config: Arc<Config>,
+    retry: RetryPolicy,
+    /// Shared by all clones so spawned tasks see logins and token refreshes
+    pub(super) session: Arc<RwLock<Option<Session>>>,
 }
 
 #[allow(dead_code)]
 impl ApiClient {
     pub fn new() -> Self {
+        Self::with_config(Config::get().clone())
+    }
+
+    /// Create a client for the given configuration, applying its request
+    /// timeout and retry settings
+    pub fn with_config(config: Config) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "Warning: Failed to build HTTP client: {}. Using defaults.",
+                    e
+                );
+                Client::new()
+            });
+        let retry = RetryPolicy::new(config.retry_attempts);
+
         Self {
-            client: Client::new(),
+            client,
+            config: Arc::new(config),
+            retry,
+            session: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Replace the retry policy derived from the configuration
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Send a request once with the session's bearer token. A 401 response
+    /// refreshes the token and repeats the request one time.
+    async fn send(&self, req: RequestBuilder) -> Result<Response, ApiError> {
+        let repeat = req.try_clone();
+        let token = self.access_token();
+        let resp = Self::authorize(req, token.as_deref()).send().await?;
+
+        let resp = match (resp.status(), repeat, token) {
+            (StatusCode::UNAUTHORIZED, Some(repeat), Some(token)) => {
+                self.recover_from_unauthorized(&token).await?;
+                Self::authorize(repeat, self.access_token().as_deref())
+                    .send()
+                    .await?
+            }
+            _ => resp,
+        };
+        if resp.status() == StatusCode::UNAUTHORIZED {
+            self.logout();
+        }
+        Self::check(resp).await
+    }
+
+    fn authorize(req: RequestBuilder, token: Option<&str>) -> RequestBuilder {
+        match token {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        }
+    }
+
+    /// Turn a non-success response into `ApiError::Status`
+    pub(super) async fn check(resp: Response) -> Result<Response, ApiError> {
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(resp);
+        }
+        let body = resp.text().await.unwrap_or_default();
+        Err(ApiError::from_status(status, body))
+    }
+
+    /// Send an idempotent request, retrying transient failures with
+    /// jittered exponential backoff
+    async fn send_with_retry(&self, req: RequestBuilder) -> Result<Response, ApiError> {
+        self.send_retrying(req, |e| self.retry.should_retry(e))
+            .await
+    }
+
+    /// Send an update, conditional on the record still having the
+    /// `updated_at` value `version` if set; the backend answers 409 or 412 if
+    /// it changed. A conditional update that may have reached the server is
+    /// not retried, as the retry would conflict with the update itself.
+    async fn send_update(
+        &self,
+        req: RequestBuilder,
+        version: Option<&str>,
+    ) -> Result<Response, ApiError> {
+        match version {
+            Some(version) => {
+                let req = req.header(IF_MATCH, format!("\"{}\"", version));
+                self.send_retrying(req, |e| self.retry.should_retry_unsent(e))
+                    .await
+            }
+            None => self.send_with_retry(req).await,
         }
     }
 
+    async fn send_retrying(
+        &self,
+        req: RequestBuilder,
+        should_retry: impl Fn(&ApiError) -> bool,
+    ) -> Result<Response, ApiError> {
+        let mut retry = 0;
+        loop {
+            let Some(attempt) = req.try_clone() else {
+                return self.send(req).await;
+            };
+            match self.send(attempt).await {
+                Err(e) if retry < self.retry.attempts && should_retry(&e) => {
+                    tokio::time::sleep(self.retry.delay_for(retry)).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Decode the JSON body of a successful response into `T`
+    pub(super) async fn decode<T: DeserializeOwned>(resp: Response) -> Result<T, ApiError> {
+        let bytes = resp.bytes().await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
     // Department endpoints
-    pub async fn get_departments(&self) -> Result<Vec<Department>, Box<dyn Error>> {
-        let config = Config::get();
+    pub async fn get_departments(&self) -> Result<Vec<Department>, ApiError> {
+        let config = &self.config;
         let url = format!("{}{}", config.api_url(), config.route_departments);
-        let resp = self.client.get(&url).send().await?;
-        let departments = resp.json::<Vec<Department>>().await?;
-        Ok(departments)
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        Self::decode(resp).await
     }
 
-    pub async fn create_department(
-        &self,
-        req: &CreateDepartmentRequest,
-    ) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+    pub async fn create_department(&self, req: &CreateDepartmentRequest) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}", config.api_url(), config.route_departments);
-        self.client.post(&url).json(req).send().await?;
+        self.send(self.client.post(&url).json(req)).await?;
         Ok(())
     }
 
+    pub async fn get_department(&self, id: &str) -> Result<Department, ApiError> {
+        let config = &self.config;
+        let url = format!("{}{}/{}", config.api_url(), config.route_departments, id);
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        Self::decode(resp).await
+    }
+
+    /// Update a department. With `version` set to the `updated_at` the edit
+    /// started from, the update fails with a conflict if it changed since.
     pub async fn update_department(
         &self,
         id: &str,
         req: &UpdateDepartmentRequest,
-    ) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+        version: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}/{}", config.api_url(), config.route_departments, id);
-        self.client.put(&url).json(req).send().await?;
+        self.send_update(self.client.put(&url).json(req), version)
+            .await?;
         Ok(())
     }
 
-    pub async fn delete_department(&self, id: &str) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+    pub async fn delete_department(&self, id: &str) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}/{}", config.api_url(), config.route_departments, id);
-        self.client.delete(&url).send().await?;
+        self.send_with_retry(self.client.delete(&url)).await?;
         Ok(())
     }
 
     pub async fn get_employees_by_department(
         &self,
         dept_id: &str,
-    ) -> Result<Vec<Employee>, Box<dyn Error>> {
-        let config = Config::get();
+    ) -> Result<Vec<Employee>, ApiError> {
+        let config = &self.config;
         let url = format!(
             "{}{}/{}/employees",
             config.api_url(),
             config.route_departments,
             dept_id
         );
-        let resp = self.client.get(&url).send().await?;
-        let employees = resp.json::<Vec<Employee>>().await?;
-        Ok(employees)
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        Self::decode(resp).await
     }
 
     // Employee endpoints
-    pub async fn get_employees(
-        &self,
-        include_inactive: bool,
-    ) -> Result<Vec<Employee>, Box<dyn Error>> {
-        let config = Config::get();
+    pub async fn get_employees(&self, include_inactive: bool) -> Result<Vec<Employee>, ApiError> {
+        let config = &self.config;
         let url = if include_inactive {
             format!(
                 "{}{}?include_inactive=true",
@@ -84,75 +224,311 @@ impl ApiClient {
         } else {
             format!("{}{}", config.api_url(), config.route_employees)
         };
-        let resp = self.client.get(&url).send().await?;
-        let employees = resp.json::<Vec<Employee>>().await?;
-        Ok(employees)
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        Self::decode(resp).await
     }
 
-    pub async fn create_employee(&self, req: &CreateEmployeeRequest) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+    /// Fetch one page of employees. Backends without pagination answer with
+    /// the full list, which is returned as a single page.
+    pub async fn get_employees_page(
+        &self,
+        page: usize,
+        limit: usize,
+        include_inactive: bool,
+    ) -> Result<Page<Employee>, ApiError> {
+        let config = &self.config;
+        let mut url = format!(
+            "{}{}?page={}&limit={}",
+            config.api_url(),
+            config.route_employees,
+            page,
+            limit
+        );
+        if include_inactive {
+            url.push_str("&include_inactive=true");
+        }
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        match Self::decode(resp).await? {
+            PageResponse::Page(page) => Ok(page),
+            PageResponse::List(items) => Ok(Page {
+                total: items.len(),
+                page: 1,
+                limit: items.len(),
+                items,
+            }),
+        }
+    }
+
+    pub async fn create_employee(&self, req: &CreateEmployeeRequest) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}", config.api_url(), config.route_employees);
-        self.client.post(&url).json(req).send().await?;
+        self.send(self.client.post(&url).json(req)).await?;
         Ok(())
     }
 
+    pub async fn get_employee(&self, id: &str) -> Result<Employee, ApiError> {
+        let config = &self.config;
+        let url = format!("{}{}/{}", config.api_url(), config.route_employees, id);
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        Self::decode(resp).await
+    }
+
+    /// Update an employee; `None` fields stay unchanged. `version` works
+    /// as for `update_department`.
     pub async fn update_employee(
         &self,
         id: &str,
         req: &UpdateEmployeeRequest,
-    ) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+        version: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}/{}", config.api_url(), config.route_employees, id);
-        let resp = self.client.put(&url).json(req).send().await?;
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(format!("API error {}: {}", status, body).into());
-        }
+        self.send_update(self.client.put(&url).json(req), version)
+            .await?;
         Ok(())
     }
 
-    pub async fn delete_employee(&self, id: &str) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+    pub async fn delete_employee(&self, id: &str) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}/{}", config.api_url(), config.route_employees, id);
-        self.client.delete(&url).send().await?;
+        self.send_with_retry(self.client.delete(&url)).await?;
         Ok(())
     }
 
     // Salary Grade endpoints
-    pub async fn get_salary_grades(&self) -> Result<Vec<SalaryGrade>, Box<dyn Error>> {
-        let config = Config::get();
+    pub async fn get_salary_grades(&self) -> Result<Vec<SalaryGrade>, ApiError> {
+        let config = &self.config;
         let url = format!("{}{}", config.api_url(), config.route_salary_grades);
-        let resp = self.client.get(&url).send().await?;
-        let grades = resp.json::<Vec<SalaryGrade>>().await?;
-        Ok(grades)
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        Self::decode(resp).await
     }
 
     pub async fn create_salary_grade(
         &self,
         req: &CreateSalaryGradeRequest,
-    ) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+    ) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}", config.api_url(), config.route_salary_grades);
-        self.client.post(&url).json(req).send().await?;
+        self.send(self.client.post(&url).json(req)).await?;
         Ok(())
     }
 
+    pub async fn get_salary_grade(&self, id: &str) -> Result<SalaryGrade, ApiError> {
+        let config = &self.config;
+        let url = format!("{}{}/{}", config.api_url(), config.route_salary_grades, id);
+        let resp = self.send_with_retry(self.client.get(&url)).await?;
+        Self::decode(resp).await
+    }
+
+    /// Update a salary grade, guarded by `version` like `update_department`
     pub async fn update_salary_grade(
         &self,
         id: &str,
         req: &UpdateSalaryGradeRequest,
-    ) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+        version: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}/{}", config.api_url(), config.route_salary_grades, id);
-        self.client.put(&url).json(req).send().await?;
+        self.send_update(self.client.put(&url).json(req), version)
+            .await?;
         Ok(())
     }
 
-    pub async fn delete_salary_grade(&self, id: &str) -> Result<(), Box<dyn Error>> {
-        let config = Config::get();
+    pub async fn delete_salary_grade(&self, id: &str) -> Result<(), ApiError> {
+        let config = &self.config;
         let url = format!("{}{}/{}", config.api_url(), config.route_salary_grades, id);
-        self.client.delete(&url).send().await?;
+        self.send_with_retry(self.client.delete(&url)).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+
+    fn fast_retry(attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            attempts,
+            base_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(20),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_retries_transient_failures() {
+        let server = MockServer::start(|_, call| match call {
+            0 | 1 => MockResponse::new(503, "busy"),
+            _ => MockResponse::new(200, "[]"),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config()).with_retry_policy(fast_retry(3));
+
+        let grades = api.get_salary_grades().await.unwrap();
+        assert!(grades.is_empty());
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retries_give_up_after_configured_attempts() {
+        let server = MockServer::start(|_, _| MockResponse::new(502, "")).await;
+        let api = ApiClient::with_config(server.config()).with_retry_policy(fast_retry(2));
+
+        let err = api.delete_employee("emp-1").await.unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::BAD_GATEWAY));
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_post_is_not_retried() {
+        let server = MockServer::start(|_, _| MockResponse::new(503, "")).await;
+        let api = ApiClient::with_config(server.config()).with_retry_policy(fast_retry(3));
+
+        let req = CreateDepartmentRequest {
+            name: "Sales".to_string(),
+            head_id: None,
+        };
+        assert!(api.create_department(&req).await.is_err());
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let server =
+            MockServer::start(|_, _| MockResponse::new(404, r#"{"error":"not found"}"#)).await;
+        let api = ApiClient::with_config(server.config()).with_retry_policy(fast_retry(3));
+
+        let err = api
+            .update_salary_grade("g-1", &UpdateSalaryGradeRequest::default(), None)
+            .await
+            .unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_applied() {
+        let server = MockServer::start(|_, _| {
+            MockResponse::new(200, "[]").delayed(Duration::from_millis(1500))
+        })
+        .await;
+        let config = Config {
+            request_timeout_secs: 1,
+            ..server.config()
+        };
+        let api = ApiClient::with_config(config).with_retry_policy(fast_retry(0));
+
+        let err = api.get_departments().await.unwrap_err();
+        assert!(matches!(err, ApiError::Timeout));
+    }
+
+    #[tokio::test]
+    async fn test_decode_error() {
+        let server = MockServer::start(|_, _| MockResponse::new(200, "{not json")).await;
+        let api = ApiClient::with_config(server.config());
+
+        let err = api.get_employees(false).await.unwrap_err();
+        assert!(matches!(err, ApiError::Decode(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_employees_page() {
+        let server = MockServer::start(|req, _| {
+            if req.path == "/employees?page=2&limit=1" {
+                MockResponse::new(
+                    200,
+                    r#"{"items":[{"id":"emp-2","first_name":"Alan","last_name":"Turing",
+                        "email":"alan@example.com","role":"Employee","active":true}],
+                        "total":3,"page":2,"limit":1}"#,
+                )
+            } else {
+                MockResponse::new(404, "")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+
+        let page = api.get_employees_page(2, 1, false).await.unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items[0].id, "emp-2");
+
+        let err = api.get_employees_page(2, 1, true).await.unwrap_err();
+        assert!(err.is_not_found());
+        assert!(server.recorded()[1]
+            .path
+            .ends_with("&include_inactive=true"));
+    }
+
+    #[tokio::test]
+    async fn test_unpaginated_response_is_a_single_page() {
+        let server = MockServer::start(|_, _| MockResponse::new(200, "[]")).await;
+        let api = ApiClient::with_config(server.config());
+
+        let page = api.get_employees_page(1, 50, false).await.unwrap();
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_update_sends_json_to_entity_route() {
+        let server = MockServer::start(|_, _| MockResponse::new(200, "{}")).await;
+        let api = ApiClient::with_config(server.config());
+
+        let req = UpdateEmployeeRequest {
+            active: Some(false),
+            ..Default::default()
+        };
+        api.update_employee("emp-7", &req, None).await.unwrap();
+
+        let recorded = server.recorded();
+        assert_eq!(recorded[0].method, "PUT");
+        assert_eq!(recorded[0].path, "/employees/emp-7");
+        assert_eq!(recorded[0].header("content-type"), Some("application/json"));
+        assert!(recorded[0].body.contains("\"active\":false"));
+        assert_eq!(recorded[0].header("if-match"), None);
+    }
+
+    #[tokio::test]
+    async fn test_update_sends_version_precondition() {
+        let server = MockServer::start(|req, _| match req.header("if-match") {
+            Some("\"2024-05-01T10:00:00Z\"") => MockResponse::new(200, "{}"),
+            _ => MockResponse::new(412, r#"{"error":"record was modified"}"#),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config()).with_retry_policy(fast_retry(3));
+        let req = UpdateDepartmentRequest::default();
+
+        api.update_department("d-1", &req, Some("2024-05-01T10:00:00Z"))
+            .await
+            .unwrap();
+        let err = api
+            .update_department("d-1", &req, Some("2024-04-01T08:00:00Z"))
+            .await
+            .unwrap_err();
+        assert!(err.is_edit_conflict());
+        // A stale version is not a transient failure
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_update_is_not_retried_after_timeout() {
+        let server = MockServer::start(|_, _| {
+            MockResponse::new(200, "{}").delayed(Duration::from_millis(1500))
+        })
+        .await;
+        let config = Config {
+            request_timeout_secs: 1,
+            ..server.config()
+        };
+        let api = ApiClient::with_config(config).with_retry_policy(fast_retry(3));
+        let req = UpdateDepartmentRequest::default();
+
+        let err = api
+            .update_department("d-1", &req, Some("2024-05-01T10:00:00Z"))
+            .await
+            .unwrap_err();
+        // The update may have been applied; a retry would report a conflict
+        assert!(matches!(err, ApiError::Timeout));
+        assert_eq!(server.request_count(), 1);
+    }
+}
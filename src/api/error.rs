@@ -0,0 +1,206 @@
+//! Error type returned by every `ApiClient` endpoint
+//!
+//! Distinguishes between failures that never reached the backend, responses
+//! with a non-success status, bodies that could not be decoded, and requests
+//! that timed out, so the GUI can react to each one differently.
+
+use reqwest::StatusCode;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request could not be sent or the connection failed
+    Transport(String),
+    /// The backend answered with a non-success status code
+    Status {
+        status: StatusCode,
+        /// Human readable message extracted from the response body
+        message: String,
+        /// Raw response body as returned by the backend
+        body: String,
+    },
+    /// The response body did not match the expected model
+    Decode(String),
+    /// The request did not complete in time
+    Timeout,
+}
+
+#[allow(dead_code)]
+impl ApiError {
+    /// Build a `Status` error from a response status and its raw body.
+    ///
+    /// If the body is JSON with an `error`, `message` or `detail` field, that
+    /// field is used as the message; otherwise the trimmed body is used.
+    pub fn from_status(status: StatusCode, body: String) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|value| {
+                ["error", "message", "detail"].iter().find_map(|key| {
+                    value
+                        .get(*key)
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                })
+            })
+            .unwrap_or_else(|| body.trim().to_string());
+
+        ApiError::Status {
+            status,
+            message,
+            body,
+        }
+    }
+
+    /// The HTTP status code, if the backend answered at all
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// The raw response body, if the backend answered with one
+    pub fn body(&self) -> Option<&str> {
+        match self {
+            ApiError::Status { body, .. } if !body.is_empty() => Some(body),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// True for 400 and 422 responses, i.e. the request data was rejected
+    pub fn is_validation(&self) -> bool {
+        matches!(
+            self.status(),
+            Some(StatusCode::BAD_REQUEST) | Some(StatusCode::UNPROCESSABLE_ENTITY)
+        )
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == Some(StatusCode::UNAUTHORIZED)
+    }
+
+    pub fn is_conflict(&self) -> bool {
+        self.status() == Some(StatusCode::CONFLICT)
+    }
+
+    /// True if a conditional request failed because the record was changed
+    /// by someone else (409 Conflict or 412 Precondition Failed)
+    pub fn is_edit_conflict(&self) -> bool {
+        matches!(
+            self.status(),
+            Some(StatusCode::CONFLICT) | Some(StatusCode::PRECONDITION_FAILED)
+        )
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        self.status().is_some_and(|s| s.is_server_error())
+    }
+
+    /// True if the backend could not be reached or did not answer in time
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, ApiError::Transport(_) | ApiError::Timeout)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Transport(msg) => write!(f, "Could not reach server: {}", msg),
+            ApiError::Status {
+                status, message, ..
+            } => {
+                let reason = status.canonical_reason().unwrap_or("Unknown status");
+                if message.is_empty() {
+                    write!(f, "API error {} {}", status.as_u16(), reason)
+                } else {
+                    write!(f, "API error {} {}: {}", status.as_u16(), reason, message)
+                }
+            }
+            ApiError::Decode(msg) => write!(f, "Invalid response from server: {}", msg),
+            ApiError::Timeout => write!(f, "Request timed out"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            ApiError::Timeout
+        } else if e.is_decode() {
+            ApiError::Decode(e.to_string())
+        } else if let Some(status) = e.status() {
+            ApiError::from_status(status, String::new())
+        } else {
+            ApiError::Transport(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(e: serde_json::Error) -> Self {
+        ApiError::Decode(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status_extracts_json_message() {
+        let err = ApiError::from_status(
+            StatusCode::BAD_REQUEST,
+            r#"{"error":"email already exists"}"#.to_string(),
+        );
+        match &err {
+            ApiError::Status { message, .. } => assert_eq!(message, "email already exists"),
+            _ => panic!("expected Status variant"),
+        }
+        assert!(err.is_validation());
+        assert!(!err.is_not_found());
+    }
+
+    #[test]
+    fn test_from_status_falls_back_to_plain_body() {
+        let err = ApiError::from_status(StatusCode::NOT_FOUND, "  not here \n".to_string());
+        match &err {
+            ApiError::Status { message, body, .. } => {
+                assert_eq!(message, "not here");
+                assert_eq!(body, "  not here \n");
+            }
+            _ => panic!("expected Status variant"),
+        }
+        assert!(err.is_not_found());
+    }
+
+    #[test]
+    fn test_status_classification() {
+        let err = ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, String::new());
+        assert!(err.is_server_error());
+        assert!(!err.is_unreachable());
+        assert!(ApiError::from_status(StatusCode::CONFLICT, String::new()).is_conflict());
+        assert!(
+            ApiError::from_status(StatusCode::PRECONDITION_FAILED, String::new())
+                .is_edit_conflict()
+        );
+        assert!(!ApiError::from_status(StatusCode::BAD_REQUEST, String::new()).is_edit_conflict());
+        assert!(ApiError::Timeout.is_unreachable());
+        assert!(ApiError::Transport("refused".to_string()).is_unreachable());
+        assert_eq!(ApiError::Timeout.status(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let err = ApiError::from_status(StatusCode::NOT_FOUND, "missing".to_string());
+        assert_eq!(err.to_string(), "API error 404 Not Found: missing");
+        let err = ApiError::from_status(StatusCode::NOT_FOUND, String::new());
+        assert_eq!(err.to_string(), "API error 404 Not Found");
+        assert_eq!(ApiError::Timeout.to_string(), "Request timed out");
+    }
+}
@@ -0,0 +1,163 @@
+//! Minimal HTTP/1.1 stand-in for the backend, used by the API client tests
+//!
+//! Each connection serves exactly one request and is then closed. The handler
+//! receives the parsed request together with a zero-based call counter so tests
+//! can script sequences such as "fail twice, then succeed".
+
+use crate::config::Config;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+type Handler = dyn Fn(&RecordedRequest, usize) -> MockResponse + Send + Sync;
+
+pub struct MockServer {
+    pub base_url: String,
+    pub requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Start the server on a random local port in the current tokio runtime
+    pub async fn start(
+        handler: impl Fn(&RecordedRequest, usize) -> MockResponse + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut socket).await else {
+                        return;
+                    };
+                    let call = {
+                        let mut recorded = recorded.lock().unwrap();
+                        recorded.push(request.clone());
+                        recorded.len() - 1
+                    };
+                    let response = handler(&request, call);
+                    tokio::time::sleep(response.delay).await;
+                    let raw = format!(
+                        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        response.status,
+                        response.body.len(),
+                        response.body
+                    );
+                    let _ = socket.write_all(raw.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    /// A configuration pointing at this server with no API prefix
+    pub fn config(&self) -> Config {
+        Config {
+            api_base_url: self.base_url.clone(),
+            api_prefix: String::new(),
+            ..Config::default()
+        }
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    pub fn recorded(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<RecordedRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
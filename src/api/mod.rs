@@ -1,2 +1,7 @@
+pub mod auth;
 pub mod client;
+pub mod error;
+#[cfg(test)]
+pub mod mock_server;
 pub mod models;
+pub mod retry;
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Department {
@@ -15,10 +15,28 @@ pub struct CreateDepartmentRequest {
     pub head_id: Option<String>,
 }
 
+/// Partial update: `None` fields are left out and stay unchanged on the
+/// server, `Some(None)` clears a reference.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateDepartmentRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
-    pub head_id: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub head_id: Option<Option<String>>,
+}
+
+/// Keeps an explicit `null` apart from a missing field: `null` becomes
+/// `Some(None)`, a missing field `None` via `#[serde(default)]`
+fn clearable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -33,11 +51,31 @@ pub struct Employee {
     pub manager_id: Option<String>,
     pub salary_grade_id: Option<String>,
     pub hire_date: Option<String>,
+    /// Last working day of a deactivated employee (YYYY-MM-DD)
+    #[serde(default)]
+    pub leave_date: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
     pub deleted_at: Option<String>,
 }
 
+impl Employee {
+    /// Neither deactivated nor soft-deleted
+    pub fn is_current(&self) -> bool {
+        self.active && self.deleted_at.is_none()
+    }
+}
+
+/// One page of a paginated list endpoint. `page` is 1-based and `total`
+/// counts all records, not just the ones in `items`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CreateEmployeeRequest {
     pub first_name: String,
@@ -50,17 +88,49 @@ pub struct CreateEmployeeRequest {
     pub hire_date: Option<String>,
 }
 
+/// Partial update, see `UpdateDepartmentRequest`
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateEmployeeRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub first_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub active: Option<bool>,
-    pub department_id: Option<String>,
-    pub manager_id: Option<String>,
-    pub salary_grade_id: Option<String>,
-    pub hire_date: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub department_id: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub manager_id: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub salary_grade_id: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hire_date: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub leave_date: Option<Option<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -70,6 +140,7 @@ pub struct SalaryGrade {
     pub base_salary: f64,
     pub description: Option<String>,
     pub created_at: Option<String>,
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -79,9 +150,47 @@ pub struct CreateSalaryGradeRequest {
     pub description: Option<String>,
 }
 
+/// Partial update, see `UpdateDepartmentRequest`
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateSalaryGradeRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub base_salary: Option<f64>,
-    pub description: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub description: Option<Option<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// The signed-in user as reported by the auth endpoints
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AuthUser {
+    pub id: String,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    pub department_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_in: Option<u64>,
+    pub user: Option<AuthUser>,
 }
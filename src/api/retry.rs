@@ -0,0 +1,133 @@
+//! Retry policy with jittered exponential backoff for idempotent requests
+
+use crate::api::error::ApiError;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt
+    pub attempts: u32,
+    /// Delay before the first retry, doubled for every following retry
+    pub base_delay: Duration,
+    /// Upper bound for a single delay
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(attempts: u32) -> Self {
+        Self {
+            attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 0).
+    ///
+    /// The exponential delay is capped at `max_delay`, then a random value in
+    /// the upper half of it is picked so that clients do not retry in lockstep.
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let half = exp / 2;
+        half + half.mul_f64(jitter())
+    }
+
+    /// Whether a failed request should be attempted again
+    pub fn should_retry(&self, error: &ApiError) -> bool {
+        match error {
+            ApiError::Transport(_) | ApiError::Timeout => true,
+            ApiError::Status { status, .. } => matches!(
+                *status,
+                StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            ApiError::Decode(_) => false,
+        }
+    }
+
+    /// Whether to retry a request that must not run twice: only when the
+    /// server reported it did not handle it
+    pub fn should_retry_unsent(&self, error: &ApiError) -> bool {
+        matches!(
+            error,
+            ApiError::Status { status, .. } if *status == StatusCode::SERVICE_UNAVAILABLE
+        )
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Random factor in `[0, 1)` without pulling in a RNG dependency
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially_within_jitter_bounds() {
+        let policy = RetryPolicy {
+            attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        };
+        for retry in 0..4 {
+            let full = Duration::from_millis(100 * 2u64.pow(retry));
+            let delay = policy.delay_for(retry);
+            assert!(delay >= full / 2, "retry {}: {:?} too short", retry, delay);
+            assert!(delay <= full, "retry {}: {:?} too long", retry, delay);
+        }
+    }
+
+    #[test]
+    fn test_delay_is_capped() {
+        let policy = RetryPolicy {
+            attempts: 50,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+        assert!(policy.delay_for(40) <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(&ApiError::Timeout));
+        assert!(policy.should_retry(&ApiError::Transport("reset".to_string())));
+        assert!(policy.should_retry(&ApiError::from_status(
+            StatusCode::SERVICE_UNAVAILABLE,
+            String::new()
+        )));
+        assert!(!policy.should_retry(&ApiError::from_status(StatusCode::NOT_FOUND, String::new())));
+        assert!(!policy.should_retry(&ApiError::Decode("bad json".to_string())));
+        assert!(!policy.should_retry_unsent(&ApiError::Timeout));
+        assert!(!policy.should_retry_unsent(&ApiError::Transport("reset".to_string())));
+        assert!(policy.should_retry_unsent(&ApiError::from_status(
+            StatusCode::SERVICE_UNAVAILABLE,
+            String::new()
+        )));
+    }
+}
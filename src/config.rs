@@ -9,6 +9,11 @@ pub struct Config {
     pub route_departments: String,
     pub route_employees: String,
     pub route_salary_grades: String,
+    pub route_auth_login: String,
+    pub route_auth_refresh: String,
+    pub request_timeout_secs: u64,
+    pub retry_attempts: u32,
+    pub ui_settings_path: String,
 }
 
 impl Config {
@@ -25,6 +30,17 @@ impl Config {
                 .unwrap_or_else(|_| "/employees".to_string()),
             route_salary_grades: std::env::var("ROUTE_SALARY_GRADES")
                 .unwrap_or_else(|_| "/salary-grades".to_string()),
+            route_auth_login: std::env::var("ROUTE_AUTH_LOGIN")
+                .unwrap_or_else(|_| "/auth/login".to_string()),
+            route_auth_refresh: std::env::var("ROUTE_AUTH_REFRESH")
+                .unwrap_or_else(|_| "/auth/refresh".to_string()),
+            request_timeout_secs: timeout_secs(std::env::var("REQUEST_TIMEOUT_SECS").ok()),
+            retry_attempts: std::env::var("RETRY_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+            ui_settings_path: std::env::var("UI_SETTINGS_FILE")
+                .unwrap_or_else(|_| "ui_settings.json".to_string()),
         })
     }
 
@@ -50,6 +66,33 @@ impl Default for Config {
             route_departments: "/departments".to_string(),
             route_employees: "/employees".to_string(),
             route_salary_grades: "/salary-grades".to_string(),
+            route_auth_login: "/auth/login".to_string(),
+            route_auth_refresh: "/auth/refresh".to_string(),
+            request_timeout_secs: 30,
+            retry_attempts: 3,
+            ui_settings_path: "ui_settings.json".to_string(),
         }
     }
 }
+
+/// The request timeout in seconds; a zero timeout would fail every request,
+/// so it falls back to the default like an unparsable value
+fn timeout_secs(value: Option<String>) -> u64 {
+    value
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(30)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_secs_rejects_zero() {
+        assert_eq!(timeout_secs(Some("10".to_string())), 10);
+        assert_eq!(timeout_secs(Some("0".to_string())), 30);
+        assert_eq!(timeout_secs(Some("soon".to_string())), 30);
+        assert_eq!(timeout_secs(None), 30);
+    }
+}
@@ -1,36 +1,73 @@
 //! Main application struct and eframe::App implementation
 
+use super::bulk::{BulkKind, BulkReport};
+use super::conflicts::EditConflict;
+use super::department_removal::DepartmentRemoval;
 use super::dialogs::{ConfirmAction, EditDialog};
-use super::{Material3Colors, Tab};
+use super::drafts::{Draft, DraftViews};
+use super::grade_removal::GradeRemoval;
+use super::history::SharedHistory;
+use super::login::LoginState;
+use super::offboarding::Offboarding;
+use super::pagination::Pager;
+use super::permissions::Permissions;
+use super::settings::UiSettings;
+use super::store::Store;
+use super::views::dashboard::{DashboardData, SharedDashboard};
+use super::views::department_detail::MembersSlot;
+use super::views::org_chart::OrgChartState;
+use super::{Material3Colors, Notifier, RequestTracker, Tab};
 use crate::api::client::ApiClient;
 use crate::api::models::*;
+use crate::config::Config;
 use egui::{Color32, Frame, Margin, Rounding, Stroke, Vec2};
+use std::collections::BTreeSet;
+use std::future::Future;
 use std::sync::{Arc, Mutex};
 
 /// Main application state for Personnel Management
 #[allow(dead_code)]
 pub struct PersonnelApp {
     pub runtime: tokio::runtime::Runtime,
+    /// Used by background tasks to request a repaint once they finish
+    pub egui_ctx: egui::Context,
     pub api: ApiClient,
     pub colors: Material3Colors,
     pub dark_mode: bool,
     pub current_tab: Tab,
 
+    // Login state
+    pub signed_in: bool,
+    pub login_email: String,
+    pub login_password: String,
+    pub login_state: Arc<Mutex<LoginState>>,
+
     // Confirmation dialog state
     pub confirm_dialog: Option<ConfirmAction>,
+    /// Text typed into the confirmation dialog (leave date, typed name)
+    pub confirm_input: String,
 
     // Edit/Create dialog state
     pub edit_dialog: Option<EditDialog>,
 
+    // Update rejected because the record changed on the server
+    pub edit_conflict: Arc<Mutex<Option<EditConflict>>>,
+
     // Department state
-    pub departments: Arc<Mutex<Vec<Department>>>,
+    pub departments: Store<Department>,
     pub dept_name: String,
     pub dept_head_id: String,
     pub selected_dept: Option<usize>,
-    pub dept_loading: bool,
+    pub dept_status: RequestTracker,
+    /// Department deletion wizard in progress and the staff it waits for
+    pub dept_removal: Option<DepartmentRemoval>,
+    pub dept_removal_staff: Arc<Mutex<Option<Vec<Employee>>>>,
+    /// Department whose detail page is open, and its fetched members
+    pub dept_detail: Option<String>,
+    pub dept_detail_members: MembersSlot,
 
     // Employee state
-    pub employees: Arc<Mutex<Vec<Employee>>>,
+    pub employees: Store<Employee>,
     pub emp_first_name: String,
     pub emp_last_name: String,
     pub emp_email: String,
@@ -38,61 +75,93 @@ pub struct PersonnelApp {
     pub emp_dept_id: String,
     pub emp_manager_id: String,
     pub emp_salary_grade_id: String,
-    pub selected_emp: Option<usize>,
-    pub emp_loading: bool,
+    /// Employee shown in the detail side panel
+    pub selected_emp: Option<String>,
+    pub emp_search: String,
+    pub emp_status: RequestTracker,
+    pub emp_pager: Pager,
+    /// Also list deactivated and soft-deleted employees
+    pub emp_include_inactive: bool,
+    /// Ids of the employees ticked for a bulk action
+    pub bulk_selection: BTreeSet<String>,
+    pub bulk_kind: BulkKind,
+    pub bulk_value: String,
+    pub bulk_report: Arc<Mutex<Option<BulkReport>>>,
+    /// Offboarding wizard in progress and the employee list it waits for
+    pub offboarding: Option<Offboarding>,
+    pub offboarding_staff: Arc<Mutex<Option<Vec<Employee>>>>,
 
     // Salary Grade state
-    pub salary_grades: Arc<Mutex<Vec<SalaryGrade>>>,
+    pub salary_grades: Store<SalaryGrade>,
     pub grade_code: String,
     pub grade_salary: String,
     pub grade_desc: String,
     pub selected_grade: Option<usize>,
-    pub grade_loading: bool,
+    pub grade_status: RequestTracker,
+    /// Salary grade deletion in progress and the employees it waits for
+    pub grade_removal: Option<GradeRemoval>,
+    pub grade_removal_staff: Arc<Mutex<Option<Vec<Employee>>>>,
+
+    // Employees the dashboard figures are computed from
+    pub dashboard: SharedDashboard,
+
+    // Pan, zoom and folding of the org chart
+    pub org_chart: OrgChartState,
 
-    pub error_message: Option<String>,
+    // Snackbar notifications fed by background tasks
+    pub notifier: Notifier,
+
+    // Applied changes that can be undone and redone
+    pub history: SharedHistory,
+
+    // Changes staged locally in draft mode, and what a commit left over
+    pub draft: Option<Draft>,
+    pub draft_review_open: bool,
+    pub logout_confirm: bool,
+    pub draft_leftovers: Arc<Mutex<Option<Vec<super::history::Change>>>>,
+    pub draft_views: Mutex<DraftViews>,
+
+    // Preferences saved between sessions
+    pub ui_settings: UiSettings,
+    pub settings_dirty: bool,
 }
 
 impl PersonnelApp {
-    /// Create a new PersonnelApp instance and load initial data
+    /// Create a new PersonnelApp instance
     pub fn new() -> Self {
         let runtime = tokio::runtime::Runtime::new().unwrap();
         let api = ApiClient::new();
 
-        let departments = Arc::new(Mutex::new(Vec::new()));
-        let employees = Arc::new(Mutex::new(Vec::new()));
-        let salary_grades = Arc::new(Mutex::new(Vec::new()));
-
-        // Load initial data
-        let api_clone = api.clone();
-        let depts = departments.clone();
-        let emps = employees.clone();
-        let grades = salary_grades.clone();
-
-        runtime.spawn(async move {
-            if let Ok(data) = api_clone.get_departments().await {
-                *depts.lock().unwrap() = data;
-            }
-            if let Ok(data) = api_clone.get_employees(false).await {
-                *emps.lock().unwrap() = data;
-            }
-            if let Ok(data) = api_clone.get_salary_grades().await {
-                *grades.lock().unwrap() = data;
-            }
-        });
+        let departments = Store::new();
+        let employees = Store::new();
+        let salary_grades = Store::new();
+        let notifier = Notifier::new();
 
+        // Data is loaded once the user has signed in, see `sync_session`
         Self {
             runtime,
+            egui_ctx: egui::Context::default(),
             api,
             colors: Material3Colors::dark(),
             dark_mode: true,
             current_tab: Tab::Departments,
+            signed_in: false,
+            login_email: String::new(),
+            login_password: String::new(),
+            login_state: Arc::new(Mutex::new(LoginState::Idle)),
             confirm_dialog: None,
+            confirm_input: String::new(),
             edit_dialog: None,
+            edit_conflict: Arc::new(Mutex::new(None)),
             departments,
             dept_name: String::new(),
             dept_head_id: String::new(),
             selected_dept: None,
-            dept_loading: false,
+            dept_status: RequestTracker::new(),
+            dept_removal: None,
+            dept_detail: None,
+            dept_detail_members: Arc::new(Mutex::new(None)),
+            dept_removal_staff: Arc::new(Mutex::new(None)),
             employees,
             emp_first_name: String::new(),
             emp_last_name: String::new(),
@@ -102,36 +171,126 @@ impl PersonnelApp {
             emp_manager_id: String::new(),
             emp_salary_grade_id: String::new(),
             selected_emp: None,
-            emp_loading: false,
+            emp_search: String::new(),
+            emp_status: RequestTracker::new(),
+            emp_pager: Pager::default(),
+            emp_include_inactive: false,
+            bulk_selection: BTreeSet::new(),
+            bulk_kind: BulkKind::default(),
+            bulk_value: String::new(),
+            bulk_report: Arc::new(Mutex::new(None)),
+            offboarding: None,
+            offboarding_staff: Arc::new(Mutex::new(None)),
             salary_grades,
             grade_code: String::new(),
             grade_salary: String::new(),
             grade_desc: String::new(),
             selected_grade: None,
-            grade_loading: false,
-            error_message: None,
+            grade_status: RequestTracker::new(),
+            grade_removal: None,
+            grade_removal_staff: Arc::new(Mutex::new(None)),
+            dashboard: SharedDashboard::default(),
+            org_chart: OrgChartState::default(),
+            notifier,
+            history: SharedHistory::default(),
+            draft: None,
+            draft_review_open: false,
+            logout_confirm: false,
+            draft_leftovers: Arc::new(Mutex::new(None)),
+            draft_views: Mutex::new(DraftViews::default()),
+            ui_settings: UiSettings::load(&Config::get().ui_settings_path),
+            settings_dirty: false,
+        }
+    }
+
+    /// Attach the context of the running UI, so finished tasks wake it up
+    pub fn with_egui_context(mut self, ctx: egui::Context) -> Self {
+        self.egui_ctx = ctx;
+        self
+    }
+
+    /// Run a background task and repaint once it finished. The UI does not
+    /// poll, so anything that changes shared state must go through here.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let ctx = self.egui_ctx.clone();
+        self.runtime.spawn(async move {
+            task.await;
+            ctx.request_repaint();
+        });
+    }
+
+    /// Permissions of the signed-in user
+    pub fn permissions(&self) -> Permissions {
+        self.api
+            .current_user()
+            .map(|user| Permissions::for_user(&user))
+            .unwrap_or_default()
+    }
+
+    /// Write changed preferences to disk. Waits until the pointer is released,
+    /// so dragging a column edge does not write on every frame.
+    fn save_ui_settings(&mut self, ctx: &egui::Context) {
+        if !self.settings_dirty || ctx.input(|i| i.pointer.any_down()) {
+            return;
         }
+        self.settings_dirty = false;
+        if let Err(e) = self.ui_settings.save(&Config::get().ui_settings_path) {
+            self.notifier
+                .warning(format!("Could not save the UI settings: {}", e));
+        }
+    }
+
+    /// Refresh all collections from the API
+    pub fn refresh_all(&mut self) {
+        self.refresh_departments();
+        self.refresh_employees();
+        self.refresh_salary_grades();
     }
 
     /// Refresh departments from the API
     pub fn refresh_departments(&mut self) {
         let api = self.api.clone();
         let depts = self.departments.clone();
-        self.runtime.spawn(async move {
-            if let Ok(data) = api.get_departments().await {
-                *depts.lock().unwrap() = data;
-            }
+        let notify = self.notifier.clone();
+        let status = self.dept_status.clone();
+        status.start_loading();
+        self.spawn(async move {
+            status
+                .load_into(&depts, &notify, "departments", api.get_departments())
+                .await;
         });
     }
 
-    /// Refresh employees from the API
+    /// Refresh employees from the API, starting again at the first page
     pub fn refresh_employees(&mut self) {
+        self.emp_pager.reset();
+        self.spawn_employee_page();
+    }
+
+    /// Load the next page of employees unless one is already loading
+    pub fn load_more_employees(&mut self) {
+        if !self.emp_status.is_loading() && self.emp_pager.has_more() {
+            self.spawn_employee_page();
+        }
+    }
+
+    fn spawn_employee_page(&mut self) {
         let api = self.api.clone();
         let emps = self.employees.clone();
-        self.runtime.spawn(async move {
-            if let Ok(data) = api.get_employees(false).await {
-                *emps.lock().unwrap() = data;
-            }
+        let notify = self.notifier.clone();
+        let status = self.emp_status.clone();
+        let pager = self.emp_pager.clone();
+        let include_inactive = self.emp_include_inactive;
+        status.start_loading();
+        self.spawn(async move {
+            pager
+                .load_next(&emps, &status, &notify, "employees", |page, limit| {
+                    api.get_employees_page(page, limit, include_inactive)
+                })
+                .await;
         });
     }
 
@@ -139,10 +298,13 @@ impl PersonnelApp {
     pub fn refresh_salary_grades(&mut self) {
         let api = self.api.clone();
         let grades = self.salary_grades.clone();
-        self.runtime.spawn(async move {
-            if let Ok(data) = api.get_salary_grades().await {
-                *grades.lock().unwrap() = data;
-            }
+        let notify = self.notifier.clone();
+        let status = self.grade_status.clone();
+        status.start_loading();
+        self.spawn(async move {
+            status
+                .load_into(&grades, &notify, "salary grades", api.get_salary_grades())
+                .await;
         });
     }
 }
@@ -179,6 +341,13 @@ impl eframe::App for PersonnelApp {
             style.visuals.widgets.active.fg_stroke = Stroke::new(1.0, colors.on_primary);
         });
 
+        self.sync_session();
+        if !self.signed_in {
+            self.show_login(ctx);
+            self.show_notifications(ctx);
+            return;
+        }
+
         // Top navigation bar
         egui::TopBottomPanel::top("top_panel")
             .frame(
@@ -192,9 +361,11 @@ impl eframe::App for PersonnelApp {
 
                     // Tab buttons with pill style and icons
                     for (tab, icon, label) in [
+                        (Tab::Dashboard, "📊", "Dashboard"),
                         (Tab::Departments, "🏢", "Departments"),
                         (Tab::Employees, "👥", "Employees"),
                         (Tab::SalaryGrades, "💰", "Salary Grades"),
+                        (Tab::OrgChart, "🌳", "Org Chart"),
                     ] {
                         let is_selected = self.current_tab == tab;
                         let (bg, text_color) = if is_selected {
@@ -214,6 +385,10 @@ impl eframe::App for PersonnelApp {
                         .min_size(Vec2::new(0.0, 40.0));
 
                         if ui.add(button).clicked() {
+                            if tab == Tab::Dashboard && self.current_tab != tab {
+                                // Figures are fetched again on every visit
+                                *self.dashboard.lock().unwrap() = DashboardData::Stale;
+                            }
                             self.current_tab = tab;
                         }
                     }
@@ -244,10 +419,53 @@ impl eframe::App for PersonnelApp {
                                 Material3Colors::light()
                             };
                         }
+
+                        ui.add_space(8.0);
+                        self.show_history_buttons(ui);
+                        ui.add_space(8.0);
+                        self.show_draft_button(ui);
+                        ui.add_space(8.0);
+
+                        let logout_btn = egui::Button::new(
+                            egui::RichText::new("⎋ Sign out")
+                                .size(13.0)
+                                .color(colors.on_surface),
+                        )
+                        .fill(colors.surface_variant)
+                        .stroke(Stroke::new(1.0, colors.outline_variant))
+                        .rounding(Rounding::same(20.0))
+                        .min_size(Vec2::new(0.0, 40.0));
+
+                        if ui.add(logout_btn).clicked() {
+                            if self.draft.as_ref().is_some_and(|d| !d.is_empty()) {
+                                self.logout_confirm = true;
+                            } else {
+                                self.logout();
+                            }
+                        }
+
+                        if let Some(user) = self.api.current_user() {
+                            let name = format!("{} {}", user.first_name, user.last_name);
+                            let display = if name.trim().is_empty() {
+                                user.email.clone()
+                            } else {
+                                name
+                            };
+                            ui.add_space(8.0);
+                            ui.label(
+                                egui::RichText::new(format!("👤 {}", display))
+                                    .size(13.0)
+                                    .color(colors.on_surface_variant),
+                            )
+                            .on_hover_text(&user.role);
+                        }
                     });
                 });
             });
 
+        // Detail panel of the selected employee, beside the content
+        self.show_employee_detail(ctx);
+
         // Main content area
         egui::CentralPanel::default()
             .frame(
@@ -260,33 +478,46 @@ impl eframe::App for PersonnelApp {
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
                         ui.spacing_mut().item_spacing.y = 12.0;
+                        self.show_draft_banner(ui);
 
                         match self.current_tab {
                             Tab::Departments => self.show_departments(ui),
                             Tab::Employees => self.show_employees(ui),
                             Tab::SalaryGrades => self.show_salary_grades(ui),
-                        }
-
-                        if let Some(ref error) = self.error_message {
-                            ui.add_space(16.0);
-                            Frame::none()
-                                .fill(Color32::from_rgb(50, 30, 30))
-                                .rounding(Rounding::same(8.0))
-                                .inner_margin(Margin::same(12.0))
-                                .show(ui, |ui| {
-                                    ui.colored_label(colors.error, format!("⚠ {}", error));
-                                });
+                            Tab::OrgChart => self.show_org_chart(ui),
+                            Tab::Dashboard => self.show_dashboard(ui),
                         }
                     });
             });
 
+        // Ctrl+Z / Ctrl+Shift+Z
+        self.handle_history_shortcuts(ctx);
+
         // Render confirmation dialog if active
         self.show_confirm_dialog(ctx);
 
         // Render edit/create dialog if active
         self.show_edit_dialog(ctx);
 
-        ctx.request_repaint();
+        // Render conflict dialog if an update was rejected
+        self.show_conflict_dialog(ctx);
+
+        // Render the offboarding and deletion wizards if active
+        self.show_offboarding(ctx);
+        self.show_department_removal(ctx);
+        self.show_grade_removal(ctx);
+
+        // Render the review of staged draft changes
+        self.show_draft_review(ctx);
+        self.show_logout_confirm(ctx);
+
+        // Render the outcome of a finished bulk action
+        self.show_bulk_report(ctx);
+
+        // Render snackbars on top of everything else
+        self.show_notifications(ctx);
+
+        self.save_ui_settings(ctx);
     }
 }
 
@@ -299,7 +530,16 @@ mod tests {
         let app = PersonnelApp::new();
         assert_eq!(app.current_tab, Tab::Departments);
         assert!(app.dept_name.is_empty());
-        assert!(app.employees.lock().unwrap().is_empty());
+        assert!(app.employees.is_empty());
+    }
+
+    #[test]
+    fn test_app_starts_signed_out() {
+        let mut app = PersonnelApp::new();
+        assert!(!app.signed_in);
+        app.sync_session();
+        assert!(!app.signed_in);
+        assert_eq!(*app.login_state.lock().unwrap(), LoginState::Idle);
     }
 
     #[test]
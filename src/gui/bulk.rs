@@ -0,0 +1,512 @@
+//! Bulk changes to several employees at once
+//!
+//! The Employees view keeps a set of selected employee ids. A bulk action
+//! applies one change to all of them after a single confirmation. Every
+//! employee is updated with its own request, so some may fail while others
+//! succeed; the outcome per employee is collected into a `BulkReport` and
+//! shown once the run finished.
+
+use super::hierarchy::{complete_staff, Hierarchy};
+use super::permissions::Permissions;
+use super::PersonnelApp;
+use crate::api::client::ApiClient;
+use crate::api::models::*;
+use egui::{Context, Frame, Margin, RichText, Rounding, Stroke, Vec2};
+use std::collections::HashSet;
+
+/// Which field the bulk-action bar is about to change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BulkKind {
+    #[default]
+    Department,
+    SalaryGrade,
+    Manager,
+    Role,
+    Deactivate,
+}
+
+impl BulkKind {
+    pub const ALL: [BulkKind; 5] = [
+        BulkKind::Department,
+        BulkKind::SalaryGrade,
+        BulkKind::Manager,
+        BulkKind::Role,
+        BulkKind::Deactivate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BulkKind::Department => "Move to department",
+            BulkKind::SalaryGrade => "Set salary grade",
+            BulkKind::Manager => "Set manager",
+            BulkKind::Role => "Change role",
+            BulkKind::Deactivate => "Deactivate",
+        }
+    }
+}
+
+/// A change applied to every selected employee. The ids are the chosen
+/// target records, the names are only used for display.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction {
+    MoveToDepartment { id: String, name: String },
+    SetSalaryGrade { id: String, code: String },
+    SetManager { id: String, name: String },
+    ChangeRole { role: String },
+    Deactivate,
+}
+
+impl BulkAction {
+    pub fn describe(&self) -> String {
+        match self {
+            BulkAction::MoveToDepartment { name, .. } => format!("Move to department \"{}\"", name),
+            BulkAction::SetSalaryGrade { code, .. } => format!("Set salary grade \"{}\"", code),
+            BulkAction::SetManager { name, .. } => format!("Set manager to \"{}\"", name),
+            BulkAction::ChangeRole { role } => format!("Change role to \"{}\"", role),
+            BulkAction::Deactivate => "Deactivate".to_string(),
+        }
+    }
+
+    /// The update sent for each employee; fields left `None` are unchanged
+    pub fn request(&self) -> UpdateEmployeeRequest {
+        let mut req = UpdateEmployeeRequest::default();
+        match self {
+            BulkAction::MoveToDepartment { id, .. } => req.department_id = Some(Some(id.clone())),
+            BulkAction::SetSalaryGrade { id, .. } => req.salary_grade_id = Some(Some(id.clone())),
+            BulkAction::SetManager { id, .. } => req.manager_id = Some(Some(id.clone())),
+            BulkAction::ChangeRole { role } => req.role = Some(role.clone()),
+            BulkAction::Deactivate => req.active = Some(false),
+        }
+        req
+    }
+
+    /// Whether `perms` allows applying this action to `emp`. `employees` are
+    /// used to keep a new manager from closing a loop in the hierarchy.
+    pub fn check(
+        &self,
+        perms: &Permissions,
+        emp: &Employee,
+        employees: &[Employee],
+    ) -> Result<(), String> {
+        let new_dept = match self {
+            BulkAction::MoveToDepartment { id, .. } => Some(id.as_str()),
+            _ => emp.department_id.as_deref(),
+        };
+        let new_role = match self {
+            BulkAction::ChangeRole { role } => role.as_str(),
+            _ => emp.role.as_str(),
+        };
+        match self {
+            BulkAction::SetManager { id, .. } => {
+                Hierarchy::new(employees).check(&emp.id, id)?;
+                perms.check_employee_change(Some(emp), new_dept, new_role)
+            }
+            BulkAction::Deactivate
+                if !perms.can_deactivate_employee(emp.department_id.as_deref()) =>
+            {
+                Err(format!(
+                    "You may not deactivate {} {}.",
+                    emp.first_name, emp.last_name
+                ))
+            }
+            _ => perms.check_employee_change(Some(emp), new_dept, new_role),
+        }
+    }
+}
+
+/// An employee a bulk action applies to
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkTarget {
+    pub id: String,
+    pub name: String,
+    /// `updated_at` when the action was confirmed, sent as If-Match
+    pub version: Option<String>,
+}
+
+impl BulkTarget {
+    pub fn new(emp: &Employee) -> Self {
+        Self {
+            id: emp.id.clone(),
+            name: format!("{} {}", emp.first_name, emp.last_name),
+            version: emp.updated_at.clone(),
+        }
+    }
+}
+
+/// Outcome for one employee; `error` is `None` if the update went through
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkRow {
+    pub id: String,
+    pub name: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkReport {
+    pub title: String,
+    pub rows: Vec<BulkRow>,
+}
+
+impl BulkReport {
+    pub fn failed(&self) -> usize {
+        self.rows.iter().filter(|r| r.error.is_some()).count()
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.rows.len() - self.failed()
+    }
+}
+
+/// Ids of everyone who manages an employee, inactive ones included, or
+/// heads a department
+async fn managers_and_heads(api: &ApiClient) -> Result<HashSet<String>, String> {
+    let staff = api.get_employees(true).await.map_err(|e| e.to_string())?;
+    let depts = api.get_departments().await.map_err(|e| e.to_string())?;
+    Ok(staff
+        .into_iter()
+        .filter_map(|e| e.manager_id)
+        .chain(depts.into_iter().filter_map(|d| d.head_id))
+        .collect())
+}
+
+/// Send the update of `action` for every target that passed its permission
+/// check, one after the other, and report the outcome of each. Managers and
+/// department heads are not deactivated here; they need the offboarding
+/// wizard.
+pub async fn apply_bulk_action(
+    api: &ApiClient,
+    action: &BulkAction,
+    targets: Vec<(BulkTarget, Result<(), String>)>,
+) -> BulkReport {
+    let req = action.request();
+    let leavers = match action {
+        BulkAction::Deactivate => Some(managers_and_heads(api).await),
+        _ => None,
+    };
+    // `check` only saw the loaded employees; a loop may run through others
+    let staff = match action {
+        BulkAction::SetManager { .. } => Some(complete_staff(api).await),
+        _ => None,
+    };
+    let hierarchy = staff
+        .as_ref()
+        .map(|staff| staff.as_ref().map(|s| Hierarchy::new(s)));
+    let mut rows = Vec::with_capacity(targets.len());
+    for (target, allowed) in targets {
+        let allowed = allowed.and_then(|()| match &leavers {
+            Some(Err(reason)) => Err(format!("Could not check for direct reports: {}", reason)),
+            Some(Ok(ids)) if ids.contains(&target.id) => Err(
+                "Manages employees or heads a department; offboard them on their own.".to_string(),
+            ),
+            _ => Ok(()),
+        });
+        let allowed = allowed.and_then(|()| match (&hierarchy, action) {
+            (Some(Err(reason)), _) => Err(reason.to_string()),
+            (Some(Ok(hierarchy)), BulkAction::SetManager { id, .. }) => {
+                hierarchy.check(&target.id, id)
+            }
+            _ => Ok(()),
+        });
+        let error = match allowed {
+            Err(reason) => Some(reason),
+            Ok(()) => match api
+                .update_employee(&target.id, &req, target.version.as_deref())
+                .await
+            {
+                Ok(()) => None,
+                Err(e) if e.is_edit_conflict() => {
+                    Some("Changed by someone else in the meantime; reload and retry.".to_string())
+                }
+                Err(e) => Some(e.to_string()),
+            },
+        };
+        rows.push(BulkRow {
+            id: target.id,
+            name: target.name,
+            error,
+        });
+    }
+    BulkReport {
+        title: action.describe(),
+        rows,
+    }
+}
+
+impl PersonnelApp {
+    /// Show the per-employee outcome of the last bulk action
+    pub fn show_bulk_report(&mut self, ctx: &Context) {
+        let colors = self.colors;
+        let Some(report) = self.bulk_report.lock().unwrap().clone() else {
+            return;
+        };
+
+        egui::Window::new(format!("☑ {}", report.title))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .rounding(Rounding::same(16.0))
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(24.0)),
+            )
+            .show(ctx, |ui| {
+                ui.set_min_width(480.0);
+                ui.label(
+                    RichText::new(format!(
+                        "{} of {} change(s) applied, {} failed.",
+                        report.succeeded(),
+                        report.rows.len(),
+                        report.failed()
+                    ))
+                    .size(14.0)
+                    .color(colors.on_surface),
+                );
+                ui.add_space(12.0);
+
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("bulk_report_grid")
+                            .num_columns(2)
+                            .spacing([16.0, 6.0])
+                            .show(ui, |ui| {
+                                for row in &report.rows {
+                                    match &row.error {
+                                        None => {
+                                            ui.label(
+                                                RichText::new(format!("✔ {}", row.name))
+                                                    .size(13.0)
+                                                    .color(colors.success),
+                                            );
+                                            ui.label("");
+                                        }
+                                        Some(error) => {
+                                            ui.label(
+                                                RichText::new(format!("✖ {}", row.name))
+                                                    .size(13.0)
+                                                    .color(colors.error),
+                                            );
+                                            ui.label(
+                                                RichText::new(error)
+                                                    .size(12.0)
+                                                    .color(colors.on_surface_variant),
+                                            );
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.add_space(20.0);
+                ui.horizontal(|ui| {
+                    let button = |text: &str| {
+                        egui::Button::new(RichText::new(text).size(13.0).color(colors.on_surface))
+                            .fill(colors.surface_variant)
+                            .stroke(Stroke::new(1.0, colors.outline_variant))
+                            .rounding(Rounding::same(8.0))
+                            .min_size(Vec2::new(100.0, 40.0))
+                    };
+                    if ui.add(button("Close")).clicked() {
+                        *self.bulk_report.lock().unwrap() = None;
+                    }
+                    if report.failed() > 0 {
+                        ui.add_space(12.0);
+                        if ui.add(button("Select failed")).clicked() {
+                            // Reports may include other records, e.g. departments
+                            let employees = self.employees.snapshot();
+                            self.bulk_selection = report
+                                .rows
+                                .iter()
+                                .filter(|r| r.error.is_some())
+                                .filter(|r| employees.iter().any(|e| e.id == r.id))
+                                .map(|r| r.id.clone())
+                                .collect();
+                            *self.bulk_report.lock().unwrap() = None;
+                        }
+                    }
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+    use crate::api::models::AuthUser;
+    use crate::gui::fixtures::employee;
+
+    fn perms(role: &str, dept: Option<&str>) -> Permissions {
+        Permissions::for_user(&AuthUser {
+            role: role.to_string(),
+            department_id: dept.map(|d| d.to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_request_only_sets_changed_field() {
+        let req = BulkAction::Deactivate.request();
+        assert_eq!(req.active, Some(false));
+        assert_eq!(req.role, None);
+
+        let req = BulkAction::SetSalaryGrade {
+            id: "g1".to_string(),
+            code: "E5".to_string(),
+        }
+        .request();
+        assert_eq!(req.salary_grade_id, Some(Some("g1".to_string())));
+        assert_eq!(req.department_id, None);
+    }
+
+    #[test]
+    fn test_check_uses_per_employee_permissions() {
+        let head = perms("DepartmentHead", Some("d1"));
+        let own = employee("e1").with_department("d1");
+        let other = employee("e2").with_department("d2");
+
+        let promote = BulkAction::ChangeRole {
+            role: "DeputyHead".to_string(),
+        };
+        assert!(promote.check(&head, &own, &[]).is_ok());
+        assert!(promote.check(&head, &other, &[]).is_err());
+
+        let move_out = BulkAction::MoveToDepartment {
+            id: "d2".to_string(),
+            name: "Sales".to_string(),
+        };
+        assert!(move_out.check(&head, &own, &[]).is_err());
+
+        let deputy = perms("DeputyHead", Some("d1"));
+        assert!(BulkAction::Deactivate.check(&deputy, &own, &[]).is_err());
+        assert!(BulkAction::Deactivate.check(&head, &own, &[]).is_ok());
+
+        let self_managed = BulkAction::SetManager {
+            id: "e1".to_string(),
+            name: "Ada e1".to_string(),
+        };
+        assert!(self_managed
+            .check(&perms("Admin", None), &own, &[])
+            .is_err());
+
+        // e1 manages e2, so e2 cannot become e1's manager
+        let report = employee("e2").with_department("d1").with_manager("e1");
+        let staff = vec![own.clone(), report];
+        let loop_back = BulkAction::SetManager {
+            id: "e2".to_string(),
+            name: "Ada e2".to_string(),
+        };
+        assert!(loop_back
+            .check(&perms("Admin", None), &own, &staff)
+            .is_err());
+        let outsider = employee("e3").with_department("d1");
+        assert!(loop_back
+            .check(&perms("Admin", None), &outsider, &staff)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_apply_reports_each_row() {
+        let server = MockServer::start(|req, _| {
+            if req.method == "GET" {
+                MockResponse::new(200, "[]")
+            } else if req.path.ends_with("/e2") {
+                MockResponse::new(412, "")
+            } else {
+                MockResponse::new(200, "{}")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let targets = vec![
+            (BulkTarget::new(&employee("e1").with_version("v1")), Ok(())),
+            (BulkTarget::new(&employee("e2").with_version("v1")), Ok(())),
+            (
+                BulkTarget::new(&employee("e3").with_version("v1")),
+                Err("not allowed".to_string()),
+            ),
+        ];
+
+        let report = apply_bulk_action(&api, &BulkAction::Deactivate, targets).await;
+        assert_eq!(report.succeeded(), 1);
+        assert_eq!(report.failed(), 2);
+        assert_eq!(report.rows[0].error, None);
+        assert!(report.rows[1].error.is_some());
+        assert_eq!(report.rows[2].error.as_deref(), Some("not allowed"));
+
+        // The rejected row never reached the backend
+        let requests: Vec<_> = server
+            .recorded()
+            .into_iter()
+            .filter(|r| r.method == "PUT")
+            .collect();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].header("If-Match"), Some("\"v1\""));
+        assert!(requests[0].body.contains(r#""active":false"#));
+    }
+
+    #[tokio::test]
+    async fn test_deactivate_refuses_managers_and_heads() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/employees?include_inactive=true") => {
+                let report = employee("e3").with_active(false).with_manager("e1");
+                MockResponse::new(200, &serde_json::to_string(&vec![report]).unwrap())
+            }
+            ("GET", "/departments") => {
+                MockResponse::new(200, r#"[{"id":"d1","name":"Sales","head_id":"e2"}]"#)
+            }
+            _ => MockResponse::new(200, "{}"),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let targets = ["e1", "e2", "e4"]
+            .into_iter()
+            .map(|id| (BulkTarget::new(&employee(id)), Ok(())))
+            .collect();
+
+        let report = apply_bulk_action(&api, &BulkAction::Deactivate, targets).await;
+        assert_eq!(report.failed(), 2);
+        assert_eq!(report.rows[2].error, None);
+        let puts: Vec<String> = server
+            .recorded()
+            .into_iter()
+            .filter(|r| r.method == "PUT")
+            .map(|r| r.path)
+            .collect();
+        assert_eq!(puts, ["/employees/e4"]);
+    }
+
+    #[tokio::test]
+    async fn test_set_manager_checks_the_complete_staff() {
+        // e1 -> e2 (inactive, not loaded) -> e3, so e3 cannot report to e1
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/employees?include_inactive=true") => {
+                let staff = vec![
+                    employee("e1").with_manager("e2"),
+                    employee("e2").with_active(false).with_manager("e3"),
+                    employee("e3"),
+                    employee("e4"),
+                ];
+                MockResponse::new(200, &serde_json::to_string(&staff).unwrap())
+            }
+            _ => MockResponse::new(200, "{}"),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let action = BulkAction::SetManager {
+            id: "e1".to_string(),
+            name: "Ada e1".to_string(),
+        };
+        let targets = ["e3", "e4"]
+            .into_iter()
+            .map(|id| (BulkTarget::new(&employee(id)), Ok(())))
+            .collect();
+
+        let report = apply_bulk_action(&api, &action, targets).await;
+        assert!(report.rows[0].error.as_deref().unwrap().contains("loop"));
+        assert_eq!(report.rows[1].error, None);
+    }
+}
@@ -31,6 +31,8 @@ pub struct Material3Colors {
     pub on_surface: Color32,
     pub surface_variant: Color32,
     pub on_surface_variant: Color32,
+    pub inverse_surface: Color32,
+    pub inverse_on_surface: Color32,
 
     // Outline colors
     pub outline: Color32,
@@ -40,6 +42,7 @@ pub struct Material3Colors {
     pub error: Color32,
     pub on_error: Color32,
     pub success: Color32,
+    pub warning: Color32,
 }
 
 impl Default for Material3Colors {
@@ -67,6 +70,8 @@ impl Default for Material3Colors {
             on_surface: Color32::from_rgb(230, 225, 230),
             surface_variant: Color32::from_rgb(44, 40, 49),
             on_surface_variant: Color32::from_rgb(202, 196, 208),
+            inverse_surface: Color32::from_rgb(230, 225, 230),
+            inverse_on_surface: Color32::from_rgb(49, 48, 51),
 
             // Outline
             outline: Color32::from_rgb(147, 143, 153),
@@ -76,6 +81,7 @@ impl Default for Material3Colors {
             error: Color32::from_rgb(242, 184, 181),
             on_error: Color32::from_rgb(96, 20, 16),
             success: Color32::from_rgb(129, 199, 132),
+            warning: Color32::from_rgb(255, 183, 77),
         }
     }
 }
@@ -105,6 +111,8 @@ impl Material3Colors {
             on_surface: Color32::from_rgb(28, 27, 31),
             surface_variant: Color32::from_rgb(231, 224, 236),
             on_surface_variant: Color32::from_rgb(73, 69, 79),
+            inverse_surface: Color32::from_rgb(49, 48, 51),
+            inverse_on_surface: Color32::from_rgb(244, 239, 244),
 
             // Outline
             outline: Color32::from_rgb(121, 116, 126),
@@ -114,6 +122,7 @@ impl Material3Colors {
             error: Color32::from_rgb(179, 38, 30),
             on_error: Color32::from_rgb(255, 255, 255),
             success: Color32::from_rgb(56, 142, 60),
+            warning: Color32::from_rgb(176, 106, 0),
         }
     }
 
@@ -2,8 +2,10 @@
 //!
 //! This module provides helper functions for creating consistent UI elements.
 
+use super::requests::{format_elapsed, until_elapsed_changes, LoadState, RequestTracker};
 use super::Material3Colors;
 use egui::{Button, Frame, Margin, Response, RichText, Rounding, Stroke, TextEdit, Ui, Vec2};
+use std::ops::Range;
 
 /// Creates a Material 3 styled button
 ///
@@ -46,7 +48,6 @@ pub fn material_button(
 ///
 /// # Returns
 /// The text edit's response
-#[allow(dead_code)]
 pub fn styled_text_input(
     ui: &mut Ui,
     colors: &Material3Colors,
@@ -138,10 +139,173 @@ pub fn material_card(ui: &mut Ui, colors: &Material3Colors, content: impl FnOnce
         .show(ui, content);
 }
 
+/// Creates a placeholder card with pulsing bars, shown while a list loads
+///
+/// # Arguments
+/// * `ui` - The egui UI context
+/// * `colors` - The Material 3 color palette
+pub fn skeleton_card(ui: &mut Ui, colors: &Material3Colors) {
+    let time = ui.ctx().input(|i| i.time);
+    let pulse = (0.55 + 0.25 * (time * 3.0).sin()) as f32;
+    let bar_color = colors.outline_variant.gamma_multiply(pulse);
+
+    Frame::none()
+        .fill(colors.surface_variant)
+        .rounding(Rounding::same(12.0))
+        .inner_margin(Margin::same(16.0))
+        .outer_margin(Margin::symmetric(0.0, 4.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let (icon_rect, _) =
+                    ui.allocate_exact_size(Vec2::splat(48.0), egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(icon_rect, Rounding::same(8.0), bar_color);
+                ui.add_space(12.0);
+                ui.vertical(|ui| {
+                    for width in [180.0, 260.0, 140.0] {
+                        let (rect, _) =
+                            ui.allocate_exact_size(Vec2::new(width, 12.0), egui::Sense::hover());
+                        ui.painter()
+                            .rect_filled(rect, Rounding::same(4.0), bar_color);
+                        ui.add_space(4.0);
+                    }
+                });
+                ui.allocate_space(Vec2::new(ui.available_width(), 0.0));
+            });
+        });
+}
+
+/// Shows the load state of a collection next to a tab header, e.g.
+/// a spinner while loading or "Last refreshed 12s ago" once loaded
+///
+/// # Arguments
+/// * `ui` - The egui UI context
+/// * `colors` - The Material 3 color palette
+/// * `tracker` - Request tracker of the collection shown in the tab
+pub fn load_status_caption(ui: &mut Ui, colors: &Material3Colors, tracker: &RequestTracker) {
+    let last_refreshed = tracker.last_loaded().map(|t| {
+        // Repaint only when the relative time text changes
+        ui.ctx()
+            .request_repaint_after(until_elapsed_changes(t.elapsed()));
+        format!("Last refreshed {}", format_elapsed(t.elapsed()))
+    });
+
+    match tracker.state() {
+        LoadState::Idle => {}
+        LoadState::Loading => {
+            ui.add(egui::Spinner::new().size(14.0).color(colors.primary));
+            ui.label(
+                RichText::new("Loading…")
+                    .size(12.0)
+                    .color(colors.on_surface_variant),
+            );
+        }
+        LoadState::Loaded(_) => {
+            if let Some(text) = last_refreshed {
+                ui.label(
+                    RichText::new(text)
+                        .size(12.0)
+                        .color(colors.on_surface_variant),
+                );
+            }
+        }
+        LoadState::Failed(message) => {
+            let text = match last_refreshed {
+                Some(last) => format!("⚠ Refresh failed · {}", last),
+                None => "⚠ Loading failed".to_string(),
+            };
+            ui.label(RichText::new(text).size(12.0).color(colors.error))
+                .on_hover_text(message);
+        }
+    }
+
+    if tracker.is_mutating() {
+        ui.add(egui::Spinner::new().size(14.0).color(colors.primary));
+        ui.label(
+            RichText::new("Saving…")
+                .size(12.0)
+                .color(colors.on_surface_variant),
+        );
+    }
+}
+
+/// Rows of a list that intersect the visible area, given where the visible
+/// area starts and ends relative to the top of the list
+pub fn visible_rows(top: f32, bottom: f32, row_pitch: f32, count: usize) -> Range<usize> {
+    if row_pitch <= 0.0 || bottom <= 0.0 {
+        return 0..0;
+    }
+    let first = ((top.max(0.0) / row_pitch).floor() as usize).min(count);
+    let last = ((bottom / row_pitch).ceil() as usize).clamp(first, count);
+    first..last
+}
+
+/// Lay out only the rows of a long list that are on screen, inside a
+/// vertical `ScrollArea`. Off-screen rows are replaced by empty space.
+///
+/// All rows are treated as being as tall as the tallest one rendered so far,
+/// starting from `estimated_height`. Rows are padded to that height.
+pub fn virtual_list(
+    ui: &mut Ui,
+    id_salt: &str,
+    count: usize,
+    estimated_height: f32,
+    mut add_row: impl FnMut(&mut Ui, usize),
+) {
+    let id = ui.id().with(id_salt);
+    let row_height = ui
+        .data(|d| d.get_temp::<f32>(id))
+        .unwrap_or(estimated_height);
+    let spacing = ui.spacing().item_spacing.y;
+    let pitch = row_height + spacing;
+
+    let list_top = ui.cursor().top();
+    let clip = ui.clip_rect();
+    let rows = visible_rows(
+        clip.top() - list_top,
+        clip.bottom() - list_top,
+        pitch,
+        count,
+    );
+
+    ui.add_space(rows.start as f32 * pitch);
+    let mut tallest = row_height;
+    for index in rows.clone() {
+        let row = ui.scope(|ui| {
+            ui.set_min_height(row_height);
+            add_row(ui, index);
+        });
+        tallest = tallest.max(row.response.rect.height());
+    }
+    let below = count - rows.end;
+    if below > 0 {
+        ui.add_space(below as f32 * pitch - spacing);
+    }
+
+    if tallest > row_height {
+        ui.data_mut(|d| d.insert_temp(id, tallest));
+        ui.ctx().request_repaint();
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::visible_rows;
     use egui::{Color32, Vec2};
 
+    #[test]
+    fn test_visible_rows() {
+        // List starts on screen: rows 0..4 cover the first 400px
+        assert_eq!(visible_rows(-50.0, 350.0, 100.0, 1000), 0..4);
+        // Scrolled into the middle
+        assert_eq!(visible_rows(1050.0, 1450.0, 100.0, 1000), 10..15);
+        // Near the end the range is clamped
+        assert_eq!(visible_rows(99_950.0, 100_400.0, 100.0, 1000), 999..1000);
+        // List below the visible area
+        assert_eq!(visible_rows(-900.0, -100.0, 100.0, 1000), 0..0);
+        assert_eq!(visible_rows(0.0, 500.0, 100.0, 0), 0..0);
+    }
+
     #[test]
     fn test_color32_from_rgb() {
         let color = Color32::from_rgb(255, 128, 64);
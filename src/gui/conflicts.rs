@@ -0,0 +1,421 @@
+//! Conflict resolution for concurrent edits
+//!
+//! Updates are sent with the `updated_at` value the edit started from. When
+//! the backend rejects one with 409 or 412, the task fetches the current
+//! record and stores an `EditConflict`. The conflict dialog shows the server
+//! values next to the local edits and lets the user keep or discard them.
+
+use super::dialogs::ConfirmAction;
+use super::PersonnelApp;
+use crate::api::models::*;
+use egui::{Context, Frame, Margin, RichText, Rounding, Stroke, Vec2};
+
+/// The local edits of a rejected update together with the server's record
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum ConflictRecord {
+    Department {
+        local: UpdateDepartmentRequest,
+        server: Department,
+    },
+    Employee {
+        local: UpdateEmployeeRequest,
+        server: Employee,
+    },
+    SalaryGrade {
+        local: UpdateSalaryGradeRequest,
+        server: SalaryGrade,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct EditConflict {
+    /// The confirmed update that was rejected
+    pub action: ConfirmAction,
+    pub record: ConflictRecord,
+}
+
+/// One field of the comparison table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictRow {
+    pub field: &'static str,
+    pub local: String,
+    pub server: String,
+}
+
+impl ConflictRow {
+    fn new(field: &'static str, local: String, server: String) -> Self {
+        Self {
+            field,
+            local,
+            server,
+        }
+    }
+
+    pub fn differs(&self) -> bool {
+        self.local != self.server
+    }
+}
+
+fn display(value: Option<&str>) -> String {
+    value.unwrap_or("—").to_string()
+}
+
+impl EditConflict {
+    /// Name of the record as shown in the dialog
+    pub fn name(&self) -> &str {
+        match &self.action {
+            ConfirmAction::UpdateDepartment { name, .. }
+            | ConfirmAction::UpdateEmployee { name, .. } => name,
+            ConfirmAction::UpdateSalaryGrade { code, .. } => code,
+            _ => "",
+        }
+    }
+
+    /// `updated_at` of the record currently stored on the server
+    pub fn server_version(&self) -> Option<String> {
+        match &self.record {
+            ConflictRecord::Department { server, .. } => server.updated_at.clone(),
+            ConflictRecord::Employee { server, .. } => server.updated_at.clone(),
+            ConflictRecord::SalaryGrade { server, .. } => server.updated_at.clone(),
+        }
+    }
+
+    /// Compare local and server values field by field. `name_of` resolves
+    /// referenced IDs (departments, employees, grades) to display names.
+    pub fn rows(&self, name_of: impl Fn(&str) -> Option<String>) -> Vec<ConflictRow> {
+        let reference = |id: Option<&str>| match id {
+            Some(id) => name_of(id).unwrap_or_else(|| id.to_string()),
+            None => display(None),
+        };
+
+        match &self.record {
+            ConflictRecord::Department { local, server } => vec![
+                ConflictRow::new("Name", display(local.name.as_deref()), server.name.clone()),
+                ConflictRow::new(
+                    "Head",
+                    reference(local.head_id.as_ref().and_then(Option::as_deref)),
+                    reference(server.head_id.as_deref()),
+                ),
+            ],
+            ConflictRecord::Employee { local, server } => vec![
+                ConflictRow::new(
+                    "First Name",
+                    display(local.first_name.as_deref()),
+                    server.first_name.clone(),
+                ),
+                ConflictRow::new(
+                    "Last Name",
+                    display(local.last_name.as_deref()),
+                    server.last_name.clone(),
+                ),
+                ConflictRow::new(
+                    "Email",
+                    display(local.email.as_deref()),
+                    server.email.clone(),
+                ),
+                ConflictRow::new("Role", display(local.role.as_deref()), server.role.clone()),
+                ConflictRow::new(
+                    "Department",
+                    reference(local.department_id.as_ref().and_then(Option::as_deref)),
+                    reference(server.department_id.as_deref()),
+                ),
+                ConflictRow::new(
+                    "Manager",
+                    reference(local.manager_id.as_ref().and_then(Option::as_deref)),
+                    reference(server.manager_id.as_deref()),
+                ),
+                ConflictRow::new(
+                    "Salary Grade",
+                    reference(local.salary_grade_id.as_ref().and_then(Option::as_deref)),
+                    reference(server.salary_grade_id.as_deref()),
+                ),
+            ],
+            ConflictRecord::SalaryGrade { local, server } => vec![
+                ConflictRow::new("Code", display(local.code.as_deref()), server.code.clone()),
+                ConflictRow::new(
+                    "Base Salary",
+                    local
+                        .base_salary
+                        .map(|s| format!("{:.2}", s))
+                        .unwrap_or_else(|| display(None)),
+                    format!("{:.2}", server.base_salary),
+                ),
+                ConflictRow::new(
+                    "Description",
+                    display(local.description.as_ref().and_then(Option::as_deref)),
+                    display(server.description.as_deref()),
+                ),
+            ],
+        }
+    }
+
+    /// The rejected update, retargeted at the server's current version so
+    /// the local edits overwrite it
+    pub fn overwrite_action(&self) -> ConfirmAction {
+        let mut action = self.action.clone();
+        let server_version = self.server_version();
+        match &mut action {
+            ConfirmAction::UpdateDepartment {
+                old_head_id,
+                version,
+                ..
+            } => {
+                if let ConflictRecord::Department { server, .. } = &self.record {
+                    // Head changes are computed against the current head
+                    *old_head_id = server.head_id.clone();
+                }
+                *version = server_version;
+            }
+            ConfirmAction::UpdateEmployee { version, .. }
+            | ConfirmAction::UpdateSalaryGrade { version, .. } => *version = server_version,
+            _ => {}
+        }
+        action
+    }
+}
+
+impl PersonnelApp {
+    /// Put the local edits of a conflict back into the form fields
+    fn restore_local_edits(&mut self, record: &ConflictRecord) {
+        match record {
+            ConflictRecord::Department { local, .. } => {
+                self.dept_name = local.name.clone().unwrap_or_default();
+                self.dept_head_id = local.head_id.clone().flatten().unwrap_or_default();
+            }
+            ConflictRecord::Employee { local, .. } => {
+                self.emp_first_name = local.first_name.clone().unwrap_or_default();
+                self.emp_last_name = local.last_name.clone().unwrap_or_default();
+                self.emp_email = local.email.clone().unwrap_or_default();
+                self.emp_role = local.role.clone().unwrap_or_default();
+                self.emp_dept_id = local.department_id.clone().flatten().unwrap_or_default();
+                self.emp_manager_id = local.manager_id.clone().flatten().unwrap_or_default();
+                self.emp_salary_grade_id =
+                    local.salary_grade_id.clone().flatten().unwrap_or_default();
+            }
+            ConflictRecord::SalaryGrade { local, .. } => {
+                self.grade_code = local.code.clone().unwrap_or_default();
+                self.grade_salary = local.base_salary.unwrap_or_default().to_string();
+                self.grade_desc = local.description.clone().flatten().unwrap_or_default();
+            }
+        }
+    }
+
+    /// Resolve a referenced ID to the name shown in the conflict dialog
+    fn reference_name(&self, id: &str) -> Option<String> {
+        if let Some(d) = self.departments.snapshot().iter().find(|d| d.id == id) {
+            return Some(d.name.clone());
+        }
+        if let Some(e) = self.employees.snapshot().iter().find(|e| e.id == id) {
+            return Some(format!("{} {}", e.first_name, e.last_name));
+        }
+        self.salary_grades
+            .snapshot()
+            .iter()
+            .find(|g| g.id == id)
+            .map(|g| g.code.clone())
+    }
+
+    /// Show the conflict dialog for a rejected update, if there is one
+    pub fn show_conflict_dialog(&mut self, ctx: &Context) {
+        let colors = self.colors;
+        let Some(conflict) = self.edit_conflict.lock().unwrap().clone() else {
+            return;
+        };
+        let rows = conflict.rows(|id| self.reference_name(id));
+
+        egui::Window::new("⚠ Edit Conflict")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .rounding(Rounding::same(16.0))
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(24.0)),
+            )
+            .show(ctx, |ui| {
+                ui.set_min_width(520.0);
+
+                ui.label(
+                    RichText::new(format!(
+                        "\"{}\" was changed by someone else while you were editing it.\n\
+                        Compare your changes with the values now stored on the server.",
+                        conflict.name()
+                    ))
+                    .size(14.0)
+                    .color(colors.on_surface),
+                );
+                ui.add_space(16.0);
+
+                egui::Grid::new("edit_conflict_grid")
+                    .num_columns(3)
+                    .spacing([24.0, 8.0])
+                    .show(ui, |ui| {
+                        for header in ["Field", "Your changes", "Server"] {
+                            ui.label(
+                                RichText::new(header)
+                                    .size(12.0)
+                                    .strong()
+                                    .color(colors.on_surface_variant),
+                            );
+                        }
+                        ui.end_row();
+
+                        for row in &rows {
+                            let color = if row.differs() {
+                                colors.warning
+                            } else {
+                                colors.on_surface
+                            };
+                            ui.label(
+                                RichText::new(row.field)
+                                    .size(13.0)
+                                    .color(colors.on_surface_variant),
+                            );
+                            ui.label(RichText::new(&row.local).size(13.0).color(color));
+                            ui.label(RichText::new(&row.server).size(13.0).color(color));
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_space(24.0);
+
+                ui.horizontal(|ui| {
+                    let discard_btn = egui::Button::new(
+                        RichText::new("Discard my changes")
+                            .size(13.0)
+                            .color(colors.on_surface),
+                    )
+                    .fill(colors.surface_variant)
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .rounding(Rounding::same(8.0))
+                    .min_size(Vec2::new(100.0, 40.0));
+
+                    if ui.add(discard_btn).clicked() {
+                        *self.edit_conflict.lock().unwrap() = None;
+                        self.notifier.info(format!(
+                            "Kept the server version of \"{}\"",
+                            conflict.name()
+                        ));
+                    }
+
+                    ui.add_space(12.0);
+
+                    let keep_btn = egui::Button::new(
+                        RichText::new("Keep my changes")
+                            .size(13.0)
+                            .color(colors.on_primary),
+                    )
+                    .fill(colors.primary)
+                    .stroke(Stroke::NONE)
+                    .rounding(Rounding::same(8.0))
+                    .min_size(Vec2::new(100.0, 40.0));
+
+                    if ui.add(keep_btn).clicked() {
+                        *self.edit_conflict.lock().unwrap() = None;
+                        self.restore_local_edits(&conflict.record);
+                        self.execute_confirmed_action(conflict.overwrite_action());
+                    }
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::{department, employee};
+
+    fn employee_conflict() -> EditConflict {
+        EditConflict {
+            action: ConfirmAction::UpdateEmployee {
+                id: "emp-1".to_string(),
+                name: "Ada Lovelace".to_string(),
+                version: Some("v1".to_string()),
+            },
+            record: ConflictRecord::Employee {
+                local: UpdateEmployeeRequest {
+                    first_name: Some("Ada".to_string()),
+                    last_name: Some("Lovelace".to_string()),
+                    email: Some("ada@example.com".to_string()),
+                    role: Some("DeputyHead".to_string()),
+                    department_id: Some(Some("d1".to_string())),
+                    ..Default::default()
+                },
+                server: employee("emp-1")
+                    .with_name("Ada", "King")
+                    .with_email("ada@example.com")
+                    .with_department("d1")
+                    .with_version("v2"),
+            },
+        }
+    }
+
+    #[test]
+    fn test_rows_mark_changed_fields() {
+        let conflict = employee_conflict();
+        let rows = conflict.rows(|id| (id == "d1").then(|| "Sales".to_string()));
+
+        let changed: Vec<&str> = rows
+            .iter()
+            .filter(|r| r.differs())
+            .map(|r| r.field)
+            .collect();
+        assert_eq!(changed, ["Last Name", "Role"]);
+
+        let dept = rows.iter().find(|r| r.field == "Department").unwrap();
+        assert_eq!(dept.server, "Sales");
+        let manager = rows.iter().find(|r| r.field == "Manager").unwrap();
+        assert_eq!(manager.local, "—");
+    }
+
+    #[test]
+    fn test_overwrite_uses_server_version() {
+        let conflict = employee_conflict();
+        assert_eq!(conflict.name(), "Ada Lovelace");
+        match conflict.overwrite_action() {
+            ConfirmAction::UpdateEmployee { version, .. } => {
+                assert_eq!(version.as_deref(), Some("v2"))
+            }
+            _ => panic!("expected UpdateEmployee"),
+        }
+    }
+
+    #[test]
+    fn test_overwrite_department_uses_current_head() {
+        let conflict = EditConflict {
+            action: ConfirmAction::UpdateDepartment {
+                id: "d1".to_string(),
+                name: "Sales".to_string(),
+                old_head_id: Some("emp-1".to_string()),
+                old_head_name: None,
+                new_head_id: Some("emp-3".to_string()),
+                new_head_name: None,
+                version: Some("v1".to_string()),
+            },
+            record: ConflictRecord::Department {
+                local: UpdateDepartmentRequest {
+                    name: Some("Sales".to_string()),
+                    head_id: Some(Some("emp-3".to_string())),
+                },
+                server: department("d1", "Sales")
+                    .with_head("emp-2")
+                    .with_version("v2"),
+            },
+        };
+
+        match conflict.overwrite_action() {
+            ConfirmAction::UpdateDepartment {
+                old_head_id,
+                version,
+                ..
+            } => {
+                assert_eq!(old_head_id.as_deref(), Some("emp-2"));
+                assert_eq!(version.as_deref(), Some("v2"));
+            }
+            _ => panic!("expected UpdateDepartment"),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Calendar dates as exchanged with the backend (`YYYY-MM-DD`)
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// Split a `YYYY-MM-DD` date into year, month and day if it is valid
+pub fn parse_date(text: &str) -> Option<(i64, u32, u32)> {
+    let mut parts = text.trim().splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let year: i64 = year.parse().ok()?;
+    let month: u32 = month.parse().ok()?;
+    let day: u32 = day.parse().ok()?;
+    let valid = (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month);
+    valid.then_some((year, month, day))
+}
+
+/// The date `days` days after 1970-01-01
+fn date_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Today's date (UTC)
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (year, month, day) = date_from_days(secs.div_euclid(86_400));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2024-02-29"), Some((2024, 2, 29)));
+        assert_eq!(parse_date(" 2023-12-31 "), Some((2023, 12, 31)));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2023-13-01"), None);
+        assert_eq!(parse_date("2023-1-01"), None);
+        assert_eq!(parse_date("31.12.2023"), None);
+        assert_eq!(parse_date(""), None);
+    }
+
+    #[test]
+    fn test_date_from_days() {
+        assert_eq!(date_from_days(0), (1970, 1, 1));
+        assert_eq!(date_from_days(19_782), (2024, 2, 29));
+        assert_eq!(date_from_days(-1), (1969, 12, 31));
+        assert!(parse_date(&today()).is_some());
+    }
+}
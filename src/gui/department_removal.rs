@@ -0,0 +1,649 @@
+//! Wizard for deleting a department that still has members
+//!
+//! Deleting a department used to leave its employees without one. The
+//! wizard fetches everyone assigned to it, lets the user move them all to a
+//! single department or pick a target per employee (leaving someone without
+//! a department has to be picked explicitly), and demotes the head the
+//! same way replacing a head through `UpdateDepartment` does. The department
+//! is only deleted once every move went through.
+
+use super::bulk::{BulkReport, BulkRow};
+use super::components::styled_dropdown;
+use super::permissions::{Permissions, Role};
+use super::{ConfirmAction, PersonnelApp};
+use crate::api::client::ApiClient;
+use crate::api::models::*;
+use egui::{Context, Frame, Margin, RichText, Rounding, Stroke, Ui, Vec2};
+
+/// Where a member goes when the department is deleted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MoveTarget {
+    /// Nothing picked yet; blocks the deletion
+    #[default]
+    Unchosen,
+    /// Explicitly left without a department
+    NoDepartment,
+    Department(String),
+}
+
+/// An employee of the department and where they go
+#[derive(Debug, Clone)]
+pub struct EmployeeMove {
+    pub employee: Employee,
+    pub target: MoveTarget,
+}
+
+#[derive(Debug, Clone)]
+pub struct DepartmentRemoval {
+    pub department: Department,
+    /// `None` until the department's employees arrived
+    pub moves: Option<Vec<EmployeeMove>>,
+    /// The head's record, if the department has one and it could be found
+    pub head: Option<Employee>,
+    /// Departments the employees can move to
+    pub targets: Vec<Department>,
+    /// Last choice in the "Move all to" dropdown
+    pub move_all_target: MoveTarget,
+    pub demote_head: bool,
+    pub reviewing: bool,
+}
+
+impl DepartmentRemoval {
+    pub fn new(department: Department, departments: &[Department]) -> Self {
+        let targets = departments
+            .iter()
+            .filter(|d| d.id != department.id)
+            .cloned()
+            .collect();
+        Self {
+            department,
+            moves: None,
+            head: None,
+            targets,
+            move_all_target: MoveTarget::Unchosen,
+            demote_head: true,
+            reviewing: false,
+        }
+    }
+
+    /// Fill in the members; `known` is searched for the head if they are
+    /// not a member themselves
+    pub fn set_members(&mut self, members: &[Employee], known: &[Employee]) {
+        self.moves = Some(
+            members
+                .iter()
+                .map(|e| EmployeeMove {
+                    employee: e.clone(),
+                    target: MoveTarget::Unchosen,
+                })
+                .collect(),
+        );
+        self.head = self
+            .department
+            .head_id
+            .as_ref()
+            .and_then(|id| members.iter().chain(known).find(|e| &e.id == id).cloned());
+        if members.is_empty() {
+            self.reviewing = true;
+        }
+    }
+
+    /// Whether the head loses the `DepartmentHead` role when the department
+    /// goes; admins keep theirs
+    pub fn demotes_head(&self) -> bool {
+        self.demote_head
+            && self
+                .head
+                .as_ref()
+                .is_some_and(|h| Role::parse(&h.role) == Some(Role::DepartmentHead))
+    }
+
+    pub fn move_all(&mut self, target: MoveTarget) {
+        for m in self.moves.iter_mut().flatten() {
+            m.target = target.clone();
+        }
+        self.move_all_target = target;
+    }
+
+    fn target_name(&self, id: Option<&str>) -> String {
+        id.and_then(|id| self.targets.iter().find(|d| d.id == id))
+            .map(|d| d.name.clone())
+            .unwrap_or_else(|| "No department".to_string())
+    }
+
+    /// Reasons the batch cannot be applied as planned
+    pub fn problems(&self, perms: &Permissions) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !perms.can_manage_departments() {
+            problems.push("Only admins may delete departments.".to_string());
+        }
+        for m in self.moves.iter().flatten() {
+            let emp = &m.employee;
+            match &m.target {
+                MoveTarget::Unchosen => problems.push(format!(
+                    "Choose where {} {} goes.",
+                    emp.first_name, emp.last_name
+                )),
+                MoveTarget::NoDepartment => {}
+                MoveTarget::Department(target) => {
+                    if let Err(reason) =
+                        perms.check_employee_change(Some(emp), Some(target), &emp.role)
+                    {
+                        problems.push(reason);
+                    }
+                }
+            }
+        }
+        problems
+    }
+
+    /// One line per change, in the order they are applied
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut left = 0;
+        for m in self.moves.iter().flatten() {
+            match &m.target {
+                MoveTarget::Department(id) => lines.push(format!(
+                    "{} {} moves to {}",
+                    m.employee.first_name,
+                    m.employee.last_name,
+                    self.target_name(Some(id))
+                )),
+                MoveTarget::NoDepartment => left += 1,
+                MoveTarget::Unchosen => {}
+            }
+        }
+        if left > 0 {
+            lines.push(format!(
+                "{} employee(s) are left without a department",
+                left
+            ));
+        }
+        lines.push(format!("{} is deleted", self.department.name));
+        if let (true, Some(head)) = (self.demotes_head(), &self.head) {
+            lines.push(format!(
+                "{} {} is demoted from department head",
+                head.first_name, head.last_name
+            ));
+        }
+        lines
+    }
+}
+
+/// Move the members, delete the department and demote its head. Nothing is
+/// deleted if a move failed.
+pub async fn apply_department_removal(api: &ApiClient, plan: &DepartmentRemoval) -> BulkReport {
+    let mut rows = Vec::new();
+    for m in plan.moves.iter().flatten() {
+        let MoveTarget::Department(target) = &m.target else {
+            continue;
+        };
+        let emp = &m.employee;
+        let req = UpdateEmployeeRequest {
+            department_id: Some(Some(target.clone())),
+            ..Default::default()
+        };
+        let result = api
+            .update_employee(&emp.id, &req, emp.updated_at.as_deref())
+            .await;
+        rows.push(BulkRow {
+            id: emp.id.clone(),
+            name: format!(
+                "{} {}: moved to {}",
+                emp.first_name,
+                emp.last_name,
+                plan.target_name(Some(target))
+            ),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+
+    let dept = &plan.department;
+    let result = if rows.iter().any(|r| r.error.is_some()) {
+        Err("Skipped because an employee could not be moved.".to_string())
+    } else {
+        api.delete_department(&dept.id)
+            .await
+            .map_err(|e| e.to_string())
+    };
+    let deleted = result.is_ok();
+    rows.push(BulkRow {
+        id: dept.id.clone(),
+        name: format!("{} deleted", dept.name),
+        error: result.err(),
+    });
+
+    if let (true, Some(head)) = (deleted && plan.demotes_head(), &plan.head) {
+        let req = UpdateEmployeeRequest {
+            role: Some("Employee".to_string()),
+            ..Default::default()
+        };
+        let result = api.update_employee(&head.id, &req, None).await;
+        rows.push(BulkRow {
+            id: head.id.clone(),
+            name: format!("{} {}: demoted", head.first_name, head.last_name),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+
+    BulkReport {
+        title: format!("Delete {}", dept.name),
+        rows,
+    }
+}
+
+impl PersonnelApp {
+    /// Start deleting `dept`. The members are always looked up on the
+    /// server, inactive employees included; if there are none and nobody
+    /// heads it, the plain confirmation takes over from the wizard.
+    pub fn confirm_department_delete(&mut self, dept: &Department) {
+        self.dept_removal = Some(DepartmentRemoval::new(
+            dept.clone(),
+            &self.departments.snapshot(),
+        ));
+        let api = self.api.clone();
+        let staff = self.dept_removal_staff.clone();
+        let notify = self.notifier.clone();
+        *staff.lock().unwrap() = None;
+        self.spawn(async move {
+            // Inactive members keep their department, so they are moved too
+            match api.get_employees(true).await {
+                Ok(list) => *staff.lock().unwrap() = Some(list),
+                Err(e) => notify.api_error("Could not load the department's employees", &e),
+            }
+        });
+    }
+
+    fn apply_department_removal(&mut self, plan: DepartmentRemoval) {
+        if let Some(problem) = plan.problems(&self.permissions()).into_iter().next() {
+            self.notifier.warning(problem);
+            return;
+        }
+
+        let api = self.api.clone();
+        let emps_ref = self.employees.clone();
+        let emp_status = self.emp_status.clone();
+        let emp_pager = self.emp_pager.clone();
+        let include_inactive = self.emp_include_inactive;
+        let depts_ref = self.departments.clone();
+        let dept_status = self.dept_status.clone();
+        let notify = self.notifier.clone();
+        let bulk_report = self.bulk_report.clone();
+        let mutations = (emp_status.begin_mutation(), dept_status.begin_mutation());
+
+        self.spawn(async move {
+            let report = apply_department_removal(&api, &plan).await;
+            let name = &plan.department.name;
+            if report.failed() == 0 {
+                notify.success(format!("Department \"{}\" deleted", name));
+            } else {
+                notify.warning(format!(
+                    "Deleting \"{}\" incomplete: {} of {} step(s) failed",
+                    name,
+                    report.failed(),
+                    report.rows.len()
+                ));
+            }
+            *bulk_report.lock().unwrap() = Some(report);
+            dept_status
+                .load_into(&depts_ref, &notify, "departments", api.get_departments())
+                .await;
+            emp_pager
+                .reload(
+                    &emps_ref,
+                    &emp_status,
+                    &notify,
+                    "employees",
+                    |page, limit| api.get_employees_page(page, limit, include_inactive),
+                )
+                .await;
+            drop(mutations);
+        });
+    }
+
+    /// Show the department deletion wizard, if one is in progress
+    pub fn show_department_removal(&mut self, ctx: &Context) {
+        let colors = self.colors;
+        let Some(mut plan) = self.dept_removal.take() else {
+            return;
+        };
+        if let Some(staff) = self.dept_removal_staff.lock().unwrap().take() {
+            let staff = match &self.draft {
+                Some(draft) => draft.employees(&staff),
+                None => staff,
+            };
+            let id = Some(plan.department.id.as_str());
+            let members: Vec<Employee> = staff
+                .iter()
+                .filter(|e| e.department_id.as_deref() == id)
+                .cloned()
+                .collect();
+            if members.is_empty() && plan.department.head_id.is_none() {
+                self.confirm_dialog = Some(ConfirmAction::DeleteDepartment {
+                    id: plan.department.id.clone(),
+                    name: plan.department.name.clone(),
+                    employee_count: 0,
+                });
+                return;
+            }
+            if self.refuse_wizard_in_draft(&format!("Deleting \"{}\"", plan.department.name)) {
+                return;
+            }
+            plan.set_members(&members, &staff);
+        }
+
+        let perms = self.permissions();
+        let busy = self.emp_status.is_mutating() || self.dept_status.is_mutating();
+        let mut open = true;
+        let mut apply = false;
+
+        egui::Window::new(format!("🗑 Delete {}", plan.department.name))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .rounding(Rounding::same(16.0))
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(24.0)),
+            )
+            .show(ctx, |ui| {
+                ui.set_min_width(520.0);
+                if plan.reviewing {
+                    show_review(ui, &colors, &mut plan, &perms);
+                } else {
+                    show_members(ui, &colors, &mut plan);
+                }
+
+                ui.add_space(24.0);
+                ui.horizontal(|ui| {
+                    let button = |text: &str, fill, color| {
+                        egui::Button::new(RichText::new(text).size(13.0).color(color))
+                            .fill(fill)
+                            .stroke(Stroke::new(1.0, colors.outline_variant))
+                            .rounding(Rounding::same(8.0))
+                            .min_size(Vec2::new(100.0, 40.0))
+                    };
+                    if ui
+                        .add(button("Cancel", colors.surface_variant, colors.on_surface))
+                        .clicked()
+                    {
+                        open = false;
+                    }
+                    ui.add_space(12.0);
+                    let has_members = plan.moves.as_ref().is_some_and(|m| !m.is_empty());
+                    if plan.reviewing {
+                        if has_members
+                            && ui
+                                .add(button("Back", colors.surface_variant, colors.on_surface))
+                                .clicked()
+                        {
+                            plan.reviewing = false;
+                        }
+                        ui.add_space(12.0);
+                        let ready = plan.problems(&perms).is_empty() && !busy;
+                        let delete = button("Delete", colors.error, colors.on_error);
+                        if ui.add_enabled(ready, delete).clicked() {
+                            apply = true;
+                        }
+                    } else if ui
+                        .add_enabled(
+                            plan.moves.is_some(),
+                            button("Next", colors.primary, colors.on_primary),
+                        )
+                        .clicked()
+                    {
+                        plan.reviewing = true;
+                    }
+                });
+            });
+
+        if apply {
+            self.apply_department_removal(plan);
+        } else if open {
+            self.dept_removal = Some(plan);
+        }
+    }
+}
+
+/// A dropdown of target departments, with "No department" first
+fn target_dropdown(
+    ui: &mut Ui,
+    colors: &super::Material3Colors,
+    id_salt: impl std::hash::Hash,
+    targets: &[Department],
+    selected: &mut MoveTarget,
+) -> bool {
+    let mut changed = false;
+    let current = match selected {
+        MoveTarget::Unchosen => "Choose...".to_string(),
+        MoveTarget::NoDepartment => "No department".to_string(),
+        MoveTarget::Department(id) => targets
+            .iter()
+            .find(|d| &d.id == id)
+            .map_or_else(|| id.clone(), |d| d.name.clone()),
+    };
+    styled_dropdown(ui, colors, |ui| {
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(RichText::new(current).color(colors.on_surface))
+            .width(220.0)
+            .show_ui(ui, |ui| {
+                let none = RichText::new("No department").color(colors.on_surface);
+                let is_none = *selected == MoveTarget::NoDepartment;
+                if ui.selectable_label(is_none, none).clicked() {
+                    *selected = MoveTarget::NoDepartment;
+                    changed = true;
+                }
+                ui.separator();
+                for dept in targets {
+                    let target = MoveTarget::Department(dept.id.clone());
+                    let label = RichText::new(&dept.name).color(colors.on_surface);
+                    if ui.selectable_label(*selected == target, label).clicked() {
+                        *selected = target;
+                        changed = true;
+                    }
+                }
+            });
+    });
+    changed
+}
+
+fn show_members(ui: &mut Ui, colors: &super::Material3Colors, plan: &mut DepartmentRemoval) {
+    let Some(count) = plan.moves.as_ref().map(|m| m.len()) else {
+        ui.horizontal(|ui| {
+            ui.add(egui::Spinner::new().color(colors.primary));
+            ui.label(RichText::new("Looking up employees…").color(colors.on_surface_variant));
+        });
+        return;
+    };
+    ui.label(
+        RichText::new(format!(
+            "{} employee(s) belong to \"{}\". Choose where they go.",
+            count, plan.department.name
+        ))
+        .size(14.0)
+        .color(colors.on_surface),
+    );
+    ui.add_space(12.0);
+
+    let mut all = plan.move_all_target.clone();
+    let mut move_all = false;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Move all to").color(colors.on_surface_variant));
+        move_all = target_dropdown(ui, colors, "dept_removal_all", &plan.targets, &mut all);
+    });
+    if move_all {
+        plan.move_all(all);
+    }
+    ui.add_space(8.0);
+
+    egui::ScrollArea::vertical()
+        .max_height(300.0)
+        .show(ui, |ui| {
+            egui::Grid::new("dept_removal_grid")
+                .num_columns(2)
+                .spacing([16.0, 8.0])
+                .show(ui, |ui| {
+                    for (i, m) in plan.moves.iter_mut().flatten().enumerate() {
+                        ui.label(
+                            RichText::new(format!(
+                                "{} {}",
+                                m.employee.first_name, m.employee.last_name
+                            ))
+                            .color(colors.on_surface),
+                        );
+                        target_dropdown(
+                            ui,
+                            colors,
+                            ("dept_removal_target", i),
+                            &plan.targets,
+                            &mut m.target,
+                        );
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn show_review(
+    ui: &mut Ui,
+    colors: &super::Material3Colors,
+    plan: &mut DepartmentRemoval,
+    perms: &Permissions,
+) {
+    ui.label(
+        RichText::new("The following changes are applied in this order:")
+            .size(14.0)
+            .color(colors.on_surface),
+    );
+    ui.add_space(8.0);
+    for line in plan.summary() {
+        ui.label(
+            RichText::new(format!("• {}", line))
+                .size(13.0)
+                .color(colors.on_surface),
+        );
+    }
+    if plan
+        .head
+        .as_ref()
+        .is_some_and(|h| Role::parse(&h.role) == Some(Role::DepartmentHead))
+    {
+        ui.add_space(12.0);
+        ui.checkbox(
+            &mut plan.demote_head,
+            RichText::new("Demote the head to Employee").color(colors.on_surface),
+        );
+    }
+    for problem in plan.problems(perms) {
+        ui.label(RichText::new(problem).size(12.0).color(colors.error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+    use crate::gui::fixtures::{department, employee};
+
+    fn plan() -> DepartmentRemoval {
+        let depts = [
+            department("d1", "Sales").with_head("boss"),
+            department("d2", "Support"),
+        ];
+        let mut plan = DepartmentRemoval::new(depts[0].clone(), &depts);
+        plan.set_members(
+            &[
+                employee("boss")
+                    .with_role("DepartmentHead")
+                    .with_department("d1"),
+                employee("ann").with_department("d1"),
+            ],
+            &[],
+        );
+        plan
+    }
+
+    #[test]
+    fn test_summary_lists_moves_and_demotion() {
+        let mut plan = plan();
+        assert_eq!(plan.targets.len(), 1);
+        assert!(!plan.reviewing);
+        let admin = Permissions::for_user(&AuthUser {
+            role: "Admin".to_string(),
+            ..Default::default()
+        });
+        // Nobody is left without a department by default
+        assert_eq!(plan.problems(&admin).len(), 2);
+        plan.moves.as_mut().unwrap()[0].target = MoveTarget::NoDepartment;
+        plan.moves.as_mut().unwrap()[1].target = MoveTarget::Department("d2".to_string());
+        assert!(plan.problems(&admin).is_empty());
+        assert_eq!(
+            plan.summary(),
+            [
+                "ann Doe moves to Support",
+                "1 employee(s) are left without a department",
+                "Sales is deleted",
+                "boss Doe is demoted from department head"
+            ]
+        );
+
+        plan.demote_head = false;
+        assert_eq!(plan.summary().len(), 3);
+
+        let mut empty = DepartmentRemoval::new(department("d3", "Legal"), &[]);
+        empty.set_members(&[], &[]);
+        assert!(empty.reviewing);
+        assert!(!empty.demotes_head());
+    }
+
+    #[tokio::test]
+    async fn test_apply_moves_then_deletes_then_demotes() {
+        let server = MockServer::start(|_, _| MockResponse::new(200, "{}")).await;
+        let api = ApiClient::with_config(server.config());
+        let mut plan = plan();
+        plan.move_all(MoveTarget::Department("d2".to_string()));
+        assert_eq!(
+            plan.move_all_target,
+            MoveTarget::Department("d2".to_string())
+        );
+
+        let report = apply_department_removal(&api, &plan).await;
+        assert_eq!(report.failed(), 0);
+        let calls: Vec<String> = server
+            .recorded()
+            .iter()
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                "PUT /employees/boss",
+                "PUT /employees/ann",
+                "DELETE /departments/d1",
+                "PUT /employees/boss"
+            ]
+        );
+        assert!(server.recorded()[3].body.contains(r#""role":"Employee""#));
+    }
+
+    #[tokio::test]
+    async fn test_failed_move_keeps_the_department() {
+        let server = MockServer::start(|req, _| {
+            if req.path.ends_with("/ann") {
+                MockResponse::new(412, "")
+            } else {
+                MockResponse::new(200, "{}")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let mut plan = plan();
+        plan.move_all(MoveTarget::Department("d2".to_string()));
+
+        let report = apply_department_removal(&api, &plan).await;
+        assert_eq!(report.failed(), 2);
+        assert!(server.recorded().iter().all(|r| r.method != "DELETE"));
+    }
+}
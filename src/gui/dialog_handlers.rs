@@ -1,6 +1,13 @@
 //! Dialog handlers for confirmation and edit dialogs
 
+use super::bulk::apply_bulk_action;
+use super::components::styled_text_input;
+use super::conflicts::{ConflictRecord, EditConflict};
+use super::dates::parse_date;
 use super::dialogs::{ConfirmAction, EditDialog};
+use super::hierarchy::{complete_staff, Hierarchy};
+use super::history::{prepare_delete, record_create, record_update, Record};
+use super::saga::{department_head_saga, run_saga};
 use super::PersonnelApp;
 use crate::api::models::*;
 use egui::{Color32, Context, Frame, Margin, Rounding, Stroke, Vec2};
@@ -11,13 +18,25 @@ impl PersonnelApp {
         let colors = self.colors;
 
         if let Some(action) = self.confirm_dialog.clone() {
+            // Deletes in draft mode are only staged and can still be dropped
+            let drafting = self.is_drafting();
+            let staged = |what: String| {
+                format!(
+                    "Stage the deletion of {} in the draft?\n\n\
+                    Nothing changes on the server until the draft is committed,\n\
+                    and the change can be dropped in the draft review.",
+                    what
+                )
+            };
             let (title, message, is_destructive) = match &action {
                 ConfirmAction::DeleteDepartment {
                     name,
                     employee_count,
                     ..
                 } => {
-                    let msg = if *employee_count > 0 {
+                    let msg = if drafting {
+                        staged(format!("the department \"{}\"", name))
+                    } else if *employee_count > 0 {
                         format!(
                             "Are you sure you want to delete the department \"{}\"?\n\n\
                             ⚠️ Warning: {} employee(s) are assigned to this department.\n\
@@ -32,22 +51,42 @@ impl PersonnelApp {
                     };
                     ("🗑 Delete Department", msg, true)
                 }
+                ConfirmAction::DeleteEmployee { name, .. } if drafting => (
+                    "🗑 Permanently Delete Employee",
+                    format!(
+                        "{}\n\n⚠️ Once committed, the delete cannot be undone.",
+                        staged(format!("the employee \"{}\"", name))
+                    ),
+                    true,
+                ),
                 ConfirmAction::DeleteEmployee { name, .. } => (
-                    "🗑 Delete Employee",
+                    "🗑 Permanently Delete Employee",
                     format!(
-                        "Are you sure you want to delete the employee \"{}\"?\n\n\
+                        "Permanently delete the employee \"{}\"?\n\n\
                             ⚠️ This action cannot be undone.\n\
-                            All associated records will be affected.",
+                            All associated records will be affected. To offboard\n\
+                            someone while keeping their records, deactivate them instead.",
                         name
                     ),
                     true,
                 ),
+                ConfirmAction::DeactivateEmployee { name, .. } => (
+                    "⏻ Deactivate Employee",
+                    format!(
+                        "Deactivate the employee \"{}\"?\n\n\
+                            Their record is kept and they can be reactivated later.",
+                        name
+                    ),
+                    false,
+                ),
                 ConfirmAction::DeleteSalaryGrade {
                     code,
                     employee_count,
                     ..
                 } => {
-                    let msg = if *employee_count > 0 {
+                    let msg = if drafting {
+                        staged(format!("salary grade \"{}\"", code))
+                    } else if *employee_count > 0 {
                         format!(
                             "Are you sure you want to delete salary grade \"{}\"?\n\n\
                             ⚠️ Warning: {} employee(s) are using this salary grade.\n\
@@ -95,6 +134,15 @@ impl PersonnelApp {
                     format!("Save changes to employee \"{}\"?", name),
                     false,
                 ),
+                ConfirmAction::ReactivateEmployee { name, .. } => (
+                    "↺ Reactivate Employee",
+                    format!(
+                        "Reactivate the employee \"{}\"?\n\n\
+                            They will be listed as active again.",
+                        name
+                    ),
+                    false,
+                ),
                 ConfirmAction::UpdateSalaryGrade { code, .. } => (
                     "✏ Update Salary Grade",
                     format!(
@@ -119,6 +167,27 @@ impl PersonnelApp {
                     format!("Create new salary grade \"{}\"?", code),
                     false,
                 ),
+                ConfirmAction::BulkUpdateEmployees { action, targets } => {
+                    const LISTED: usize = 12;
+                    let mut names: Vec<String> = targets
+                        .iter()
+                        .take(LISTED)
+                        .map(|t| format!("• {}", t.name))
+                        .collect();
+                    if targets.len() > LISTED {
+                        names.push(format!("… and {} more", targets.len() - LISTED));
+                    }
+                    (
+                        "☑ Bulk Update",
+                        format!(
+                            "{} for {} employee(s)?\n\n{}",
+                            action.describe(),
+                            targets.len(),
+                            names.join("\n")
+                        ),
+                        false,
+                    )
+                }
             };
 
             egui::Window::new(title)
@@ -141,6 +210,8 @@ impl PersonnelApp {
                             .color(colors.on_surface),
                     );
 
+                    let can_confirm = self.show_confirm_input(ui, &action);
+
                     ui.add_space(24.0);
 
                     ui.horizontal(|ui| {
@@ -157,6 +228,7 @@ impl PersonnelApp {
 
                         if ui.add(cancel_btn).clicked() {
                             self.confirm_dialog = None;
+                            self.confirm_input.clear();
                         }
 
                         ui.add_space(12.0);
@@ -179,132 +251,340 @@ impl PersonnelApp {
                         .rounding(Rounding::same(8.0))
                         .min_size(Vec2::new(100.0, 40.0));
 
-                        if ui.add(confirm_btn).clicked() {
+                        if ui.add_enabled(can_confirm, confirm_btn).clicked() {
                             self.execute_confirmed_action(action);
                             self.confirm_dialog = None;
+                            self.confirm_input.clear();
                         }
                     });
                 });
         }
     }
 
+    /// Extra input some confirmations ask for. Returns whether the input
+    /// allows confirming.
+    fn show_confirm_input(&mut self, ui: &mut egui::Ui, action: &ConfirmAction) -> bool {
+        let colors = self.colors;
+        let (prompt, hint) = match action {
+            ConfirmAction::DeactivateEmployee { .. } => {
+                ("Leave date (optional)", "YYYY-MM-DD".to_string())
+            }
+            ConfirmAction::DeleteEmployee { name, .. } => {
+                ("Type the employee's name to confirm", name.clone())
+            }
+            _ => return true,
+        };
+
+        ui.add_space(16.0);
+        ui.label(
+            egui::RichText::new(prompt)
+                .size(12.0)
+                .color(colors.on_surface_variant),
+        );
+        ui.add_space(4.0);
+        styled_text_input(ui, &colors, &mut self.confirm_input, &hint);
+
+        let input = self.confirm_input.trim();
+        match action {
+            ConfirmAction::DeactivateEmployee { .. } => {
+                let valid = input.is_empty() || parse_date(input).is_some();
+                if !valid {
+                    ui.label(
+                        egui::RichText::new("Enter the date as YYYY-MM-DD")
+                            .size(12.0)
+                            .color(colors.error),
+                    );
+                }
+                valid
+            }
+            ConfirmAction::DeleteEmployee { name, .. } => input == name,
+            _ => true,
+        }
+    }
+
     /// Execute the confirmed action
     pub fn execute_confirmed_action(&mut self, action: ConfirmAction) {
-        match action {
-            ConfirmAction::DeleteDepartment { id, .. } => {
+        // The views already hide what the role may not do; check again so a
+        // stale dialog cannot slip through after a role or data change
+        let perms = self.permissions();
+        let emps = self.view_employees();
+        let new_dept = Some(self.emp_dept_id.as_str()).filter(|d| !d.is_empty());
+        let allowed = perms
+            .check_action(&action, &emps)
+            .and_then(|_| match &action {
+                ConfirmAction::UpdateEmployee { id, .. } => {
+                    let current = emps.iter().find(|e| &e.id == id);
+                    perms
+                        .check_employee_change(current, new_dept, &self.emp_role)
+                        .and_then(|_| match self.emp_manager_id.as_str() {
+                            "" => Ok(()),
+                            manager => Hierarchy::new(&emps).check(id, manager),
+                        })
+                }
+                ConfirmAction::CreateEmployee { .. } => {
+                    perms.check_employee_change(None, new_dept, &self.emp_role)
+                }
+                _ => Ok(()),
+            });
+        if let Err(reason) = allowed {
+            self.notifier.warning(reason);
+            return;
+        }
+        if self.is_drafting() {
+            self.stage_action(action);
+            return;
+        }
+
+        match action.clone() {
+            ConfirmAction::DeleteDepartment { id, name, .. } => {
                 let api = self.api.clone();
                 let depts_ref = self.departments.clone();
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.delete_department(&id).await {
-                        eprintln!("Error deleting department: {}", e);
-                    }
-                    if let Ok(data) = api.get_departments().await {
-                        *depts_ref.lock().unwrap() = data;
+                let dept_status = self.dept_status.clone();
+                let notify = self.notifier.clone();
+                let history = self.history.clone();
+                let before = self.department_record(&id);
+                let mutation = dept_status.begin_mutation();
+                self.spawn(async move {
+                    let changes = prepare_delete(&api, before).await;
+                    match api.delete_department(&id).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Department \"{}\" deleted", name),
+                            changes,
+                        ),
+                        Err(e) => notify.api_error("Could not delete department", &e),
                     }
+                    dept_status
+                        .load_into(&depts_ref, &notify, "departments", api.get_departments())
+                        .await;
+                    drop(mutation);
                 });
             }
-            ConfirmAction::DeleteEmployee { id, .. } => {
+            ConfirmAction::DeleteEmployee { id, name } => {
                 let api = self.api.clone();
                 let emps_ref = self.employees.clone();
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.delete_employee(&id).await {
-                        eprintln!("Error deleting employee: {}", e);
+                let emp_status = self.emp_status.clone();
+                let emp_pager = self.emp_pager.clone();
+                let include_inactive = self.emp_include_inactive;
+                let notify = self.notifier.clone();
+                let mutation = emp_status.begin_mutation();
+                // A recreated employee would lose their id and history, so
+                // permanent deletes are not offered for undo
+                self.spawn(async move {
+                    match api.delete_employee(&id).await {
+                        Ok(()) => {
+                            notify.success(format!("Employee \"{}\" permanently deleted", name))
+                        }
+                        Err(e) => notify.api_error("Could not delete employee", &e),
                     }
-                    if let Ok(data) = api.get_employees(false).await {
-                        *emps_ref.lock().unwrap() = data;
+                    emp_pager
+                        .reload(
+                            &emps_ref,
+                            &emp_status,
+                            &notify,
+                            "employees",
+                            |page, limit| api.get_employees_page(page, limit, include_inactive),
+                        )
+                        .await;
+                    drop(mutation);
+                });
+            }
+            ConfirmAction::DeactivateEmployee { id, name, version } => {
+                let api = self.api.clone();
+                let req = UpdateEmployeeRequest {
+                    active: Some(false),
+                    leave_date: Some(self.confirm_input.trim().to_string())
+                        .filter(|d| !d.is_empty())
+                        .map(Some),
+                    ..Default::default()
+                };
+                let emps_ref = self.employees.clone();
+                let emp_status = self.emp_status.clone();
+                let emp_pager = self.emp_pager.clone();
+                let include_inactive = self.emp_include_inactive;
+                let notify = self.notifier.clone();
+                let history = self.history.clone();
+                let before = self.employee_record(&id);
+                let mutation = emp_status.begin_mutation();
+                self.spawn(async move {
+                    match api.update_employee(&id, &req, version.as_deref()).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Employee \"{}\" deactivated", name),
+                            record_update(&api, before).await,
+                        ),
+                        Err(e) => notify.api_error("Could not deactivate employee", &e),
                     }
+                    emp_pager
+                        .reload(
+                            &emps_ref,
+                            &emp_status,
+                            &notify,
+                            "employees",
+                            |page, limit| api.get_employees_page(page, limit, include_inactive),
+                        )
+                        .await;
+                    drop(mutation);
                 });
             }
-            ConfirmAction::DeleteSalaryGrade { id, .. } => {
+            ConfirmAction::DeleteSalaryGrade { id, code, .. } => {
                 let api = self.api.clone();
                 let grades_ref = self.salary_grades.clone();
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.delete_salary_grade(&id).await {
-                        eprintln!("Error deleting salary grade: {}", e);
-                    }
-                    if let Ok(data) = api.get_salary_grades().await {
-                        *grades_ref.lock().unwrap() = data;
+                let grade_status = self.grade_status.clone();
+                let notify = self.notifier.clone();
+                let history = self.history.clone();
+                let before = self.grade_record(&id);
+                let mutation = grade_status.begin_mutation();
+                self.spawn(async move {
+                    let changes = prepare_delete(&api, before).await;
+                    match api.delete_salary_grade(&id).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Salary grade \"{}\" deleted", code),
+                            changes,
+                        ),
+                        Err(e) => notify.api_error("Could not delete salary grade", &e),
                     }
+                    grade_status
+                        .load_into(
+                            &grades_ref,
+                            &notify,
+                            "salary grades",
+                            api.get_salary_grades(),
+                        )
+                        .await;
+                    drop(mutation);
                 });
             }
             ConfirmAction::UpdateDepartment {
                 id,
+                name,
                 old_head_id,
                 new_head_id,
+                version,
                 ..
             } => {
                 let api = self.api.clone();
                 let req = UpdateDepartmentRequest {
                     name: Some(self.dept_name.clone()),
-                    head_id: if self.dept_head_id.is_empty() {
-                        None
-                    } else {
-                        Some(self.dept_head_id.clone())
-                    },
+                    head_id: Some(Some(self.dept_head_id.clone()).filter(|h| !h.is_empty())),
                 };
                 let depts_ref = self.departments.clone();
+                let dept_status = self.dept_status.clone();
                 let emps_ref = self.employees.clone();
-
-                // Clone the head IDs for the async block
-                let old_head = old_head_id.clone();
-                let new_head = new_head_id.clone();
-
-                self.runtime.spawn(async move {
-                    // 1. Demote old head if they exist and are different from new head
-                    if let Some(ref old_id) = old_head {
-                        let should_demote = match &new_head {
-                            Some(new_id) => old_id != new_id,
-                            None => true,
+                let emp_status = self.emp_status.clone();
+                let emp_pager = self.emp_pager.clone();
+                let include_inactive = self.emp_include_inactive;
+
+                let current = self
+                    .departments
+                    .snapshot()
+                    .iter()
+                    .find(|d| d.id == id)
+                    .cloned()
+                    .unwrap_or_else(|| Department {
+                        id: id.clone(),
+                        name: name.clone(),
+                        head_id: old_head_id.clone(),
+                        created_at: None,
+                        updated_at: None,
+                    });
+                let notify = self.notifier.clone();
+                let conflict = self.edit_conflict.clone();
+                let bulk_report = self.bulk_report.clone();
+                let history = self.history.clone();
+                let mutations = (dept_status.begin_mutation(), emp_status.begin_mutation());
+
+                self.spawn(async move {
+                    // 1. Look up both heads first, so their roles can be restored
+                    let mut heads = Vec::new();
+                    for head_id in [&old_head_id, &new_head_id] {
+                        let head = match head_id {
+                            Some(head_id) => match api.get_employee(head_id).await {
+                                Ok(emp) => Some(emp),
+                                Err(e) => {
+                                    notify.api_error("Could not update department", &e);
+                                    heads.clear();
+                                    break;
+                                }
+                            },
+                            None => None,
                         };
-
-                        if should_demote {
-                            let demote_req = UpdateEmployeeRequest {
-                                role: Some("Employee".to_string()),
-                                ..Default::default()
-                            };
-                            if let Err(e) = api.update_employee(old_id, &demote_req).await {
-                                eprintln!("Error demoting old department head: {}", e);
-                            }
-                        }
+                        heads.push(head);
                     }
 
-                    // 2. Promote new head if they exist and are different from old head
-                    if let Some(ref new_id) = new_head {
-                        let should_promote = match &old_head {
-                            Some(old_id) => new_id != old_id,
-                            None => true,
-                        };
-
-                        if should_promote {
-                            let promote_req = UpdateEmployeeRequest {
-                                role: Some("DepartmentHead".to_string()),
-                                ..Default::default()
-                            };
-                            if let Err(e) = api.update_employee(new_id, &promote_req).await {
-                                eprintln!("Error promoting new department head: {}", e);
+                    // 2. Update the department, then move the head role. The
+                    //    department goes first, so a conflicting edit leaves
+                    //    the roles untouched; later failures are rolled back.
+                    if let [old_head, new_head] = heads.as_slice() {
+                        let steps = department_head_saga(
+                            &current,
+                            req.clone(),
+                            version,
+                            old_head.as_ref(),
+                            new_head.as_ref(),
+                        );
+                        let title = format!("Update department \"{}\"", name);
+                        let outcome = run_saga(&api, title, steps).await;
+                        match outcome.failure {
+                            None => {
+                                let mut changes =
+                                    record_update(&api, Some(Record::Department(current))).await;
+                                for head in [old_head, new_head].into_iter().flatten() {
+                                    let head = Some(Record::Employee(head.clone()));
+                                    changes.extend(record_update(&api, head).await);
+                                }
+                                history.commit(
+                                    &notify,
+                                    format!("Department \"{}\" updated", name),
+                                    changes,
+                                );
+                            }
+                            Some((0, e)) if e.is_edit_conflict() => {
+                                match api.get_department(&id).await {
+                                    Ok(server) => {
+                                        *conflict.lock().unwrap() = Some(EditConflict {
+                                            action,
+                                            record: ConflictRecord::Department {
+                                                local: req,
+                                                server,
+                                            },
+                                        })
+                                    }
+                                    Err(e) => notify.api_error("Could not update department", &e),
+                                }
+                            }
+                            Some(_) => {
+                                notify.warning(format!(
+                                    "Department \"{}\" was not updated; completed steps were rolled back",
+                                    name
+                                ));
+                                *bulk_report.lock().unwrap() = Some(outcome.report);
                             }
                         }
                     }
 
-                    // 3. Update the department
-                    if let Err(e) = api.update_department(&id, &req).await {
-                        eprintln!("Error updating department: {}", e);
-                    }
-
-                    // 4. Refresh data
-                    if let Ok(data) = api.get_departments().await {
-                        *depts_ref.lock().unwrap() = data;
-                    }
-                    if let Ok(data) = api.get_employees(false).await {
-                        *emps_ref.lock().unwrap() = data;
-                    }
+                    // 3. Refresh data
+                    dept_status
+                        .load_into(&depts_ref, &notify, "departments", api.get_departments())
+                        .await;
+                    emp_pager
+                        .reload(
+                            &emps_ref,
+                            &emp_status,
+                            &notify,
+                            "employees",
+                            |page, limit| api.get_employees_page(page, limit, include_inactive),
+                        )
+                        .await;
+                    drop(mutations);
                 });
 
                 self.selected_dept = None;
                 self.dept_name.clear();
                 self.dept_head_id.clear();
             }
-            ConfirmAction::UpdateEmployee { id, .. } => {
+            ConfirmAction::UpdateEmployee { id, name, version } => {
                 let api = self.api.clone();
                 let req = UpdateEmployeeRequest {
                     first_name: Some(self.emp_first_name.clone()),
@@ -312,64 +592,150 @@ impl PersonnelApp {
                     email: Some(self.emp_email.clone()),
                     role: Some(self.emp_role.clone()),
                     active: None,
-                    department_id: if self.emp_dept_id.is_empty() {
-                        None
-                    } else {
-                        Some(self.emp_dept_id.clone())
-                    },
-                    manager_id: if self.emp_manager_id.is_empty() {
-                        None
-                    } else {
-                        Some(self.emp_manager_id.clone())
-                    },
-                    salary_grade_id: if self.emp_salary_grade_id.is_empty() {
-                        None
-                    } else {
-                        Some(self.emp_salary_grade_id.clone())
-                    },
+                    department_id: Some(Some(self.emp_dept_id.clone()).filter(|d| !d.is_empty())),
+                    manager_id: Some(Some(self.emp_manager_id.clone()).filter(|m| !m.is_empty())),
+                    salary_grade_id: Some(
+                        Some(self.emp_salary_grade_id.clone()).filter(|g| !g.is_empty()),
+                    ),
                     hire_date: None,
+                    leave_date: None,
                 };
                 let emps_ref = self.employees.clone();
-
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.update_employee(&id, &req).await {
-                        eprintln!("Error updating employee: {}", e);
-                    }
-                    if let Ok(data) = api.get_employees(false).await {
-                        *emps_ref.lock().unwrap() = data;
+                let emp_status = self.emp_status.clone();
+                let emp_pager = self.emp_pager.clone();
+                let include_inactive = self.emp_include_inactive;
+                let notify = self.notifier.clone();
+                let conflict = self.edit_conflict.clone();
+                let history = self.history.clone();
+                let before = self.employee_record(&id);
+                let mutation = emp_status.begin_mutation();
+
+                self.spawn(async move {
+                    let in_line = match &req.manager_id {
+                        Some(Some(manager)) => complete_staff(&api)
+                            .await
+                            .and_then(|staff| Hierarchy::new(&staff).check(&id, manager)),
+                        _ => Ok(()),
+                    };
+                    match in_line {
+                        Err(reason) => notify.warning(reason),
+                        Ok(()) => match api.update_employee(&id, &req, version.as_deref()).await {
+                            Ok(()) => history.commit(
+                                &notify,
+                                format!("Employee \"{}\" updated", name),
+                                record_update(&api, before).await,
+                            ),
+                            Err(e) if e.is_edit_conflict() => match api.get_employee(&id).await {
+                                Ok(server) => {
+                                    *conflict.lock().unwrap() = Some(EditConflict {
+                                        action,
+                                        record: ConflictRecord::Employee { local: req, server },
+                                    })
+                                }
+                                Err(e) => notify.api_error("Could not update employee", &e),
+                            },
+                            Err(e) => notify.api_error("Could not update employee", &e),
+                        },
                     }
+                    emp_pager
+                        .reload(
+                            &emps_ref,
+                            &emp_status,
+                            &notify,
+                            "employees",
+                            |page, limit| api.get_employees_page(page, limit, include_inactive),
+                        )
+                        .await;
+                    drop(mutation);
                 });
 
-                self.selected_emp = None;
                 self.clear_emp_form();
             }
-            ConfirmAction::UpdateSalaryGrade { id, .. } => {
+            ConfirmAction::ReactivateEmployee { id, name, version } => {
+                let api = self.api.clone();
+                let req = UpdateEmployeeRequest {
+                    active: Some(true),
+                    ..Default::default()
+                };
+                let emps_ref = self.employees.clone();
+                let emp_status = self.emp_status.clone();
+                let emp_pager = self.emp_pager.clone();
+                let include_inactive = self.emp_include_inactive;
+                let notify = self.notifier.clone();
+                let history = self.history.clone();
+                let before = self.employee_record(&id);
+                let mutation = emp_status.begin_mutation();
+
+                self.spawn(async move {
+                    match api.update_employee(&id, &req, version.as_deref()).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Employee \"{}\" reactivated", name),
+                            record_update(&api, before).await,
+                        ),
+                        Err(e) => notify.api_error("Could not reactivate employee", &e),
+                    }
+                    emp_pager
+                        .reload(
+                            &emps_ref,
+                            &emp_status,
+                            &notify,
+                            "employees",
+                            |page, limit| api.get_employees_page(page, limit, include_inactive),
+                        )
+                        .await;
+                    drop(mutation);
+                });
+            }
+            ConfirmAction::UpdateSalaryGrade { id, code, version } => {
                 let base_salary = self.grade_salary.parse::<f64>().unwrap_or(0.0);
                 let api = self.api.clone();
                 let req = UpdateSalaryGradeRequest {
                     code: Some(self.grade_code.clone()),
                     base_salary: Some(base_salary),
-                    description: if self.grade_desc.is_empty() {
-                        None
-                    } else {
-                        Some(self.grade_desc.clone())
-                    },
+                    description: Some(Some(self.grade_desc.clone()).filter(|d| !d.is_empty())),
                 };
                 let grades_ref = self.salary_grades.clone();
-
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.update_salary_grade(&id, &req).await {
-                        eprintln!("Error updating salary grade: {}", e);
-                    }
-                    if let Ok(data) = api.get_salary_grades().await {
-                        *grades_ref.lock().unwrap() = data;
+                let grade_status = self.grade_status.clone();
+                let notify = self.notifier.clone();
+                let conflict = self.edit_conflict.clone();
+                let history = self.history.clone();
+                let before = self.grade_record(&id);
+                let mutation = grade_status.begin_mutation();
+
+                self.spawn(async move {
+                    match api.update_salary_grade(&id, &req, version.as_deref()).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Salary grade \"{}\" updated", code),
+                            record_update(&api, before).await,
+                        ),
+                        Err(e) if e.is_edit_conflict() => match api.get_salary_grade(&id).await {
+                            Ok(server) => {
+                                *conflict.lock().unwrap() = Some(EditConflict {
+                                    action,
+                                    record: ConflictRecord::SalaryGrade { local: req, server },
+                                })
+                            }
+                            Err(e) => notify.api_error("Could not update salary grade", &e),
+                        },
+                        Err(e) => notify.api_error("Could not update salary grade", &e),
                     }
+                    grade_status
+                        .load_into(
+                            &grades_ref,
+                            &notify,
+                            "salary grades",
+                            api.get_salary_grades(),
+                        )
+                        .await;
+                    drop(mutation);
                 });
 
                 self.selected_grade = None;
                 self.clear_grade_form();
             }
-            ConfirmAction::CreateDepartment { .. } => {
+            ConfirmAction::CreateDepartment { name } => {
                 let api = self.api.clone();
                 let req = CreateDepartmentRequest {
                     name: self.dept_name.clone(),
@@ -380,20 +746,38 @@ impl PersonnelApp {
                     },
                 };
                 let depts_ref = self.departments.clone();
-
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.create_department(&req).await {
-                        eprintln!("Error creating department: {}", e);
-                    }
-                    if let Ok(data) = api.get_departments().await {
-                        *depts_ref.lock().unwrap() = data;
+                let dept_status = self.dept_status.clone();
+                let notify = self.notifier.clone();
+                let history = self.history.clone();
+                let mutation = dept_status.begin_mutation();
+
+                self.spawn(async move {
+                    let template = Record::Department(Department {
+                        id: String::new(),
+                        name: req.name.clone(),
+                        head_id: req.head_id.clone(),
+                        created_at: None,
+                        updated_at: None,
+                    });
+                    let existing = template.existing_ids(&api).await.ok();
+                    match api.create_department(&req).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Department \"{}\" created", name),
+                            record_create(&api, template, existing).await,
+                        ),
+                        Err(e) => notify.api_error("Could not create department", &e),
                     }
+                    dept_status
+                        .load_into(&depts_ref, &notify, "departments", api.get_departments())
+                        .await;
+                    drop(mutation);
                 });
 
                 self.dept_name.clear();
                 self.dept_head_id.clear();
             }
-            ConfirmAction::CreateEmployee { .. } => {
+            ConfirmAction::CreateEmployee { name } => {
                 let api = self.api.clone();
                 let req = CreateEmployeeRequest {
                     first_name: self.emp_first_name.clone(),
@@ -418,19 +802,54 @@ impl PersonnelApp {
                     hire_date: None,
                 };
                 let emps_ref = self.employees.clone();
-
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.create_employee(&req).await {
-                        eprintln!("Error creating employee: {}", e);
-                    }
-                    if let Ok(data) = api.get_employees(false).await {
-                        *emps_ref.lock().unwrap() = data;
+                let emp_status = self.emp_status.clone();
+                let emp_pager = self.emp_pager.clone();
+                let include_inactive = self.emp_include_inactive;
+                let notify = self.notifier.clone();
+                let history = self.history.clone();
+                let mutation = emp_status.begin_mutation();
+
+                self.spawn(async move {
+                    let template = Record::Employee(Employee {
+                        id: String::new(),
+                        first_name: req.first_name.clone(),
+                        last_name: req.last_name.clone(),
+                        email: req.email.clone(),
+                        role: req.role.clone().unwrap_or_default(),
+                        active: true,
+                        department_id: req.department_id.clone(),
+                        manager_id: req.manager_id.clone(),
+                        salary_grade_id: req.salary_grade_id.clone(),
+                        hire_date: req.hire_date.clone(),
+                        leave_date: None,
+                        created_at: None,
+                        updated_at: None,
+                        deleted_at: None,
+                    });
+                    let existing = template.existing_ids(&api).await.ok();
+                    match api.create_employee(&req).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Employee \"{}\" created", name),
+                            record_create(&api, template, existing).await,
+                        ),
+                        Err(e) => notify.api_error("Could not create employee", &e),
                     }
+                    emp_pager
+                        .reload(
+                            &emps_ref,
+                            &emp_status,
+                            &notify,
+                            "employees",
+                            |page, limit| api.get_employees_page(page, limit, include_inactive),
+                        )
+                        .await;
+                    drop(mutation);
                 });
 
                 self.clear_emp_form();
             }
-            ConfirmAction::CreateSalaryGrade { .. } => {
+            ConfirmAction::CreateSalaryGrade { code } => {
                 let base_salary = self.grade_salary.parse::<f64>().unwrap_or(0.0);
                 let api = self.api.clone();
                 let req = CreateSalaryGradeRequest {
@@ -443,18 +862,93 @@ impl PersonnelApp {
                     },
                 };
                 let grades_ref = self.salary_grades.clone();
-
-                self.runtime.spawn(async move {
-                    if let Err(e) = api.create_salary_grade(&req).await {
-                        eprintln!("Error creating salary grade: {}", e);
-                    }
-                    if let Ok(data) = api.get_salary_grades().await {
-                        *grades_ref.lock().unwrap() = data;
+                let grade_status = self.grade_status.clone();
+                let notify = self.notifier.clone();
+                let history = self.history.clone();
+                let mutation = grade_status.begin_mutation();
+
+                self.spawn(async move {
+                    let template = Record::SalaryGrade(SalaryGrade {
+                        id: String::new(),
+                        code: req.code.clone(),
+                        base_salary: req.base_salary,
+                        description: req.description.clone(),
+                        created_at: None,
+                        updated_at: None,
+                    });
+                    let existing = template.existing_ids(&api).await.ok();
+                    match api.create_salary_grade(&req).await {
+                        Ok(()) => history.commit(
+                            &notify,
+                            format!("Salary grade \"{}\" created", code),
+                            record_create(&api, template, existing).await,
+                        ),
+                        Err(e) => notify.api_error("Could not create salary grade", &e),
                     }
+                    grade_status
+                        .load_into(
+                            &grades_ref,
+                            &notify,
+                            "salary grades",
+                            api.get_salary_grades(),
+                        )
+                        .await;
+                    drop(mutation);
                 });
 
                 self.clear_grade_form();
             }
+            ConfirmAction::BulkUpdateEmployees { action, targets } => {
+                let checked = targets
+                    .into_iter()
+                    .map(|target| {
+                        let allowed = match emps.iter().find(|e| e.id == target.id) {
+                            Some(emp) => action.check(&perms, emp, &emps),
+                            None => Err("No longer in the employee list.".to_string()),
+                        };
+                        (target, allowed)
+                    })
+                    .collect();
+                let api = self.api.clone();
+                let emps_ref = self.employees.clone();
+                let emp_status = self.emp_status.clone();
+                let emp_pager = self.emp_pager.clone();
+                let include_inactive = self.emp_include_inactive;
+                let notify = self.notifier.clone();
+                let bulk_report = self.bulk_report.clone();
+                let mutation = emp_status.begin_mutation();
+
+                self.spawn(async move {
+                    let report = apply_bulk_action(&api, &action, checked).await;
+                    if report.failed() == 0 {
+                        notify.success(format!(
+                            "{}: {} employee(s) updated",
+                            report.title,
+                            report.succeeded()
+                        ));
+                    } else {
+                        notify.warning(format!(
+                            "{}: {} of {} employee(s) failed",
+                            report.title,
+                            report.failed(),
+                            report.rows.len()
+                        ));
+                    }
+                    *bulk_report.lock().unwrap() = Some(report);
+                    emp_pager
+                        .reload(
+                            &emps_ref,
+                            &emp_status,
+                            &notify,
+                            "employees",
+                            |page, limit| api.get_employees_page(page, limit, include_inactive),
+                        )
+                        .await;
+                    drop(mutation);
+                });
+
+                self.bulk_selection.clear();
+            }
         }
     }
 
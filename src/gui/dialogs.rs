@@ -1,17 +1,27 @@
 //! Dialog types for confirmation and edit/create dialogs
 
+use super::bulk::{BulkAction, BulkTarget};
+
 /// Types of confirmation dialogs
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub enum ConfirmAction {
     DeleteDepartment {
         id: String,
         name: String,
         employee_count: usize,
     },
+    /// Permanent removal; admins only, confirmed by typing the name
     DeleteEmployee {
         id: String,
         name: String,
     },
+    /// Offboarding that keeps the record; the leave date is entered in the
+    /// confirmation dialog
+    DeactivateEmployee {
+        id: String,
+        name: String,
+        version: Option<String>,
+    },
     DeleteSalaryGrade {
         id: String,
         code: String,
@@ -24,14 +34,23 @@ pub enum ConfirmAction {
         old_head_name: Option<String>,
         new_head_id: Option<String>,
         new_head_name: Option<String>,
+        /// `updated_at` of the record when editing started
+        version: Option<String>,
     },
     UpdateEmployee {
         id: String,
         name: String,
+        version: Option<String>,
+    },
+    ReactivateEmployee {
+        id: String,
+        name: String,
+        version: Option<String>,
     },
     UpdateSalaryGrade {
         id: String,
         code: String,
+        version: Option<String>,
     },
     CreateDepartment {
         name: String,
@@ -42,6 +61,11 @@ pub enum ConfirmAction {
     CreateSalaryGrade {
         code: String,
     },
+    /// One change applied to several employees
+    BulkUpdateEmployees {
+        action: BulkAction,
+        targets: Vec<BulkTarget>,
+    },
 }
 
 /// Types of edit/create dialogs
@@ -51,13 +75,16 @@ pub enum EditDialog {
     EditDepartment {
         id: String,
         old_head_id: Option<String>,
+        version: Option<String>,
     },
     CreateEmployee,
     EditEmployee {
         id: String,
+        version: Option<String>,
     },
     CreateSalaryGrade,
     EditSalaryGrade {
         id: String,
+        version: Option<String>,
     },
 }
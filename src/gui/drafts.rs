@@ -0,0 +1,1255 @@
+//! Draft mode: stage changes locally and commit them together
+//!
+//! While a draft is open, confirmed creates, updates and deletes are not sent
+//! to the backend. Each touched record is kept as a `Change` with snapshots
+//! before and after (one change per record, later edits are merged into it),
+//! and the views show the loaded collections with the draft laid over them.
+//! Records created in the draft get temporary `draft-` ids, so other staged
+//! changes can refer to them.
+//!
+//! "Commit all" replays the changes in dependency order: departments and
+//! salary grades are created first, then employees, so the temporary ids
+//! can be replaced by the real ones before anything refers to them. Updates
+//! follow, and deletes come last, employees before the departments and
+//! grades they may still point to. Failed changes stay in the draft.
+
+use super::bulk::{BulkAction, BulkReport, BulkRow, BulkTarget};
+use super::dialogs::ConfirmAction;
+use super::hierarchy::{complete_staff, Hierarchy};
+use super::history::{Change, Record};
+use super::PersonnelApp;
+use crate::api::client::ApiClient;
+use crate::api::models::*;
+use egui::{Context, Frame, Margin, RichText, Rounding, Stroke, Vec2};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Prefix of the ids given to records created in a draft
+const DRAFT_PREFIX: &str = "draft-";
+
+pub fn is_draft_id(id: &str) -> bool {
+    id.starts_with(DRAFT_PREFIX)
+}
+
+/// Ids of the other records `record` points to
+fn references(record: &Record) -> Vec<&str> {
+    match record {
+        Record::Department(d) => d.head_id.iter().map(String::as_str).collect(),
+        Record::Employee(e) => [&e.department_id, &e.manager_id, &e.salary_grade_id]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect(),
+        Record::SalaryGrade(_) => Vec::new(),
+    }
+}
+
+/// Drop the references to records that only exist in the draft. Returns
+/// whether there were any.
+fn clear_draft_references(record: &mut Record) -> bool {
+    let mut cleared = false;
+    let mut clear = |id: &mut Option<String>| {
+        if id.as_deref().is_some_and(is_draft_id) {
+            *id = None;
+            cleared = true;
+        }
+    };
+    match record {
+        Record::Department(d) => clear(&mut d.head_id),
+        Record::Employee(e) => {
+            clear(&mut e.department_id);
+            clear(&mut e.manager_id);
+            clear(&mut e.salary_grade_id);
+        }
+        Record::SalaryGrade(_) => {}
+    }
+    cleared
+}
+
+fn kind(record: &Record) -> &'static str {
+    match record {
+        Record::Department(_) => "department",
+        Record::Employee(_) => "employee",
+        Record::SalaryGrade(_) => "salary grade",
+    }
+}
+
+fn display_name(record: &Record) -> String {
+    match record {
+        Record::Department(d) => d.name.clone(),
+        Record::Employee(e) => format!("{} {}", e.first_name, e.last_name),
+        Record::SalaryGrade(g) => g.code.clone(),
+    }
+}
+
+/// The fields shown in the review diff; references are ids
+fn fields(record: &Record) -> Vec<(&'static str, String)> {
+    let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+    match record {
+        Record::Department(d) => vec![("Name", d.name.clone()), ("Head", opt(&d.head_id))],
+        Record::Employee(e) => vec![
+            ("First name", e.first_name.clone()),
+            ("Last name", e.last_name.clone()),
+            ("Email", e.email.clone()),
+            ("Role", e.role.clone()),
+            ("Active", if e.active { "Yes" } else { "No" }.to_string()),
+            ("Department", opt(&e.department_id)),
+            ("Manager", opt(&e.manager_id)),
+            ("Salary grade", opt(&e.salary_grade_id)),
+            ("Leave date", opt(&e.leave_date)),
+        ],
+        Record::SalaryGrade(g) => vec![
+            ("Code", g.code.clone()),
+            ("Base salary", format!("{:.2}", g.base_salary)),
+            ("Description", opt(&g.description)),
+        ],
+    }
+}
+
+fn change_id(change: &Change) -> &str {
+    change
+        .after
+        .as_ref()
+        .or(change.before.as_ref())
+        .map_or("", |r| r.id())
+}
+
+/// "Create department "Sales"" and the like
+pub fn describe(change: &Change) -> String {
+    let (verb, record) = match (&change.before, &change.after) {
+        (None, Some(after)) => ("Create", after),
+        (Some(before), None) => ("Delete", before),
+        (_, Some(after)) => ("Update", after),
+        (None, None) => return String::new(),
+    };
+    format!("{} {} \"{}\"", verb, kind(record), display_name(record))
+}
+
+/// Field by field differences of a change, as (field, old, new)
+pub fn diff(change: &Change) -> Vec<(&'static str, String, String)> {
+    let old = change.before.as_ref().map(fields).unwrap_or_default();
+    let new = change.after.as_ref().map(fields).unwrap_or_default();
+    match (old.is_empty(), new.is_empty()) {
+        (true, _) => new
+            .into_iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(f, v)| (f, String::new(), v))
+            .collect(),
+        (_, true) => Vec::new(),
+        _ => old
+            .into_iter()
+            .zip(new)
+            .filter(|((_, a), (_, b))| a != b)
+            .map(|((f, a), (_, b))| (f, a, b))
+            .collect(),
+    }
+}
+
+/// Lay the draft's changes of one kind over `base`
+fn overlay<T: Clone>(
+    base: &[T],
+    changes: &[Change],
+    extract: impl Fn(&Record) -> Option<&T>,
+    id_of: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let mut list = base.to_vec();
+    for change in changes {
+        match (change.before.as_ref(), change.after.as_ref()) {
+            (_, Some(after)) => {
+                let Some(record) = extract(after) else {
+                    continue;
+                };
+                match list.iter_mut().find(|x| id_of(x) == id_of(record)) {
+                    Some(slot) => *slot = record.clone(),
+                    None => list.push(record.clone()),
+                }
+            }
+            (Some(before), None) => {
+                if let Some(record) = extract(before) {
+                    list.retain(|x| id_of(x) != id_of(record));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    list
+}
+
+/// A number no draft had before, so a replaced draft never matches a view
+/// cached for the one before it
+fn next_revision() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Draft {
+    /// One change per record, in the order the records were first touched
+    changes: Vec<Change>,
+    /// Id of the user who started the draft
+    pub owner: String,
+    next_id: u64,
+    /// Changes whenever `changes` does
+    revision: u64,
+}
+
+impl Draft {
+    pub fn new(owner: impl Into<String>) -> Self {
+        Self {
+            owner: owner.into(),
+            revision: next_revision(),
+            ..Default::default()
+        }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Take all changes out, e.g. to commit them
+    pub fn take_changes(&mut self) -> Vec<Change> {
+        self.revision = next_revision();
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Put back changes a commit left over
+    pub fn restore(&mut self, changes: Vec<Change>) {
+        self.revision = next_revision();
+        self.changes.extend(changes);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn new_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("{}{}", DRAFT_PREFIX, self.next_id)
+    }
+
+    /// Stage a change of one record, merging it with earlier changes of the
+    /// same record
+    pub fn stage(&mut self, before: Option<Record>, after: Option<Record>) {
+        self.revision = next_revision();
+        let Some(id) = after
+            .as_ref()
+            .or(before.as_ref())
+            .map(|r| r.id().to_string())
+        else {
+            return;
+        };
+        match self.changes.iter().position(|c| change_id(c) == id) {
+            Some(i) => {
+                self.changes[i].after = after;
+                let change = &self.changes[i];
+                if change.before.is_none() && change.after.is_none() {
+                    // Created and deleted within the draft
+                    self.changes.remove(i);
+                    for change in &mut self.changes {
+                        if let Some(record) = change.after.as_mut() {
+                            if references(record).contains(&id.as_str()) {
+                                record.remap(&id, "");
+                                clear_empty_references(record);
+                            }
+                        }
+                    }
+                }
+            }
+            None => self.changes.push(Change {
+                before,
+                after,
+                references: Vec::new(),
+            }),
+        }
+    }
+
+    /// Drop the change at `index`; a record created in the draft is removed
+    /// like a staged delete, so nothing keeps pointing to it
+    pub fn unstage(&mut self, index: usize) {
+        self.revision = next_revision();
+        let change = self.changes[index].clone();
+        if change.before.is_none() {
+            self.stage(change.after, None);
+        } else {
+            self.changes.remove(index);
+        }
+    }
+
+    pub fn departments(&self, base: &[Department]) -> Vec<Department> {
+        overlay(
+            base,
+            &self.changes,
+            |r| match r {
+                Record::Department(d) => Some(d),
+                _ => None,
+            },
+            |d| &d.id,
+        )
+    }
+
+    pub fn employees(&self, base: &[Employee]) -> Vec<Employee> {
+        overlay(
+            base,
+            &self.changes,
+            |r| match r {
+                Record::Employee(e) => Some(e),
+                _ => None,
+            },
+            |e| &e.id,
+        )
+    }
+
+    pub fn salary_grades(&self, base: &[SalaryGrade]) -> Vec<SalaryGrade> {
+        overlay(
+            base,
+            &self.changes,
+            |r| match r {
+                Record::SalaryGrade(g) => Some(g),
+                _ => None,
+            },
+            |g| &g.id,
+        )
+    }
+}
+
+/// `remap(id, "")` leaves empty ids behind; turn them into `None`
+fn clear_empty_references(record: &mut Record) {
+    let clear = |id: &mut Option<String>| {
+        if id.as_deref() == Some("") {
+            *id = None;
+        }
+    };
+    match record {
+        Record::Department(d) => clear(&mut d.head_id),
+        Record::Employee(e) => {
+            clear(&mut e.department_id);
+            clear(&mut e.manager_id);
+            clear(&mut e.salary_grade_id);
+        }
+        Record::SalaryGrade(_) => {}
+    }
+}
+
+/// Commit order of a change; see the module docs
+fn phase(change: &Change) -> u8 {
+    match (&change.before, &change.after) {
+        (None, Some(Record::Employee(_))) => 1,
+        (None, Some(_)) => 0,
+        (Some(_), Some(_)) => 2,
+        (Some(Record::Employee(_)), None) => 3,
+        _ => 4,
+    }
+}
+
+/// Staged manager changes that would close a loop, by index. They were
+/// checked against the loaded employees only, so the complete staff is
+/// fetched once the draft has any.
+async fn manager_loops(api: &ApiClient, changes: &[Change]) -> HashMap<usize, String> {
+    let new_managers: Vec<(usize, &Employee, &str)> = changes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, change)| match (&change.before, &change.after) {
+            (before, Some(Record::Employee(after))) => {
+                let manager = after.manager_id.as_deref()?;
+                let unchanged = matches!(before, Some(Record::Employee(b)) if b.manager_id.as_deref() == Some(manager));
+                (!unchanged).then_some((i, after, manager))
+            }
+            _ => None,
+        })
+        .collect();
+    if new_managers.is_empty() {
+        return HashMap::new();
+    }
+    let planned = complete_staff(api).await.map(|staff| {
+        overlay(
+            &staff,
+            changes,
+            |r| match r {
+                Record::Employee(e) => Some(e),
+                _ => None,
+            },
+            |e| &e.id,
+        )
+    });
+    new_managers
+        .into_iter()
+        .filter_map(|(i, emp, manager)| {
+            let result = match &planned {
+                Ok(planned) => Hierarchy::new(planned).check(&emp.id, manager),
+                Err(reason) => Err(reason.clone()),
+            };
+            result.err().map(|reason| (i, reason))
+        })
+        .collect()
+}
+
+/// Send the draft to the backend. Returns the outcome per change and the
+/// changes that did not go through, with draft ids already replaced where
+/// the referenced record could be created.
+pub async fn commit_draft(api: &ApiClient, mut changes: Vec<Change>) -> (BulkReport, Vec<Change>) {
+    let mut order: Vec<usize> = (0..changes.len()).collect();
+    order.sort_by_key(|&i| phase(&changes[i]));
+
+    let mut results: Vec<Option<Result<(), String>>> = vec![None; changes.len()];
+    let mut remaps: HashMap<String, String> = HashMap::new();
+    // Created records whose draft references are set once everything exists
+    let mut fix_ups = Vec::new();
+    let loops = manager_loops(api, &changes).await;
+
+    for &i in &order {
+        if let Some(reason) = loops.get(&i) {
+            results[i] = Some(Err(reason.clone()));
+            continue;
+        }
+        for (old, new) in &remaps {
+            changes[i].remap(old, new);
+        }
+        let change = &changes[i];
+        let result = match (&change.before, &change.after) {
+            (None, Some(after)) => {
+                let mut create = after.clone();
+                let needs_fix_up = clear_draft_references(&mut create);
+                let created = match create.existing_ids(api).await {
+                    Ok(existing) => create.create(api).await.map(|()| existing),
+                    Err(e) => Err(e),
+                };
+                match created {
+                    Err(e) => Err(e.to_string()),
+                    Ok(existing) => match create.find_created(api, &existing).await {
+                        Ok(Some(created)) => {
+                            remaps.insert(after.id().to_string(), created.id().to_string());
+                            if needs_fix_up {
+                                fix_ups.push(i);
+                            }
+                            Ok(())
+                        }
+                        Ok(None) => Err("Created, but not found afterwards.".to_string()),
+                        Err(e) => Err(e.to_string()),
+                    },
+                }
+            }
+            (Some(before), Some(after)) => {
+                if references(after).into_iter().any(is_draft_id) {
+                    Err("Depends on a change that failed.".to_string())
+                } else {
+                    after
+                        .update(api, before.version())
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+            }
+            (Some(before), None) => before.delete(api).await.map_err(|e| e.to_string()),
+            (None, None) => Ok(()),
+        };
+        results[i] = Some(result);
+    }
+
+    for i in fix_ups {
+        for (old, new) in &remaps {
+            changes[i].remap(old, new);
+        }
+        let Some(after) = &changes[i].after else {
+            continue;
+        };
+        let result = if references(after).into_iter().any(is_draft_id) {
+            Err("Created, but a record it refers to failed.".to_string())
+        } else {
+            after.update(api, None).await.map_err(|e| e.to_string())
+        };
+        if let Err(e) = result {
+            // The record exists now; only its references are still missing
+            let mut fixed = changes[i].clone();
+            fixed.before = fixed.after.clone();
+            if let Some(before) = fixed.before.as_mut() {
+                clear_draft_references(before);
+            }
+            changes[i] = fixed;
+            results[i] = Some(Err(e));
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut remaining = Vec::new();
+    for (change, result) in changes.into_iter().zip(results) {
+        let error = result.and_then(|r| r.err());
+        rows.push(BulkRow {
+            id: change_id(&change).to_string(),
+            name: describe(&change),
+            error: error.clone(),
+        });
+        if error.is_some() {
+            remaining.push(change);
+        }
+    }
+    (
+        BulkReport {
+            title: "Commit draft".to_string(),
+            rows,
+        },
+        remaining,
+    )
+}
+
+/// A collection with the draft laid over, and what it was built from
+struct Overlaid<T> {
+    revision: u64,
+    base: Arc<Vec<T>>,
+    view: Arc<Vec<T>>,
+}
+
+/// The overlaid collections of the last frames, so they are only rebuilt when
+/// the draft or a store changed. Holding on to `base` also makes
+/// `Store::update` copy instead of changing it in place.
+#[derive(Default)]
+pub struct DraftViews {
+    departments: Option<Overlaid<Department>>,
+    employees: Option<Overlaid<Employee>>,
+    salary_grades: Option<Overlaid<SalaryGrade>>,
+}
+
+fn cached<T>(
+    slot: &mut Option<Overlaid<T>>,
+    revision: u64,
+    base: Arc<Vec<T>>,
+    build: impl FnOnce(&[T]) -> Vec<T>,
+) -> Arc<Vec<T>> {
+    if let Some(cache) = slot {
+        if cache.revision == revision && Arc::ptr_eq(&cache.base, &base) {
+            return cache.view.clone();
+        }
+    }
+    let view = Arc::new(build(&base));
+    *slot = Some(Overlaid {
+        revision,
+        base,
+        view: view.clone(),
+    });
+    view
+}
+
+impl PersonnelApp {
+    /// The collections as the views show them, with the draft laid over
+    pub fn view_departments(&self) -> Arc<Vec<Department>> {
+        let base = self.departments.snapshot();
+        match &self.draft {
+            Some(draft) if !draft.is_empty() => cached(
+                &mut self.draft_views.lock().unwrap().departments,
+                draft.revision(),
+                base,
+                |base| draft.departments(base),
+            ),
+            _ => base,
+        }
+    }
+
+    pub fn view_employees(&self) -> Arc<Vec<Employee>> {
+        let base = self.employees.snapshot();
+        match &self.draft {
+            Some(draft) if !draft.is_empty() => cached(
+                &mut self.draft_views.lock().unwrap().employees,
+                draft.revision(),
+                base,
+                |base| draft.employees(base),
+            ),
+            _ => base,
+        }
+    }
+
+    pub fn view_salary_grades(&self) -> Arc<Vec<SalaryGrade>> {
+        let base = self.salary_grades.snapshot();
+        match &self.draft {
+            Some(draft) if !draft.is_empty() => cached(
+                &mut self.draft_views.lock().unwrap().salary_grades,
+                draft.revision(),
+                base,
+                |base| draft.salary_grades(base),
+            ),
+            _ => base,
+        }
+    }
+
+    pub fn is_drafting(&self) -> bool {
+        self.draft.is_some()
+    }
+
+    /// The removal and offboarding wizards change the server right away, so
+    /// they are refused while a draft is open. Returns whether it refused.
+    pub fn refuse_wizard_in_draft(&self, what: &str) -> bool {
+        if self.is_drafting() {
+            self.notifier.warning(format!(
+                "{} needs reassignments, which cannot be staged. Commit or discard the draft first.",
+                what
+            ));
+        }
+        self.is_drafting()
+    }
+
+    fn opt_field(value: &str) -> Option<String> {
+        Some(value.to_string()).filter(|v| !v.is_empty())
+    }
+
+    /// Record a confirmed action in the draft instead of sending it
+    pub fn stage_action(&mut self, action: ConfirmAction) {
+        let depts = self.view_departments();
+        let emps = self.view_employees();
+        let grades = self.view_salary_grades();
+        let dept = |id: &str| depts.iter().find(|d| d.id == id).cloned();
+        let emp = |id: &str| emps.iter().find(|e| e.id == id).cloned();
+        let grade = |id: &str| grades.iter().find(|g| g.id == id).cloned();
+        let mut staged: Vec<(Option<Record>, Option<Record>)> = Vec::new();
+
+        match action {
+            ConfirmAction::CreateDepartment { .. } => {
+                let id = self.draft.as_mut().map(Draft::new_id).unwrap_or_default();
+                let head_id = Self::opt_field(&self.dept_head_id);
+                staged.push((
+                    None,
+                    Some(Record::Department(Department {
+                        id,
+                        name: self.dept_name.clone(),
+                        head_id: head_id.clone(),
+                        created_at: None,
+                        updated_at: None,
+                    })),
+                ));
+                staged.extend(head_role_changes(None, head_id.as_deref().and_then(emp)));
+                self.dept_name.clear();
+                self.dept_head_id.clear();
+            }
+            ConfirmAction::UpdateDepartment {
+                id, old_head_id, ..
+            } => {
+                if let Some(before) = dept(&id) {
+                    let mut after = before.clone();
+                    after.name = self.dept_name.clone();
+                    after.head_id = Self::opt_field(&self.dept_head_id);
+                    if old_head_id != after.head_id {
+                        staged.extend(head_role_changes(
+                            old_head_id.as_deref().and_then(emp),
+                            after.head_id.as_deref().and_then(emp),
+                        ));
+                    }
+                    staged.insert(
+                        0,
+                        (
+                            Some(Record::Department(before)),
+                            Some(Record::Department(after)),
+                        ),
+                    );
+                }
+                self.selected_dept = None;
+                self.dept_name.clear();
+                self.dept_head_id.clear();
+            }
+            ConfirmAction::DeleteDepartment { id, .. } => {
+                staged.push((dept(&id).map(Record::Department), None));
+            }
+            ConfirmAction::CreateEmployee { .. } => {
+                let id = self.draft.as_mut().map(Draft::new_id).unwrap_or_default();
+                staged.push((
+                    None,
+                    Some(Record::Employee(Employee {
+                        id,
+                        first_name: self.emp_first_name.clone(),
+                        last_name: self.emp_last_name.clone(),
+                        email: self.emp_email.clone(),
+                        role: self.emp_role.clone(),
+                        active: true,
+                        department_id: Self::opt_field(&self.emp_dept_id),
+                        manager_id: Self::opt_field(&self.emp_manager_id),
+                        salary_grade_id: Self::opt_field(&self.emp_salary_grade_id),
+                        hire_date: None,
+                        leave_date: None,
+                        created_at: None,
+                        updated_at: None,
+                        deleted_at: None,
+                    })),
+                ));
+                self.clear_emp_form();
+            }
+            ConfirmAction::UpdateEmployee { id, .. } => {
+                if let Some(before) = emp(&id) {
+                    let mut after = before.clone();
+                    after.first_name = self.emp_first_name.clone();
+                    after.last_name = self.emp_last_name.clone();
+                    after.email = self.emp_email.clone();
+                    after.role = self.emp_role.clone();
+                    after.department_id = Self::opt_field(&self.emp_dept_id);
+                    after.manager_id = Self::opt_field(&self.emp_manager_id);
+                    after.salary_grade_id = Self::opt_field(&self.emp_salary_grade_id);
+                    staged.push((
+                        Some(Record::Employee(before)),
+                        Some(Record::Employee(after)),
+                    ));
+                }
+                self.clear_emp_form();
+            }
+            ConfirmAction::DeactivateEmployee { id, .. } => {
+                if let Some(before) = emp(&id) {
+                    let mut after = before.clone();
+                    after.active = false;
+                    after.leave_date = Self::opt_field(self.confirm_input.trim());
+                    staged.push((
+                        Some(Record::Employee(before)),
+                        Some(Record::Employee(after)),
+                    ));
+                }
+            }
+            ConfirmAction::ReactivateEmployee { id, .. } => {
+                if let Some(before) = emp(&id) {
+                    let mut after = before.clone();
+                    after.active = true;
+                    staged.push((
+                        Some(Record::Employee(before)),
+                        Some(Record::Employee(after)),
+                    ));
+                }
+            }
+            ConfirmAction::DeleteEmployee { id, .. } => {
+                staged.push((emp(&id).map(Record::Employee), None));
+            }
+            ConfirmAction::BulkUpdateEmployees { action, targets } => {
+                let perms = self.permissions();
+                for target in targets {
+                    if let Some(before) = emp(&target.id) {
+                        let leaver =
+                            action == BulkAction::Deactivate && self.needs_offboarding(&before);
+                        let allowed = if leaver {
+                            Err("Needs offboarding, which cannot be staged.".to_string())
+                        } else {
+                            action.check(&perms, &before, &emps)
+                        };
+                        if let Err(reason) = allowed {
+                            self.notifier
+                                .warning(format!("{} not staged: {}", target.name, reason));
+                            continue;
+                        }
+                        let after = bulk_result(&action, &target, &before);
+                        staged.push((
+                            Some(Record::Employee(before)),
+                            Some(Record::Employee(after)),
+                        ));
+                    }
+                }
+                self.bulk_selection.clear();
+            }
+            ConfirmAction::CreateSalaryGrade { .. } => {
+                let id = self.draft.as_mut().map(Draft::new_id).unwrap_or_default();
+                staged.push((
+                    None,
+                    Some(Record::SalaryGrade(SalaryGrade {
+                        id,
+                        code: self.grade_code.clone(),
+                        base_salary: self.grade_salary.parse().unwrap_or(0.0),
+                        description: Self::opt_field(&self.grade_desc),
+                        created_at: None,
+                        updated_at: None,
+                    })),
+                ));
+                self.clear_grade_form();
+            }
+            ConfirmAction::UpdateSalaryGrade { id, .. } => {
+                if let Some(before) = grade(&id) {
+                    let mut after = before.clone();
+                    after.code = self.grade_code.clone();
+                    after.base_salary = self.grade_salary.parse().unwrap_or(0.0);
+                    after.description = Self::opt_field(&self.grade_desc);
+                    staged.push((
+                        Some(Record::SalaryGrade(before)),
+                        Some(Record::SalaryGrade(after)),
+                    ));
+                }
+                self.selected_grade = None;
+                self.clear_grade_form();
+            }
+            ConfirmAction::DeleteSalaryGrade { id, .. } => {
+                staged.push((grade(&id).map(Record::SalaryGrade), None));
+            }
+        }
+
+        let count = staged.len();
+        if let Some(draft) = self.draft.as_mut() {
+            for (before, after) in staged {
+                draft.stage(before, after);
+            }
+        }
+        self.notifier
+            .info(format!("Staged {} change(s) in the draft", count));
+    }
+
+    /// Send the draft to the backend; what fails stays in the draft
+    pub fn commit_draft(&mut self) {
+        let Some(draft) = self.draft.as_mut() else {
+            return;
+        };
+        let changes = draft.take_changes();
+        let api = self.api.clone();
+        let notify = self.notifier.clone();
+        let bulk_report = self.bulk_report.clone();
+        let leftovers = self.draft_leftovers.clone();
+        let depts_ref = self.departments.clone();
+        let dept_status = self.dept_status.clone();
+        let grades_ref = self.salary_grades.clone();
+        let grade_status = self.grade_status.clone();
+        let emps_ref = self.employees.clone();
+        let emp_status = self.emp_status.clone();
+        let emp_pager = self.emp_pager.clone();
+        let include_inactive = self.emp_include_inactive;
+        let mutations = (
+            dept_status.begin_mutation(),
+            grade_status.begin_mutation(),
+            emp_status.begin_mutation(),
+        );
+
+        self.spawn(async move {
+            let (report, remaining) = commit_draft(&api, changes).await;
+            if remaining.is_empty() {
+                notify.success(format!("Draft committed: {} change(s)", report.rows.len()));
+            } else {
+                notify.warning(format!(
+                    "Draft committed with {} of {} change(s) failed; they stay in the draft",
+                    report.failed(),
+                    report.rows.len()
+                ));
+                *bulk_report.lock().unwrap() = Some(report);
+            }
+            *leftovers.lock().unwrap() = Some(remaining);
+
+            dept_status
+                .load_into(&depts_ref, &notify, "departments", api.get_departments())
+                .await;
+            grade_status
+                .load_into(
+                    &grades_ref,
+                    &notify,
+                    "salary grades",
+                    api.get_salary_grades(),
+                )
+                .await;
+            emp_pager
+                .reload(
+                    &emps_ref,
+                    &emp_status,
+                    &notify,
+                    "employees",
+                    |page, limit| api.get_employees_page(page, limit, include_inactive),
+                )
+                .await;
+            drop(mutations);
+        });
+    }
+
+    /// Top bar button that starts a draft or opens its review
+    pub fn show_draft_button(&mut self, ui: &mut egui::Ui) {
+        let colors = self.colors;
+        if let Some(remaining) = self.draft_leftovers.lock().unwrap().take() {
+            if let Some(draft) = self.draft.as_mut() {
+                draft.restore(remaining);
+            }
+        }
+        let (label, fill, text) = match &self.draft {
+            Some(draft) => (
+                format!("📝 Draft ({})", draft.len()),
+                colors.primary,
+                colors.on_primary,
+            ),
+            None => (
+                "📝 Start draft".to_string(),
+                colors.surface_variant,
+                colors.on_surface,
+            ),
+        };
+        let button = egui::Button::new(RichText::new(label).size(13.0).color(text))
+            .fill(fill)
+            .stroke(Stroke::new(1.0, colors.outline_variant))
+            .rounding(Rounding::same(20.0))
+            .min_size(Vec2::new(0.0, 40.0));
+        let hover = if self.is_drafting() {
+            "Review, commit or discard the staged changes"
+        } else {
+            "Stage changes locally and commit them together"
+        };
+        if ui.add(button).on_hover_text(hover).clicked() {
+            if self.is_drafting() {
+                self.draft_review_open = true;
+            } else {
+                let owner = self.api.current_user().map(|u| u.id).unwrap_or_default();
+                self.draft = Some(Draft::new(owner));
+            }
+        }
+    }
+
+    /// Review window listing every staged change with its field diff
+    pub fn show_draft_review(&mut self, ctx: &Context) {
+        if !self.draft_review_open {
+            return;
+        }
+        let Some(draft) = self.draft.clone() else {
+            self.draft_review_open = false;
+            return;
+        };
+        let colors = self.colors;
+
+        // Show references by name, including records only in the draft
+        let mut names: HashMap<String, String> = HashMap::new();
+        let base_depts = self.departments.snapshot();
+        let base_emps = self.employees.snapshot();
+        let base_grades = self.salary_grades.snapshot();
+        for d in base_depts
+            .iter()
+            .chain(draft.departments(&base_depts).iter())
+        {
+            names.insert(d.id.clone(), d.name.clone());
+        }
+        for e in base_emps.iter().chain(draft.employees(&base_emps).iter()) {
+            names.insert(e.id.clone(), format!("{} {}", e.first_name, e.last_name));
+        }
+        for g in base_grades
+            .iter()
+            .chain(draft.salary_grades(&base_grades).iter())
+        {
+            names.insert(g.id.clone(), g.code.clone());
+        }
+        let show = |value: &str| {
+            names
+                .get(value)
+                .cloned()
+                .unwrap_or_else(|| value.to_string())
+        };
+
+        let busy = self.emp_status.is_mutating()
+            || self.dept_status.is_mutating()
+            || self.grade_status.is_mutating();
+        let mut close = false;
+        let mut commit = false;
+        let mut discard = false;
+        let mut drop_change = None;
+
+        egui::Window::new("📝 Draft")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .rounding(Rounding::same(16.0))
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(24.0)),
+            )
+            .show(ctx, |ui| {
+                ui.set_min_width(560.0);
+                ui.label(
+                    RichText::new(format!(
+                        "{} staged change(s). Nothing has been sent to the server yet.",
+                        draft.len()
+                    ))
+                    .size(14.0)
+                    .color(colors.on_surface),
+                );
+                ui.add_space(12.0);
+
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        for (i, change) in draft.changes.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(describe(change))
+                                        .size(14.0)
+                                        .strong()
+                                        .color(colors.on_surface),
+                                );
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui
+                                            .small_button("✖")
+                                            .on_hover_text("Remove from the draft")
+                                            .clicked()
+                                        {
+                                            drop_change = Some(i);
+                                        }
+                                    },
+                                );
+                            });
+                            for (field, old, new) in diff(change) {
+                                let text = if old.is_empty() {
+                                    format!("{}: {}", field, show(&new))
+                                } else {
+                                    format!("{}: {} → {}", field, show(&old), show(&new))
+                                };
+                                ui.label(
+                                    RichText::new(text)
+                                        .size(12.0)
+                                        .color(colors.on_surface_variant),
+                                );
+                            }
+                            ui.add_space(8.0);
+                        }
+                    });
+
+                ui.add_space(20.0);
+                ui.horizontal(|ui| {
+                    let button = |text: &str, fill, color| {
+                        egui::Button::new(RichText::new(text).size(13.0).color(color))
+                            .fill(fill)
+                            .stroke(Stroke::new(1.0, colors.outline_variant))
+                            .rounding(Rounding::same(8.0))
+                            .min_size(Vec2::new(100.0, 40.0))
+                    };
+                    if ui
+                        .add(button("Close", colors.surface_variant, colors.on_surface))
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                    ui.add_space(12.0);
+                    if ui
+                        .add(button("Discard draft", colors.error, colors.on_error))
+                        .clicked()
+                    {
+                        discard = true;
+                    }
+                    ui.add_space(12.0);
+                    let ready = !draft.is_empty() && !busy;
+                    if ui
+                        .add_enabled(
+                            ready,
+                            button("Commit all", colors.primary, colors.on_primary),
+                        )
+                        .clicked()
+                    {
+                        commit = true;
+                    }
+                });
+            });
+
+        if let (Some(i), Some(draft)) = (drop_change, self.draft.as_mut()) {
+            draft.unstage(i);
+        }
+        if commit {
+            self.commit_draft();
+            self.draft_review_open = false;
+        } else if discard {
+            self.draft = None;
+            self.draft_review_open = false;
+            self.notifier.info("Draft discarded");
+        } else if close {
+            self.draft_review_open = false;
+        }
+    }
+
+    /// Strip across the top of the content while a draft is open
+    pub fn show_draft_banner(&mut self, ui: &mut egui::Ui) {
+        let Some(count) = self.draft.as_ref().map(Draft::len) else {
+            return;
+        };
+        let colors = self.colors;
+        Frame::none()
+            .fill(colors.primary_container)
+            .rounding(Rounding::same(8.0))
+            .inner_margin(Margin::symmetric(16.0, 8.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!(
+                            "📝 Draft mode: {} change(s) staged locally. The lists show the result.",
+                            count
+                        ))
+                        .size(13.0)
+                        .color(colors.on_primary_container),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Review…").clicked() {
+                            self.draft_review_open = true;
+                        }
+                    });
+                });
+            });
+        ui.add_space(12.0);
+    }
+}
+
+/// The employee after a bulk action, as staged in a draft
+fn bulk_result(action: &BulkAction, target: &BulkTarget, before: &Employee) -> Employee {
+    let mut after = before.clone();
+    debug_assert_eq!(target.id, before.id);
+    match action {
+        BulkAction::MoveToDepartment { id, .. } => after.department_id = Some(id.clone()),
+        BulkAction::SetSalaryGrade { id, .. } => after.salary_grade_id = Some(id.clone()),
+        BulkAction::SetManager { id, .. } => after.manager_id = Some(id.clone()),
+        BulkAction::ChangeRole { role } => after.role = role.clone(),
+        BulkAction::Deactivate => after.active = false,
+    }
+    after
+}
+
+/// The role changes that come with a new department head, as in
+/// `department_head_saga`
+fn head_role_changes(
+    old_head: Option<Employee>,
+    new_head: Option<Employee>,
+) -> Vec<(Option<Record>, Option<Record>)> {
+    let mut staged = Vec::new();
+    let same = matches!((&old_head, &new_head), (Some(a), Some(b)) if a.id == b.id);
+    if let Some(old) = old_head.filter(|e| !same && e.role == "DepartmentHead") {
+        let mut after = old.clone();
+        after.role = "Employee".to_string();
+        staged.push((Some(Record::Employee(old)), Some(Record::Employee(after))));
+    }
+    if let Some(new) = new_head.filter(|e| !same && e.role != "DepartmentHead" && e.role != "Admin")
+    {
+        let mut after = new.clone();
+        after.role = "DepartmentHead".to_string();
+        staged.push((Some(Record::Employee(new)), Some(Record::Employee(after))));
+    }
+    staged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+    use crate::gui::fixtures::{department, employee};
+
+    #[test]
+    fn test_view_is_rebuilt_only_when_draft_or_store_changes() {
+        let mut slot = None;
+        let base = Arc::new(vec![1, 2]);
+        let first = cached(&mut slot, 1, base.clone(), |b| b.to_vec());
+        let again = cached(&mut slot, 1, base.clone(), |_| unreachable!());
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let staged = cached(&mut slot, 2, base, |b| [b, &[3]].concat());
+        assert_eq!(*staged, vec![1, 2, 3]);
+        let reloaded = cached(&mut slot, 2, Arc::new(vec![1]), |b| [b, &[3]].concat());
+        assert_eq!(*reloaded, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_overlay_merges_changes_per_record() {
+        let base = vec![employee("e1").with_department("d1"), employee("e2")];
+        let mut draft = Draft::default();
+        let new_dept = draft.new_id();
+        draft.stage(
+            None,
+            Some(Record::Department(department(&new_dept, "Labs"))),
+        );
+
+        let moved = employee("e1").with_department(new_dept.as_str());
+        draft.stage(
+            Some(Record::Employee(base[0].clone())),
+            Some(Record::Employee(moved.clone())),
+        );
+        let mut renamed = moved.clone();
+        renamed.first_name = "Eve".to_string();
+        draft.stage(
+            Some(Record::Employee(moved)),
+            Some(Record::Employee(renamed)),
+        );
+        draft.stage(Some(Record::Employee(base[1].clone())), None);
+        assert_eq!(draft.len(), 3);
+
+        let emps = draft.employees(&base);
+        assert_eq!(emps.len(), 1);
+        assert_eq!(emps[0].first_name, "Eve");
+        assert_eq!(emps[0].department_id.as_deref(), Some(new_dept.as_str()));
+        // The merged change still diffs against the original record
+        let fields: Vec<&str> = diff(&draft.changes[1]).iter().map(|d| d.0).collect();
+        assert_eq!(fields, ["First name", "Department"]);
+
+        // Dropping the created department unlinks the employee
+        draft.stage(
+            Some(Record::Department(department(&new_dept, "Labs"))),
+            None,
+        );
+        assert_eq!(draft.len(), 2);
+        assert!(draft.departments(&[]).is_empty());
+        assert_eq!(draft.employees(&base)[0].department_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_commit_creates_dependencies_first() {
+        let server = MockServer::start(|req, n| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/departments") => {
+                let mut depts = vec![department("d5", "Labs")];
+                if n > 0 {
+                    depts.insert(0, department("d9", "Labs"));
+                }
+                MockResponse::new(200, &serde_json::to_string(&depts).unwrap())
+            }
+            ("DELETE", "/employees/e2") => MockResponse::new(500, "boom"),
+            _ => MockResponse::new(200, "{}"),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+
+        let mut draft = Draft::default();
+        draft.stage(Some(Record::Employee(employee("e2"))), None);
+        let e1 = employee("e1").with_department("d1").with_version("v1");
+        draft.stage(
+            Some(Record::Employee(e1.clone())),
+            Some(Record::Employee(employee("e1").with_department("draft-1"))),
+        );
+        let id = draft.new_id();
+        draft.stage(None, Some(Record::Department(department(&id, "Labs"))));
+
+        let (report, remaining) = commit_draft(&api, draft.changes).await;
+        let calls: Vec<String> = server
+            .recorded()
+            .iter()
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                "GET /departments",
+                "POST /departments",
+                "GET /departments",
+                "PUT /employees/e1",
+                "DELETE /employees/e2"
+            ]
+        );
+        assert!(server.recorded()[3]
+            .body
+            .contains(r#""department_id":"d9""#));
+        assert_eq!(server.recorded()[3].header("If-Match"), Some("\"v1\""));
+        assert_eq!(report.failed(), 1);
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].after.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_commit_refuses_manager_loops_through_unloaded_staff() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/employees?include_inactive=true") => {
+                let staff = vec![
+                    employee("e1").with_manager("e2"),
+                    employee("e2").with_active(false).with_manager("e3"),
+                    employee("e3"),
+                ];
+                MockResponse::new(200, &serde_json::to_string(&staff).unwrap())
+            }
+            _ => MockResponse::new(200, "{}"),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+
+        let mut draft = Draft::default();
+        let after = employee("e3").with_manager("e1");
+        draft.stage(
+            Some(Record::Employee(employee("e3"))),
+            Some(Record::Employee(after)),
+        );
+
+        let (report, remaining) = commit_draft(&api, draft.changes).await;
+        assert_eq!(report.failed(), 1);
+        assert_eq!(remaining.len(), 1);
+        assert!(server.recorded().iter().all(|r| r.method != "PUT"));
+    }
+}
@@ -0,0 +1,111 @@
+//! Records for the GUI tests
+//!
+//! `employee`, `department` and `grade` build a plain record with every
+//! reference empty; the `with_*` methods fill in what a test cares about,
+//! e.g. `employee("e2").with_manager("e1").with_role("DepartmentHead")`.
+//! Reference setters take `&str` or `Option<&str>`.
+
+use crate::api::models::*;
+
+/// An active "<id> Doe" with the email "<id>@example.com" and the role
+/// Employee
+pub fn employee(id: &str) -> Employee {
+    Employee {
+        id: id.to_string(),
+        first_name: id.to_string(),
+        last_name: "Doe".to_string(),
+        email: format!("{}@example.com", id),
+        role: "Employee".to_string(),
+        active: true,
+        department_id: None,
+        manager_id: None,
+        salary_grade_id: None,
+        hire_date: None,
+        leave_date: None,
+        created_at: None,
+        updated_at: None,
+        deleted_at: None,
+    }
+}
+
+/// A department without a head
+pub fn department(id: &str, name: &str) -> Department {
+    Department {
+        id: id.to_string(),
+        name: name.to_string(),
+        head_id: None,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+pub fn grade(id: &str, code: &str, base_salary: f64) -> SalaryGrade {
+    SalaryGrade {
+        id: id.to_string(),
+        code: code.to_string(),
+        base_salary,
+        description: None,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+fn owned<'a>(id: impl Into<Option<&'a str>>) -> Option<String> {
+    id.into().map(str::to_string)
+}
+
+impl Employee {
+    pub fn with_name(mut self, first: &str, last: &str) -> Self {
+        self.first_name = first.to_string();
+        self.last_name = last.to_string();
+        self
+    }
+
+    pub fn with_email(mut self, email: &str) -> Self {
+        self.email = email.to_string();
+        self
+    }
+
+    pub fn with_role(mut self, role: &str) -> Self {
+        self.role = role.to_string();
+        self
+    }
+
+    pub fn with_department<'a>(mut self, id: impl Into<Option<&'a str>>) -> Self {
+        self.department_id = owned(id);
+        self
+    }
+
+    pub fn with_manager<'a>(mut self, id: impl Into<Option<&'a str>>) -> Self {
+        self.manager_id = owned(id);
+        self
+    }
+
+    pub fn with_grade<'a>(mut self, id: impl Into<Option<&'a str>>) -> Self {
+        self.salary_grade_id = owned(id);
+        self
+    }
+
+    pub fn with_active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+
+    /// Set `updated_at`, the version conditional updates send
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.updated_at = Some(version.to_string());
+        self
+    }
+}
+
+impl Department {
+    pub fn with_head<'a>(mut self, id: impl Into<Option<&'a str>>) -> Self {
+        self.head_id = owned(id);
+        self
+    }
+
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.updated_at = Some(version.to_string());
+        self
+    }
+}
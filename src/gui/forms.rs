@@ -1,6 +1,7 @@
 //! Form handlers for create/edit dialogs
 
 use super::dialogs::{ConfirmAction, EditDialog};
+use super::hierarchy::Hierarchy;
 use super::{styled_dropdown, PersonnelApp};
 use egui::{Frame, Margin, Rounding, Stroke, Ui, Vec2};
 
@@ -8,7 +9,7 @@ impl PersonnelApp {
     /// Show department form in dialog
     pub fn show_department_form(&mut self, ui: &mut Ui, dialog: &EditDialog) {
         let colors = self.colors;
-        let emps = self.employees.lock().unwrap().clone();
+        let emps = self.view_employees();
 
         ui.vertical(|ui| {
             // Department Name
@@ -73,7 +74,12 @@ impl PersonnelApp {
                             self.dept_head_id.clear();
                         }
                         ui.separator();
-                        for emp in &emps {
+                        // Inactive employees are only listed while still chosen
+                        let chosen_head = self.dept_head_id.clone();
+                        for emp in emps
+                            .iter()
+                            .filter(|e| e.is_current() || e.id == chosen_head)
+                        {
                             let is_selected = self.dept_head_id == emp.id;
                             let label =
                                 format!("👤 {} {} - {}", emp.first_name, emp.last_name, emp.role);
@@ -128,7 +134,12 @@ impl PersonnelApp {
 
                 if ui.add(action_btn).clicked() && !self.dept_name.is_empty() {
                     if is_edit {
-                        if let EditDialog::EditDepartment { id, old_head_id } = dialog {
+                        if let EditDialog::EditDepartment {
+                            id,
+                            old_head_id,
+                            version,
+                        } = dialog
+                        {
                             let old_head_name = old_head_id
                                 .as_ref()
                                 .and_then(|hid| emps.iter().find(|e| &e.id == hid))
@@ -153,6 +164,7 @@ impl PersonnelApp {
                                 old_head_name,
                                 new_head_id,
                                 new_head_name,
+                                version: version.clone(),
                             });
                             self.edit_dialog = None;
                         }
@@ -170,9 +182,42 @@ impl PersonnelApp {
     /// Show employee form in dialog
     pub fn show_employee_form(&mut self, ui: &mut Ui, dialog: &EditDialog) {
         let colors = self.colors;
-        let depts = self.departments.lock().unwrap().clone();
-        let emps = self.employees.lock().unwrap().clone();
-        let grades = self.salary_grades.lock().unwrap().clone();
+        let depts = self.view_departments();
+        let emps = self.view_employees();
+        let grades = self.view_salary_grades();
+        let perms = self.permissions();
+
+        // Keep the stored role and department selectable even if this user
+        // could not assign them
+        let current = match dialog {
+            EditDialog::EditEmployee { id, .. } => emps.iter().find(|e| &e.id == id),
+            _ => None,
+        };
+        let mut roles: Vec<&str> = perms
+            .assignable_roles()
+            .iter()
+            .map(|r| r.as_str())
+            .collect();
+        if let Some(emp) = current {
+            if !roles.contains(&emp.role.as_str()) {
+                roles.insert(0, emp.role.as_str());
+            }
+        }
+        let current_dept = current.and_then(|e| e.department_id.as_deref());
+        let dept_choices: Vec<_> = depts
+            .iter()
+            .filter(|d| {
+                perms.can_assign_department(Some(&d.id)) || current_dept == Some(d.id.as_str())
+            })
+            .collect();
+        let allow_no_dept =
+            perms.can_assign_department(None) || (current.is_some() && current_dept.is_none());
+        // A new employee has no reports yet, so only an edit can close a loop
+        let hierarchy = Hierarchy::new(&emps);
+        let editing_id = current.map(|e| e.id.as_str());
+        let manager_problem = editing_id
+            .filter(|_| !self.emp_manager_id.is_empty())
+            .and_then(|id| hierarchy.check(id, &self.emp_manager_id).err());
 
         ui.vertical(|ui| {
             // Row 1: First Name, Last Name
@@ -278,7 +323,7 @@ impl PersonnelApp {
                     .height(300.0)
                     .show_ui(ui, |ui| {
                         ui.set_min_width(405.0);
-                        for role in ["Employee", "DepartmentHead", "DeputyHead", "Admin"] {
+                        for &role in &roles {
                             let is_selected = self.emp_role == role;
                             let label_text = egui::RichText::new(role).color(if is_selected {
                                 colors.on_primary
@@ -317,18 +362,21 @@ impl PersonnelApp {
                     .height(300.0)
                     .show_ui(ui, |ui| {
                         ui.set_min_width(405.0);
-                        let is_none_selected = self.emp_dept_id.is_empty();
-                        let none_text =
-                            egui::RichText::new("❌ No department").color(if is_none_selected {
-                                colors.on_primary
-                            } else {
-                                colors.on_surface
-                            });
-                        if ui.selectable_label(is_none_selected, none_text).clicked() {
-                            self.emp_dept_id.clear();
+                        if allow_no_dept {
+                            let is_none_selected = self.emp_dept_id.is_empty();
+                            let none_text = egui::RichText::new("❌ No department").color(
+                                if is_none_selected {
+                                    colors.on_primary
+                                } else {
+                                    colors.on_surface
+                                },
+                            );
+                            if ui.selectable_label(is_none_selected, none_text).clicked() {
+                                self.emp_dept_id.clear();
+                            }
+                            ui.separator();
                         }
-                        ui.separator();
-                        for dept in &depts {
+                        for dept in &dept_choices {
                             let is_selected = self.emp_dept_id == dept.id;
                             let label_text =
                                 egui::RichText::new(&dept.name).color(if is_selected {
@@ -378,7 +426,13 @@ impl PersonnelApp {
                             self.emp_manager_id.clear();
                         }
                         ui.separator();
-                        for emp in &emps {
+                        // Inactive employees are only listed while still chosen;
+                        // choices that would make a reporting loop are left out
+                        let chosen_manager = self.emp_manager_id.clone();
+                        for emp in emps.iter().filter(|e| {
+                            (e.is_current() || e.id == chosen_manager)
+                                && editing_id.is_none_or(|id| hierarchy.is_valid_manager(id, &e.id))
+                        }) {
                             let is_selected = self.emp_manager_id == emp.id;
                             let label =
                                 format!("{} {} - {}", emp.first_name, emp.last_name, emp.role);
@@ -394,6 +448,15 @@ impl PersonnelApp {
                     })
             });
 
+            if let Some(problem) = &manager_problem {
+                ui.add_space(4.0);
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", problem))
+                        .size(12.0)
+                        .color(colors.error),
+                );
+            }
+
             ui.add_space(12.0);
 
             // Salary Grade dropdown
@@ -430,7 +493,7 @@ impl PersonnelApp {
                             self.emp_salary_grade_id.clear();
                         }
                         ui.separator();
-                        for grade in &grades {
+                        for grade in grades.iter() {
                             let is_selected = self.emp_salary_grade_id == grade.id;
                             let label = format!("{} - ${:.2}", grade.code, grade.base_salary);
                             let label_text = egui::RichText::new(label).color(if is_selected {
@@ -481,16 +544,21 @@ impl PersonnelApp {
                 .rounding(Rounding::same(8.0))
                 .min_size(Vec2::new(100.0, 40.0));
 
-                if ui.add(action_btn).clicked()
+                let clicked = ui.add(action_btn).clicked();
+                if clicked && manager_problem.is_some() {
+                    self.notifier
+                        .warning(manager_problem.clone().unwrap_or_default());
+                } else if clicked
                     && !self.emp_first_name.is_empty()
                     && !self.emp_last_name.is_empty()
                 {
                     let name = format!("{} {}", self.emp_first_name, self.emp_last_name);
                     if is_edit {
-                        if let EditDialog::EditEmployee { id } = dialog {
+                        if let EditDialog::EditEmployee { id, version } = dialog {
                             self.confirm_dialog = Some(ConfirmAction::UpdateEmployee {
                                 id: id.clone(),
                                 name,
+                                version: version.clone(),
                             });
                             self.edit_dialog = None;
                         }
@@ -625,10 +693,11 @@ impl PersonnelApp {
 
                 if ui.add(action_btn).clicked() && !self.grade_code.is_empty() {
                     if is_edit {
-                        if let EditDialog::EditSalaryGrade { id } = dialog {
+                        if let EditDialog::EditSalaryGrade { id, version } = dialog {
                             self.confirm_dialog = Some(ConfirmAction::UpdateSalaryGrade {
                                 id: id.clone(),
                                 code: self.grade_code.clone(),
+                                version: version.clone(),
                             });
                             self.edit_dialog = None;
                         }
@@ -0,0 +1,457 @@
+//! Deleting a salary grade that is still in use
+//!
+//! Employees on the grade have to be moved to a replacement grade first,
+//! otherwise they keep a `salary_grade_id` pointing nowhere. The dialog
+//! lists everyone affected (inactive employees included) with their salary
+//! change and the resulting payroll impact, and only deletes the grade once
+//! every regrading went through.
+
+use super::bulk::{BulkReport, BulkRow};
+use super::components::styled_dropdown;
+use super::permissions::Permissions;
+use super::{ConfirmAction, PersonnelApp};
+use crate::api::client::ApiClient;
+use crate::api::models::*;
+use egui::{Context, Frame, Margin, RichText, Rounding, Stroke, Vec2};
+
+#[derive(Debug, Clone)]
+pub struct GradeRemoval {
+    pub grade: SalaryGrade,
+    /// `None` until the affected employees arrived
+    pub affected: Option<Vec<Employee>>,
+    /// Grades the affected employees can move to
+    pub replacements: Vec<SalaryGrade>,
+    pub replacement: Option<String>,
+}
+
+impl GradeRemoval {
+    pub fn new(grade: SalaryGrade, grades: &[SalaryGrade]) -> Self {
+        let replacements = grades
+            .iter()
+            .filter(|g| g.id != grade.id)
+            .cloned()
+            .collect();
+        Self {
+            grade,
+            affected: None,
+            replacements,
+            replacement: None,
+        }
+    }
+
+    /// Keep the employees of `staff` that are on the grade being deleted
+    pub fn set_staff(&mut self, staff: &[Employee]) {
+        self.affected = Some(
+            staff
+                .iter()
+                .filter(|e| e.salary_grade_id.as_deref() == Some(self.grade.id.as_str()))
+                .cloned()
+                .collect(),
+        );
+    }
+
+    fn replacement_grade(&self) -> Option<&SalaryGrade> {
+        let id = self.replacement.as_deref()?;
+        self.replacements.iter().find(|g| g.id == id)
+    }
+
+    /// Salary change per employee when moving to the replacement grade
+    pub fn deltas(&self) -> Vec<(&Employee, f64)> {
+        let new_salary = self.replacement_grade().map_or(0.0, |g| g.base_salary);
+        self.affected
+            .iter()
+            .flatten()
+            .map(|e| (e, new_salary - self.grade.base_salary))
+            .collect()
+    }
+
+    /// Change of the payroll; only current employees are paid
+    pub fn payroll_delta(&self) -> f64 {
+        self.deltas()
+            .iter()
+            .filter(|(e, _)| e.is_current())
+            .map(|(_, d)| d)
+            .sum()
+    }
+
+    /// Reasons the deletion cannot go ahead yet
+    pub fn problems(&self, perms: &Permissions) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !perms.can_manage_salary_grades() {
+            problems.push("Only admins may delete salary grades.".to_string());
+        }
+        let in_use = self.affected.as_ref().is_some_and(|a| !a.is_empty());
+        if in_use && self.replacement_grade().is_none() {
+            problems.push("Choose a replacement grade for the affected employees.".to_string());
+        }
+        problems
+    }
+}
+
+/// Move every affected employee to the replacement grade, then delete the
+/// grade. Nothing is deleted if a regrading failed.
+pub async fn apply_grade_removal(api: &ApiClient, plan: &GradeRemoval) -> BulkReport {
+    let mut rows = Vec::new();
+    if let Some(new_grade) = plan.replacement_grade() {
+        let req = UpdateEmployeeRequest {
+            salary_grade_id: Some(Some(new_grade.id.clone())),
+            ..Default::default()
+        };
+        for emp in plan.affected.iter().flatten() {
+            let result = api
+                .update_employee(&emp.id, &req, emp.updated_at.as_deref())
+                .await;
+            rows.push(BulkRow {
+                id: emp.id.clone(),
+                name: format!(
+                    "{} {}: regraded to {}",
+                    emp.first_name, emp.last_name, new_grade.code
+                ),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+    }
+
+    let grade = &plan.grade;
+    let result = if rows.iter().any(|r| r.error.is_some()) {
+        Err("Skipped because an employee could not be regraded.".to_string())
+    } else {
+        api.delete_salary_grade(&grade.id)
+            .await
+            .map_err(|e| e.to_string())
+    };
+    rows.push(BulkRow {
+        id: grade.id.clone(),
+        name: format!("{} deleted", grade.code),
+        error: result.err(),
+    });
+
+    BulkReport {
+        title: format!("Delete {}", grade.code),
+        rows,
+    }
+}
+
+fn signed_amount(value: f64) -> String {
+    if value < 0.0 {
+        format!("-${:.2}", -value)
+    } else {
+        format!("+${:.2}", value)
+    }
+}
+
+impl PersonnelApp {
+    /// Start deleting `grade`. The holders are always counted on the
+    /// server, inactive employees included; if there are none, the plain
+    /// confirmation takes over from the regrading dialog.
+    pub fn confirm_grade_delete(&mut self, grade: &SalaryGrade) {
+        self.grade_removal = Some(GradeRemoval::new(
+            grade.clone(),
+            &self.salary_grades.snapshot(),
+        ));
+        let api = self.api.clone();
+        let staff = self.grade_removal_staff.clone();
+        let notify = self.notifier.clone();
+        *staff.lock().unwrap() = None;
+        self.spawn(async move {
+            // Inactive employees keep their grade, so they are regraded too
+            match api.get_employees(true).await {
+                Ok(list) => *staff.lock().unwrap() = Some(list),
+                Err(e) => notify.api_error("Could not load the affected employees", &e),
+            }
+        });
+    }
+
+    fn apply_grade_removal(&mut self, plan: GradeRemoval) {
+        if let Some(problem) = plan.problems(&self.permissions()).into_iter().next() {
+            self.notifier.warning(problem);
+            return;
+        }
+
+        let api = self.api.clone();
+        let emps_ref = self.employees.clone();
+        let emp_status = self.emp_status.clone();
+        let emp_pager = self.emp_pager.clone();
+        let include_inactive = self.emp_include_inactive;
+        let grades_ref = self.salary_grades.clone();
+        let grade_status = self.grade_status.clone();
+        let notify = self.notifier.clone();
+        let bulk_report = self.bulk_report.clone();
+        let mutations = (emp_status.begin_mutation(), grade_status.begin_mutation());
+
+        self.spawn(async move {
+            let report = apply_grade_removal(&api, &plan).await;
+            let code = &plan.grade.code;
+            if report.failed() == 0 {
+                notify.success(format!("Salary grade \"{}\" deleted", code));
+            } else {
+                notify.warning(format!(
+                    "Deleting \"{}\" incomplete: {} of {} step(s) failed",
+                    code,
+                    report.failed(),
+                    report.rows.len()
+                ));
+            }
+            *bulk_report.lock().unwrap() = Some(report);
+            grade_status
+                .load_into(
+                    &grades_ref,
+                    &notify,
+                    "salary grades",
+                    api.get_salary_grades(),
+                )
+                .await;
+            emp_pager
+                .reload(
+                    &emps_ref,
+                    &emp_status,
+                    &notify,
+                    "employees",
+                    |page, limit| api.get_employees_page(page, limit, include_inactive),
+                )
+                .await;
+            drop(mutations);
+        });
+    }
+
+    /// Show the regrading dialog, if a grade deletion is in progress
+    pub fn show_grade_removal(&mut self, ctx: &Context) {
+        let colors = self.colors;
+        let Some(mut plan) = self.grade_removal.take() else {
+            return;
+        };
+        if let Some(staff) = self.grade_removal_staff.lock().unwrap().take() {
+            match &self.draft {
+                Some(draft) => plan.set_staff(&draft.employees(&staff)),
+                None => plan.set_staff(&staff),
+            }
+            if plan.affected.as_ref().is_some_and(|a| a.is_empty()) {
+                self.confirm_dialog = Some(ConfirmAction::DeleteSalaryGrade {
+                    id: plan.grade.id.clone(),
+                    code: plan.grade.code.clone(),
+                    employee_count: 0,
+                });
+                return;
+            }
+            if self.refuse_wizard_in_draft(&format!("Deleting {}", plan.grade.code)) {
+                return;
+            }
+        }
+
+        let perms = self.permissions();
+        let busy = self.emp_status.is_mutating() || self.grade_status.is_mutating();
+        let mut open = true;
+        let mut apply = false;
+
+        egui::Window::new(format!("🗑 Delete Salary Grade {}", plan.grade.code))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .rounding(Rounding::same(16.0))
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(24.0)),
+            )
+            .show(ctx, |ui| {
+                ui.set_min_width(520.0);
+                match &plan.affected {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Spinner::new().color(colors.primary));
+                            ui.label(
+                                RichText::new("Looking up affected employees…")
+                                    .color(colors.on_surface_variant),
+                            );
+                        });
+                    }
+                    Some(affected) => {
+                        ui.label(
+                            RichText::new(format!(
+                                "{} employee(s) are on salary grade \"{}\" (${:.2}). \
+                                Choose the grade they move to.",
+                                affected.len(),
+                                plan.grade.code,
+                                plan.grade.base_salary
+                            ))
+                            .size(14.0)
+                            .color(colors.on_surface),
+                        );
+                        ui.add_space(12.0);
+                        show_replacement_dropdown(ui, &colors, &mut plan);
+                        ui.add_space(12.0);
+                        show_deltas(ui, &colors, &plan);
+                    }
+                }
+                for problem in plan.problems(&perms) {
+                    ui.label(RichText::new(problem).size(12.0).color(colors.error));
+                }
+
+                ui.add_space(24.0);
+                ui.horizontal(|ui| {
+                    let button = |text: &str, fill, color| {
+                        egui::Button::new(RichText::new(text).size(13.0).color(color))
+                            .fill(fill)
+                            .stroke(Stroke::new(1.0, colors.outline_variant))
+                            .rounding(Rounding::same(8.0))
+                            .min_size(Vec2::new(100.0, 40.0))
+                    };
+                    if ui
+                        .add(button("Cancel", colors.surface_variant, colors.on_surface))
+                        .clicked()
+                    {
+                        open = false;
+                    }
+                    ui.add_space(12.0);
+                    let ready =
+                        plan.affected.is_some() && plan.problems(&perms).is_empty() && !busy;
+                    let delete = button("Delete", colors.error, colors.on_error);
+                    if ui.add_enabled(ready, delete).clicked() {
+                        apply = true;
+                    }
+                });
+            });
+
+        if apply {
+            self.apply_grade_removal(plan);
+        } else if open {
+            self.grade_removal = Some(plan);
+        }
+    }
+}
+
+fn show_replacement_dropdown(
+    ui: &mut egui::Ui,
+    colors: &super::Material3Colors,
+    plan: &mut GradeRemoval,
+) {
+    let current = plan
+        .replacement_grade()
+        .map(|g| format!("{} - ${:.2}", g.code, g.base_salary))
+        .unwrap_or_else(|| "Choose a replacement...".to_string());
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Replacement grade").color(colors.on_surface_variant));
+        styled_dropdown(ui, colors, |ui| {
+            egui::ComboBox::from_id_salt("grade_removal_replacement")
+                .selected_text(RichText::new(current).color(colors.on_surface))
+                .width(240.0)
+                .show_ui(ui, |ui| {
+                    for grade in &plan.replacements {
+                        let selected = plan.replacement.as_deref() == Some(grade.id.as_str());
+                        let label =
+                            RichText::new(format!("{} - ${:.2}", grade.code, grade.base_salary))
+                                .color(colors.on_surface);
+                        if ui.selectable_label(selected, label).clicked() {
+                            plan.replacement = Some(grade.id.clone());
+                        }
+                    }
+                });
+        });
+    });
+}
+
+fn show_deltas(ui: &mut egui::Ui, colors: &super::Material3Colors, plan: &GradeRemoval) {
+    let delta_color = |d: f64| {
+        if d < 0.0 {
+            colors.error
+        } else if d > 0.0 {
+            colors.success
+        } else {
+            colors.on_surface_variant
+        }
+    };
+    egui::ScrollArea::vertical()
+        .max_height(260.0)
+        .show(ui, |ui| {
+            egui::Grid::new("grade_removal_grid")
+                .num_columns(2)
+                .spacing([24.0, 6.0])
+                .show(ui, |ui| {
+                    for (emp, delta) in plan.deltas() {
+                        let mut name = format!("{} {}", emp.first_name, emp.last_name);
+                        if !emp.is_current() {
+                            name.push_str(" (inactive)");
+                        }
+                        ui.label(RichText::new(name).size(13.0).color(colors.on_surface));
+                        let text = if plan.replacement.is_some() {
+                            signed_amount(delta)
+                        } else {
+                            "—".to_string()
+                        };
+                        ui.label(RichText::new(text).size(13.0).color(delta_color(delta)));
+                        ui.end_row();
+                    }
+                });
+        });
+    if plan.replacement.is_some() {
+        ui.add_space(8.0);
+        let total = plan.payroll_delta();
+        ui.label(
+            RichText::new(format!("Payroll impact: {}", signed_amount(total)))
+                .size(14.0)
+                .strong()
+                .color(delta_color(total)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+    use crate::gui::fixtures::{employee, grade};
+
+    fn plan() -> GradeRemoval {
+        let grades = [grade("g1", "G1", 3000.0), grade("g2", "G2", 3250.0)];
+        let mut plan = GradeRemoval::new(grades[0].clone(), &grades);
+        plan.set_staff(&[
+            employee("ann").with_grade("g1"),
+            employee("bob").with_grade("g1").with_active(false),
+            employee("cat").with_grade("g2"),
+        ]);
+        plan
+    }
+
+    #[test]
+    fn test_payroll_delta_counts_current_employees() {
+        let mut plan = plan();
+        assert_eq!(plan.affected.as_ref().unwrap().len(), 2);
+        assert_eq!(plan.replacements.len(), 1);
+
+        let admin = Permissions {
+            role: crate::gui::permissions::Role::Admin,
+            department_id: None,
+        };
+        assert_eq!(plan.problems(&admin).len(), 1);
+
+        plan.replacement = Some("g2".to_string());
+        assert!(plan.problems(&admin).is_empty());
+        assert!(plan.deltas().iter().all(|(_, d)| *d == 250.0));
+        assert_eq!(plan.payroll_delta(), 250.0);
+        assert_eq!(signed_amount(-12.5), "-$12.50");
+        assert_eq!(signed_amount(250.0), "+$250.00");
+    }
+
+    #[tokio::test]
+    async fn test_apply_regrades_before_deleting() {
+        let server = MockServer::start(|req, _| {
+            if req.path.ends_with("/bob") {
+                MockResponse::new(500, "boom")
+            } else {
+                MockResponse::new(200, "{}")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let mut plan = plan();
+        plan.replacement = Some("g2".to_string());
+
+        let report = apply_grade_removal(&api, &plan).await;
+        assert_eq!(report.rows.len(), 3);
+        assert_eq!(report.failed(), 2);
+        let requests = server.recorded();
+        assert!(requests[0].body.contains(r#""salary_grade_id":"g2""#));
+        assert!(requests.iter().all(|r| r.method != "DELETE"));
+    }
+}
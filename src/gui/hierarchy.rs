@@ -0,0 +1,150 @@
+//! Validation of the reporting hierarchy
+//!
+//! An employee must not report to themselves, and following `manager_id`
+//! upwards must never lead back to where it started. The forms check the
+//! loaded employees while a manager is chosen; since those are paginated and
+//! may hide inactive staff, the change is checked again against the complete
+//! list from the server before it is sent.
+
+use crate::api::client::ApiClient;
+use crate::api::models::Employee;
+use std::collections::HashMap;
+
+/// Manager and name lookups over a list of employees
+pub struct Hierarchy<'a> {
+    managers: HashMap<&'a str, &'a str>,
+    names: HashMap<&'a str, String>,
+}
+
+impl<'a> Hierarchy<'a> {
+    pub fn new(employees: &'a [Employee]) -> Self {
+        Self {
+            managers: employees
+                .iter()
+                .filter_map(|e| Some((e.id.as_str(), e.manager_id.as_deref()?)))
+                .collect(),
+            names: employees
+                .iter()
+                .map(|e| (e.id.as_str(), format!("{} {}", e.first_name, e.last_name)))
+                .collect(),
+        }
+    }
+
+    /// The chain that would loop if `employee` reported to `manager`:
+    /// `manager`, their manager and so on, ending with `employee`. `None` if
+    /// the choice is fine.
+    pub fn cycle(&self, employee: &str, manager: &str) -> Option<Vec<String>> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut current = manager;
+        loop {
+            if current == employee {
+                chain.push(current.to_string());
+                return Some(chain);
+            }
+            if chain.iter().any(|id| id == current) {
+                // An existing loop further up that does not involve `employee`
+                return None;
+            }
+            chain.push(current.to_string());
+            current = self.managers.get(current)?;
+        }
+    }
+
+    /// Whether `manager` may be chosen for `employee`
+    pub fn is_valid_manager(&self, employee: &str, manager: &str) -> bool {
+        self.cycle(employee, manager).is_none()
+    }
+
+    /// Explain why `manager` cannot be chosen for `employee`
+    pub fn check(&self, employee: &str, manager: &str) -> Result<(), String> {
+        let Some(chain) = self.cycle(employee, manager) else {
+            return Ok(());
+        };
+        if chain.len() == 1 {
+            return Err(format!(
+                "{} cannot be their own manager.",
+                self.name(employee)
+            ));
+        }
+        let path: Vec<String> = std::iter::once(employee)
+            .chain(chain.iter().map(String::as_str))
+            .map(|id| self.name(id))
+            .collect();
+        Err(format!(
+            "{} cannot report to {}, the chain would loop: {}.",
+            self.name(employee),
+            self.name(manager),
+            path.join(" → ")
+        ))
+    }
+
+    fn name(&self, id: &str) -> String {
+        self.names
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// Every employee, inactive ones included, for checking reporting lines
+/// that may run through records the views have not loaded
+pub async fn complete_staff(api: &ApiClient) -> Result<Vec<Employee>, String> {
+    api.get_employees(true)
+        .await
+        .map_err(|e| format!("Could not check the reporting line: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::employee;
+
+    #[test]
+    fn test_cycle_detection() {
+        // c reports to b, b to a
+        let emps = vec![
+            employee("a"),
+            employee("b").with_manager("a"),
+            employee("c").with_manager("b"),
+        ];
+        let tree = Hierarchy::new(&emps);
+        assert_eq!(tree.cycle("a", "a").unwrap(), ["a"]);
+        assert_eq!(tree.cycle("a", "c").unwrap(), ["c", "b", "a"]);
+        assert!(tree.is_valid_manager("c", "a"));
+        assert!(tree.is_valid_manager("a", "unknown"));
+        assert!(!tree.is_valid_manager("b", "c"));
+    }
+
+    #[test]
+    fn test_existing_loop_elsewhere_is_not_reported() {
+        let emps = vec![
+            employee("x").with_manager("y"),
+            employee("y").with_manager("x"),
+            employee("z"),
+        ];
+        let tree = Hierarchy::new(&emps);
+        assert!(tree.is_valid_manager("z", "x"));
+        assert!(!tree.is_valid_manager("x", "y"));
+    }
+
+    #[test]
+    fn test_check_explains_the_chain() {
+        let emps = vec![
+            employee("a").with_name("A", "Doe"),
+            employee("b").with_name("B", "Doe").with_manager("a"),
+        ];
+        let tree = Hierarchy::new(&emps);
+        assert_eq!(
+            tree.check("a", "a"),
+            Err("A Doe cannot be their own manager.".to_string())
+        );
+        assert_eq!(
+            tree.check("a", "b"),
+            Err(
+                "A Doe cannot report to B Doe, the chain would loop: A Doe → B Doe → A Doe."
+                    .to_string()
+            )
+        );
+        assert_eq!(tree.check("b", "a"), Ok(()));
+    }
+}
@@ -0,0 +1,1114 @@
+//! Undo and redo of confirmed changes
+//!
+//! Every successful create, update and delete from `execute_confirmed_action`
+//! is recorded as a `HistoryEntry` holding snapshots of the affected records
+//! before and after the change. Undo moves each record back to its `before`
+//! snapshot, redo forward to its `after` one:
+//!
+//! - both snapshots present: the record is updated, with the version of the
+//!   state it is leaving as If-Match, so changes made elsewhere in the
+//!   meantime are not overwritten
+//! - only `before`/`after` present: the record is deleted or created
+//!
+//! The backend assigns a new id to a re-created record. The old id is then
+//! replaced throughout the history, and the records that pointed at the
+//! deleted one (members, reports, headed departments) are pointed at the new
+//! one again.
+//!
+//! Before an entry is applied, each of its changes is checked against the
+//! user's permissions as the create, update or delete it amounts to.
+
+use super::dialogs::ConfirmAction;
+use super::notifications::Notifier;
+use super::permissions::Permissions;
+use super::PersonnelApp;
+use crate::api::client::ApiClient;
+use crate::api::error::ApiError;
+use crate::api::models::*;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// How many entries the undo stack keeps
+const MAX_ENTRIES: usize = 50;
+
+/// Snapshot of a single record
+#[derive(Debug, Clone)]
+pub enum Record {
+    Department(Department),
+    Employee(Employee),
+    SalaryGrade(SalaryGrade),
+}
+
+impl Record {
+    pub fn id(&self) -> &str {
+        match self {
+            Record::Department(d) => &d.id,
+            Record::Employee(e) => &e.id,
+            Record::SalaryGrade(g) => &g.id,
+        }
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        match self {
+            Record::Department(d) => d.updated_at.as_deref(),
+            Record::Employee(e) => e.updated_at.as_deref(),
+            Record::SalaryGrade(g) => g.updated_at.as_deref(),
+        }
+    }
+
+    /// Replace the id `old` by `new`, in the record itself and in its
+    /// references to other records
+    pub fn remap(&mut self, old: &str, new: &str) {
+        let swap = |id: &mut String| {
+            if id == old {
+                *id = new.to_string();
+            }
+        };
+        let swap_opt = |id: &mut Option<String>| {
+            if id.as_deref() == Some(old) {
+                *id = Some(new.to_string());
+            }
+        };
+        match self {
+            Record::Department(d) => {
+                swap(&mut d.id);
+                swap_opt(&mut d.head_id);
+            }
+            Record::Employee(e) => {
+                swap(&mut e.id);
+                swap_opt(&mut e.department_id);
+                swap_opt(&mut e.manager_id);
+                swap_opt(&mut e.salary_grade_id);
+            }
+            Record::SalaryGrade(g) => swap(&mut g.id),
+        }
+    }
+
+    /// Bring the server's copy in line with this snapshot
+    pub async fn update(&self, api: &ApiClient, version: Option<&str>) -> Result<(), ApiError> {
+        match self {
+            Record::Department(d) => {
+                let req = UpdateDepartmentRequest {
+                    name: Some(d.name.clone()),
+                    head_id: Some(d.head_id.clone()),
+                };
+                api.update_department(&d.id, &req, version).await
+            }
+            Record::Employee(e) => {
+                let req = UpdateEmployeeRequest {
+                    first_name: Some(e.first_name.clone()),
+                    last_name: Some(e.last_name.clone()),
+                    email: Some(e.email.clone()),
+                    role: Some(e.role.clone()),
+                    active: Some(e.active),
+                    department_id: Some(e.department_id.clone()),
+                    manager_id: Some(e.manager_id.clone()),
+                    salary_grade_id: Some(e.salary_grade_id.clone()),
+                    hire_date: Some(e.hire_date.clone()),
+                    leave_date: Some(e.leave_date.clone()),
+                };
+                api.update_employee(&e.id, &req, version).await
+            }
+            Record::SalaryGrade(g) => {
+                let req = UpdateSalaryGradeRequest {
+                    code: Some(g.code.clone()),
+                    base_salary: Some(g.base_salary),
+                    description: Some(g.description.clone()),
+                };
+                api.update_salary_grade(&g.id, &req, version).await
+            }
+        }
+    }
+
+    pub async fn create(&self, api: &ApiClient) -> Result<(), ApiError> {
+        match self {
+            Record::Department(d) => {
+                let req = CreateDepartmentRequest {
+                    name: d.name.clone(),
+                    head_id: d.head_id.clone(),
+                };
+                api.create_department(&req).await
+            }
+            Record::Employee(e) => {
+                let req = CreateEmployeeRequest {
+                    first_name: e.first_name.clone(),
+                    last_name: e.last_name.clone(),
+                    email: e.email.clone(),
+                    department_id: e.department_id.clone(),
+                    manager_id: e.manager_id.clone(),
+                    role: Some(e.role.clone()),
+                    salary_grade_id: e.salary_grade_id.clone(),
+                    hire_date: e.hire_date.clone(),
+                };
+                api.create_employee(&req).await
+            }
+            Record::SalaryGrade(g) => {
+                let req = CreateSalaryGradeRequest {
+                    code: g.code.clone(),
+                    base_salary: g.base_salary,
+                    description: g.description.clone(),
+                };
+                api.create_salary_grade(&req).await
+            }
+        }
+    }
+
+    pub async fn delete(&self, api: &ApiClient) -> Result<(), ApiError> {
+        match self {
+            Record::Department(d) => api.delete_department(&d.id).await,
+            Record::Employee(e) => api.delete_employee(&e.id).await,
+            Record::SalaryGrade(g) => api.delete_salary_grade(&g.id).await,
+        }
+    }
+
+    /// The server's current copy of this record
+    pub async fn fetch(&self, api: &ApiClient) -> Result<Record, ApiError> {
+        Ok(match self {
+            Record::Department(d) => Record::Department(api.get_department(&d.id).await?),
+            Record::Employee(e) => Record::Employee(api.get_employee(&e.id).await?),
+            Record::SalaryGrade(g) => Record::SalaryGrade(api.get_salary_grade(&g.id).await?),
+        })
+    }
+
+    /// Ids of all records of this kind, taken before a create so the new
+    /// record can be told apart from existing ones
+    pub async fn existing_ids(&self, api: &ApiClient) -> Result<HashSet<String>, ApiError> {
+        Ok(match self {
+            Record::Department(_) => api
+                .get_departments()
+                .await?
+                .into_iter()
+                .map(|d| d.id)
+                .collect(),
+            Record::Employee(_) => api
+                .get_employees(true)
+                .await?
+                .into_iter()
+                .map(|e| e.id)
+                .collect(),
+            Record::SalaryGrade(_) => api
+                .get_salary_grades()
+                .await?
+                .into_iter()
+                .map(|g| g.id)
+                .collect(),
+        })
+    }
+
+    /// Find the record created from this snapshot. Creates do not return
+    /// the new id, so it is looked up by name, email or code among the
+    /// records that are not in `existing`.
+    pub async fn find_created(
+        &self,
+        api: &ApiClient,
+        existing: &HashSet<String>,
+    ) -> Result<Option<Record>, ApiError> {
+        let new = |id: &String| !existing.contains(id);
+        Ok(match self {
+            Record::Department(d) => api
+                .get_departments()
+                .await?
+                .into_iter()
+                .find(|x| new(&x.id) && x.name == d.name)
+                .map(Record::Department),
+            Record::Employee(e) => api
+                .get_employees(true)
+                .await?
+                .into_iter()
+                .find(|x| new(&x.id) && x.email.eq_ignore_ascii_case(&e.email))
+                .map(Record::Employee),
+            Record::SalaryGrade(g) => api
+                .get_salary_grades()
+                .await?
+                .into_iter()
+                .find(|x| new(&x.id) && x.code == g.code)
+                .map(Record::SalaryGrade),
+        })
+    }
+
+    /// Records that point at this one and lose that link when it is deleted
+    pub async fn fetch_references(&self, api: &ApiClient) -> Result<Vec<Record>, ApiError> {
+        let id = Some(self.id());
+        Ok(match self {
+            Record::Department(d) => api
+                .get_employees_by_department(&d.id)
+                .await?
+                .into_iter()
+                .map(Record::Employee)
+                .collect(),
+            Record::Employee(_) => {
+                let reports = api.get_employees(true).await?;
+                let headed = api.get_departments().await?;
+                reports
+                    .into_iter()
+                    .filter(|e| e.manager_id.as_deref() == id)
+                    .map(Record::Employee)
+                    .chain(
+                        headed
+                            .into_iter()
+                            .filter(|d| d.head_id.as_deref() == id)
+                            .map(Record::Department),
+                    )
+                    .collect()
+            }
+            Record::SalaryGrade(_) => api
+                .get_employees(true)
+                .await?
+                .into_iter()
+                .filter(|e| e.salary_grade_id.as_deref() == id)
+                .map(Record::Employee)
+                .collect(),
+        })
+    }
+
+    /// Point `reference` at this record again after it was re-created
+    async fn restore_reference(&self, api: &ApiClient, reference: &Record) -> Result<(), ApiError> {
+        let id = Some(Some(self.id().to_string()));
+        match (self, reference) {
+            (Record::Employee(_), Record::Department(d)) => {
+                let req = UpdateDepartmentRequest {
+                    name: Some(d.name.clone()),
+                    head_id: id,
+                };
+                api.update_department(&d.id, &req, None).await
+            }
+            (_, Record::Employee(e)) => {
+                let mut req = UpdateEmployeeRequest::default();
+                match self {
+                    Record::Department(_) => req.department_id = id,
+                    Record::Employee(_) => req.manager_id = id,
+                    Record::SalaryGrade(_) => req.salary_grade_id = id,
+                }
+                api.update_employee(&e.id, &req, None).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// One record's part of a history entry. `before` is `None` for a created
+/// record, `after` for a deleted one.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub before: Option<Record>,
+    pub after: Option<Record>,
+    /// Records that pointed at a deleted record
+    pub references: Vec<Record>,
+}
+
+impl Change {
+    pub fn created(record: Record) -> Self {
+        Self {
+            before: None,
+            after: Some(record),
+            references: Vec::new(),
+        }
+    }
+
+    pub fn updated(before: Record, after: Record) -> Self {
+        Self {
+            before: Some(before),
+            after: Some(after),
+            references: Vec::new(),
+        }
+    }
+
+    pub fn deleted(record: Record, references: Vec<Record>) -> Self {
+        Self {
+            before: Some(record),
+            after: None,
+            references,
+        }
+    }
+
+    pub fn remap(&mut self, old: &str, new: &str) {
+        for record in self
+            .before
+            .iter_mut()
+            .chain(self.after.iter_mut())
+            .chain(self.references.iter_mut())
+        {
+            record.remap(old, new);
+        }
+    }
+
+    /// Move the record from one snapshot to the other. Returns the id change
+    /// if the record had to be re-created.
+    async fn apply(
+        &mut self,
+        api: &ApiClient,
+        direction: Direction,
+    ) -> Result<Option<(String, String)>, String> {
+        let (from, to) = match direction {
+            Direction::Undo => (self.after.clone(), self.before.clone()),
+            Direction::Redo => (self.before.clone(), self.after.clone()),
+        };
+        let fresh = match (from, to) {
+            (Some(from), Some(to)) => {
+                to.update(api, from.version()).await.map_err(describe)?;
+                Some(to.fetch(api).await.map_err(describe)?)
+            }
+            (Some(from), None) => {
+                from.delete(api).await.map_err(describe)?;
+                None
+            }
+            (None, Some(to)) => {
+                let existing = to.existing_ids(api).await.map_err(describe)?;
+                to.create(api).await.map_err(describe)?;
+                let created = to
+                    .find_created(api, &existing)
+                    .await
+                    .map_err(describe)?
+                    .ok_or_else(|| "The re-created record could not be found.".to_string())?;
+                let old = to.id().to_string();
+                let new = created.id().to_string();
+                self.remap(&old, &new);
+                let mut target = to;
+                target.remap(&old, &new);
+
+                // Creates do not carry every field, e.g. `active`
+                target.update(api, None).await.map_err(describe)?;
+                for reference in &self.references {
+                    target
+                        .restore_reference(api, reference)
+                        .await
+                        .map_err(describe)?;
+                }
+                let fresh = target.fetch(api).await.map_err(describe)?;
+                self.set(direction, Some(fresh));
+                return Ok(Some((old, new)));
+            }
+            (None, None) => None,
+        };
+        self.set(direction, fresh);
+        Ok(None)
+    }
+
+    /// Whether the current user may move the record in `direction`, checked
+    /// like the confirmed action it amounts to
+    fn check(&self, perms: &Permissions, direction: Direction) -> Result<(), String> {
+        let (from, to) = match direction {
+            Direction::Undo => (self.after.as_ref(), self.before.as_ref()),
+            Direction::Redo => (self.before.as_ref(), self.after.as_ref()),
+        };
+        let action = match (from, to) {
+            (Some(Record::Employee(current)), Some(Record::Employee(e))) => {
+                return perms.check_employee_change(
+                    Some(current),
+                    e.department_id.as_deref(),
+                    &e.role,
+                );
+            }
+            (None, Some(Record::Employee(e))) => {
+                return perms.check_employee_change(None, e.department_id.as_deref(), &e.role);
+            }
+            (Some(Record::Department(d)), Some(_)) => ConfirmAction::UpdateDepartment {
+                id: d.id.clone(),
+                name: d.name.clone(),
+                old_head_id: None,
+                old_head_name: None,
+                new_head_id: None,
+                new_head_name: None,
+                version: None,
+            },
+            (Some(Record::SalaryGrade(g)), Some(_)) => ConfirmAction::UpdateSalaryGrade {
+                id: g.id.clone(),
+                code: g.code.clone(),
+                version: None,
+            },
+            (Some(from), None) => match from {
+                Record::Department(d) => ConfirmAction::DeleteDepartment {
+                    id: d.id.clone(),
+                    name: d.name.clone(),
+                    employee_count: 0,
+                },
+                Record::Employee(e) => ConfirmAction::DeleteEmployee {
+                    id: e.id.clone(),
+                    name: format!("{} {}", e.first_name, e.last_name),
+                },
+                Record::SalaryGrade(g) => ConfirmAction::DeleteSalaryGrade {
+                    id: g.id.clone(),
+                    code: g.code.clone(),
+                    employee_count: 0,
+                },
+            },
+            (None, Some(Record::Department(d))) => ConfirmAction::CreateDepartment {
+                name: d.name.clone(),
+            },
+            (None, Some(Record::SalaryGrade(g))) => ConfirmAction::CreateSalaryGrade {
+                code: g.code.clone(),
+            },
+            // Nothing to apply; both snapshots are always of the same kind
+            _ => return Ok(()),
+        };
+        perms.check_action(&action, &[])
+    }
+
+    /// Store the server's copy as the snapshot `direction` moved to
+    fn set(&mut self, direction: Direction, record: Option<Record>) {
+        match direction {
+            Direction::Undo => self.before = record,
+            Direction::Redo => self.after = record,
+        }
+    }
+}
+
+fn describe(e: ApiError) -> String {
+    if e.is_edit_conflict() {
+        "The record was changed by someone else since.".to_string()
+    } else if e.is_not_found() {
+        "The record no longer exists.".to_string()
+    } else {
+        e.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Undo,
+    Redo,
+}
+
+/// Where `HistoryEntry::apply` stopped after a change failed
+#[derive(Debug)]
+pub struct Stopped {
+    /// How many changes were applied before the failing one
+    pub applied: usize,
+    /// Ids of records re-created by the applied changes
+    pub remaps: Vec<(String, String)>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub label: String,
+    /// Applied in order on redo, in reverse on undo
+    pub changes: Vec<Change>,
+}
+
+impl HistoryEntry {
+    /// Check every change before any of them is applied
+    pub fn check(&self, perms: &Permissions, direction: Direction) -> Result<(), String> {
+        self.changes
+            .iter()
+            .try_for_each(|change| change.check(perms, direction))
+    }
+
+    /// Apply all changes in `direction`, returning the ids of re-created
+    /// records
+    pub async fn apply(
+        &mut self,
+        api: &ApiClient,
+        direction: Direction,
+    ) -> Result<Vec<(String, String)>, Stopped> {
+        let mut remaps = Vec::new();
+        let count = self.changes.len();
+        for n in 0..count {
+            let i = match direction {
+                Direction::Undo => count - 1 - n,
+                Direction::Redo => n,
+            };
+            match self.changes[i].apply(api, direction).await {
+                Ok(Some((old, new))) => {
+                    for change in &mut self.changes {
+                        change.remap(&old, &new);
+                    }
+                    remaps.push((old, new));
+                }
+                Ok(None) => {}
+                Err(reason) => {
+                    return Err(Stopped {
+                        applied: n,
+                        remaps,
+                        reason,
+                    })
+                }
+            }
+        }
+        Ok(remaps)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    next_id: u64,
+}
+
+impl History {
+    /// Record a new change; this discards everything that could be redone
+    pub fn record(&mut self, label: impl Into<String>, changes: Vec<Change>) -> u64 {
+        self.next_id += 1;
+        self.undo.push(HistoryEntry {
+            id: self.next_id,
+            label: label.into(),
+            changes,
+        });
+        if self.undo.len() > MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        self.next_id
+    }
+
+    fn stack(&mut self, direction: Direction) -> &mut Vec<HistoryEntry> {
+        match direction {
+            Direction::Undo => &mut self.undo,
+            Direction::Redo => &mut self.redo,
+        }
+    }
+
+    /// Label of the entry the next undo or redo would apply
+    pub fn peek(&self, direction: Direction) -> Option<&str> {
+        match direction {
+            Direction::Undo => self.undo.last(),
+            Direction::Redo => self.redo.last(),
+        }
+        .map(|e| e.label.as_str())
+    }
+
+    /// Take the next entry to undo or redo; with `only` set, just if it is
+    /// that entry
+    pub fn take(&mut self, direction: Direction, only: Option<u64>) -> Option<HistoryEntry> {
+        let stack = self.stack(direction);
+        match (stack.last(), only) {
+            (Some(entry), Some(id)) if entry.id != id => None,
+            _ => stack.pop(),
+        }
+    }
+
+    /// Put back an entry that could not be applied at all
+    pub fn restore(&mut self, direction: Direction, entry: HistoryEntry) {
+        self.stack(direction).push(entry);
+    }
+
+    /// File an applied entry so the opposite direction can revert it
+    pub fn finish(&mut self, direction: Direction, entry: HistoryEntry) {
+        let opposite = match direction {
+            Direction::Undo => Direction::Redo,
+            Direction::Redo => Direction::Undo,
+        };
+        self.stack(opposite).push(entry);
+    }
+
+    /// File the first `applied` changes of `entry` like `finish`, and put
+    /// the rest back under the entry's id so it can be tried again
+    pub fn finish_partly(&mut self, direction: Direction, entry: HistoryEntry, applied: usize) {
+        let HistoryEntry {
+            id,
+            label,
+            mut changes,
+        } = entry;
+        let at = match direction {
+            Direction::Undo => changes.len() - applied,
+            Direction::Redo => applied,
+        };
+        let tail = changes.split_off(at);
+        let (done, rest) = match direction {
+            Direction::Undo => (tail, changes),
+            Direction::Redo => (changes, tail),
+        };
+        self.next_id += 1;
+        let done = HistoryEntry {
+            id: self.next_id,
+            label: label.clone(),
+            changes: done,
+        };
+        self.finish(direction, done);
+        self.restore(
+            direction,
+            HistoryEntry {
+                id,
+                label,
+                changes: rest,
+            },
+        );
+    }
+
+    /// Forget all entries, e.g. when the user signs out
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn remap(&mut self, old: &str, new: &str) {
+        for entry in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            for change in &mut entry.changes {
+                change.remap(old, new);
+            }
+        }
+    }
+}
+
+/// Cloneable handle for recording from background tasks
+#[derive(Clone, Default)]
+pub struct SharedHistory(Arc<Mutex<History>>);
+
+impl SharedHistory {
+    pub fn lock(&self) -> std::sync::MutexGuard<'_, History> {
+        self.0.lock().unwrap()
+    }
+
+    /// Record `changes` and announce them with an undo button. Without
+    /// changes, e.g. when the snapshots could not be read, there is nothing
+    /// to undo and a plain success is shown.
+    pub fn commit(&self, notify: &Notifier, message: String, changes: Vec<Change>) {
+        if changes.is_empty() {
+            notify.success(message);
+            return;
+        }
+        let id = self.lock().record(message.clone(), changes);
+        notify.success_with_undo(message, id);
+    }
+}
+
+/// The change of a record created from `template`; `existing` are the ids
+/// from before the create, without them nothing is recorded
+pub async fn record_create(
+    api: &ApiClient,
+    template: Record,
+    existing: Option<HashSet<String>>,
+) -> Vec<Change> {
+    let Some(existing) = existing else {
+        return Vec::new();
+    };
+    match template.find_created(api, &existing).await {
+        Ok(Some(record)) => vec![Change::created(record)],
+        _ => Vec::new(),
+    }
+}
+
+/// The change of a record updated from `before`, read back from the server
+pub async fn record_update(api: &ApiClient, before: Option<Record>) -> Vec<Change> {
+    let Some(before) = before else {
+        return Vec::new();
+    };
+    match before.fetch(api).await {
+        Ok(after) => vec![Change::updated(before, after)],
+        Err(_) => Vec::new(),
+    }
+}
+
+/// The change of deleting `before`; must run before the delete, while the
+/// references can still be found
+pub async fn prepare_delete(api: &ApiClient, before: Option<Record>) -> Vec<Change> {
+    let Some(before) = before else {
+        return Vec::new();
+    };
+    let references = before.fetch_references(api).await.unwrap_or_default();
+    vec![Change::deleted(before, references)]
+}
+
+impl PersonnelApp {
+    /// Snapshots from the loaded collections, taken before a change
+    pub fn department_record(&self, id: &str) -> Option<Record> {
+        let depts = self.departments.snapshot();
+        let dept = depts.iter().find(|d| d.id == id)?;
+        Some(Record::Department(dept.clone()))
+    }
+
+    pub fn employee_record(&self, id: &str) -> Option<Record> {
+        let emps = self.employees.snapshot();
+        let emp = emps.iter().find(|e| e.id == id)?;
+        Some(Record::Employee(emp.clone()))
+    }
+
+    pub fn grade_record(&self, id: &str) -> Option<Record> {
+        let grades = self.salary_grades.snapshot();
+        let grade = grades.iter().find(|g| g.id == id)?;
+        Some(Record::SalaryGrade(grade.clone()))
+    }
+
+    /// Undo the latest change; with `only` set, just if it is that entry
+    pub fn undo(&mut self, only: Option<u64>) {
+        self.run_history(Direction::Undo, only);
+    }
+
+    pub fn redo(&mut self) {
+        self.run_history(Direction::Redo, None);
+    }
+
+    fn history_busy(&self) -> bool {
+        self.emp_status.is_mutating()
+            || self.dept_status.is_mutating()
+            || self.grade_status.is_mutating()
+    }
+
+    fn run_history(&mut self, direction: Direction, only: Option<u64>) {
+        if self.history_busy() {
+            return;
+        }
+        let Some(mut entry) = self.history.lock().take(direction, only) else {
+            if only.is_some() {
+                self.notifier
+                    .info("Only the most recent change can be undone from here");
+            }
+            return;
+        };
+        if let Err(reason) = entry.check(&self.permissions(), direction) {
+            self.notifier.warning(format!(
+                "Cannot {} \"{}\": {}",
+                match direction {
+                    Direction::Undo => "undo",
+                    Direction::Redo => "redo",
+                },
+                entry.label,
+                reason
+            ));
+            self.history.lock().restore(direction, entry);
+            return;
+        }
+
+        let api = self.api.clone();
+        let history = self.history.clone();
+        let notify = self.notifier.clone();
+        let depts_ref = self.departments.clone();
+        let dept_status = self.dept_status.clone();
+        let grades_ref = self.salary_grades.clone();
+        let grade_status = self.grade_status.clone();
+        let emps_ref = self.employees.clone();
+        let emp_status = self.emp_status.clone();
+        let emp_pager = self.emp_pager.clone();
+        let include_inactive = self.emp_include_inactive;
+        let mutations = (
+            dept_status.begin_mutation(),
+            grade_status.begin_mutation(),
+            emp_status.begin_mutation(),
+        );
+
+        self.spawn(async move {
+            let verb = match direction {
+                Direction::Undo => "Undo",
+                Direction::Redo => "Redo",
+            };
+            match entry.apply(&api, direction).await {
+                Ok(remaps) => {
+                    notify.info(format!("{}: {}", verb, entry.label));
+                    let mut history = history.lock();
+                    for (old, new) in &remaps {
+                        history.remap(old, new);
+                    }
+                    history.finish(direction, entry);
+                }
+                Err(stopped) if stopped.applied == 0 => {
+                    notify.error(
+                        format!("{} failed: {}", verb, entry.label),
+                        Some(stopped.reason),
+                    );
+                    history.lock().restore(direction, entry);
+                }
+                Err(stopped) => {
+                    notify.error(
+                        format!(
+                            "{} only partly applied, the rest can be tried again: {}",
+                            verb, entry.label
+                        ),
+                        Some(stopped.reason),
+                    );
+                    let mut history = history.lock();
+                    for (old, new) in &stopped.remaps {
+                        history.remap(old, new);
+                    }
+                    history.finish_partly(direction, entry, stopped.applied);
+                }
+            }
+
+            dept_status
+                .load_into(&depts_ref, &notify, "departments", api.get_departments())
+                .await;
+            grade_status
+                .load_into(
+                    &grades_ref,
+                    &notify,
+                    "salary grades",
+                    api.get_salary_grades(),
+                )
+                .await;
+            emp_pager
+                .reload(
+                    &emps_ref,
+                    &emp_status,
+                    &notify,
+                    "employees",
+                    |page, limit| api.get_employees_page(page, limit, include_inactive),
+                )
+                .await;
+            drop(mutations);
+        });
+    }
+
+    /// Undo and redo buttons for the top bar, right-to-left
+    pub fn show_history_buttons(&mut self, ui: &mut egui::Ui) {
+        let colors = self.colors;
+        let busy = self.history_busy();
+        let (undo_label, redo_label) = {
+            let history = self.history.lock();
+            (
+                history
+                    .peek(Direction::Undo)
+                    .map(|l| format!("Undo: {} (Ctrl+Z)", l)),
+                history
+                    .peek(Direction::Redo)
+                    .map(|l| format!("Redo: {} (Ctrl+Shift+Z)", l)),
+            )
+        };
+        let button = |icon: &str| {
+            egui::Button::new(
+                egui::RichText::new(icon)
+                    .size(16.0)
+                    .color(colors.on_surface),
+            )
+            .fill(colors.surface_variant)
+            .stroke(egui::Stroke::new(1.0, colors.outline_variant))
+            .rounding(egui::Rounding::same(20.0))
+            .min_size(egui::Vec2::new(40.0, 40.0))
+        };
+
+        let redo = ui
+            .add_enabled(!busy && redo_label.is_some(), button("↷"))
+            .on_hover_text(redo_label.unwrap_or_else(|| "Nothing to redo".to_string()));
+        if redo.clicked() {
+            self.redo();
+        }
+        ui.add_space(4.0);
+        let undo = ui
+            .add_enabled(!busy && undo_label.is_some(), button("↶"))
+            .on_hover_text(undo_label.unwrap_or_else(|| "Nothing to undo".to_string()));
+        if undo.clicked() {
+            self.undo(None);
+        }
+    }
+
+    /// Ctrl+Z undoes, Ctrl+Shift+Z redoes, unless a text field has focus
+    pub fn handle_history_shortcuts(&mut self, ctx: &egui::Context) {
+        let dialog_open = self.confirm_dialog.is_some() || self.edit_dialog.is_some();
+        if dialog_open || ctx.wants_keyboard_input() {
+            return;
+        }
+        let redo = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::Z,
+        );
+        let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+            self.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+            self.undo(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+    use crate::gui::fixtures::{department, employee};
+
+    #[test]
+    fn test_record_clears_redo_and_take_checks_entry() {
+        let mut history = History::default();
+        let first = history.record("first", vec![]);
+        let second = history.record("second", vec![]);
+        assert_eq!(history.peek(Direction::Undo), Some("second"));
+        assert!(history.take(Direction::Undo, Some(first)).is_none());
+
+        let entry = history.take(Direction::Undo, Some(second)).unwrap();
+        history.finish(Direction::Undo, entry);
+        assert_eq!(history.peek(Direction::Redo), Some("second"));
+
+        history.record("third", vec![]);
+        assert_eq!(history.peek(Direction::Redo), None);
+        assert_eq!(history.peek(Direction::Undo), Some("third"));
+    }
+
+    #[tokio::test]
+    async fn test_partly_applied_undo_keeps_the_rest() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("PUT", "/departments/d1") => MockResponse::new(422, r#"{"error":"invalid"}"#),
+            ("GET", path) => {
+                let id = path.trim_start_matches("/departments/");
+                MockResponse::new(
+                    200,
+                    &serde_json::to_string(&department(id, "Sales")).unwrap(),
+                )
+            }
+            _ => MockResponse::new(200, "{}"),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let renamed = |id: &str| {
+            Change::updated(
+                Record::Department(department(id, "Sales")),
+                Record::Department(department(id, "Marketing")),
+            )
+        };
+        let mut history = History::default();
+        let id = history.record("Rename both", vec![renamed("d1"), renamed("d2")]);
+        let mut entry = history.take(Direction::Undo, Some(id)).unwrap();
+
+        let stopped = entry.apply(&api, Direction::Undo).await.unwrap_err();
+        assert_eq!(stopped.applied, 1);
+        history.finish_partly(Direction::Undo, entry, stopped.applied);
+
+        // d2 was renamed back and can be redone, d1 is left to undo
+        let rest = history.take(Direction::Undo, Some(id)).unwrap();
+        assert_eq!(rest.changes.len(), 1);
+        assert_eq!(rest.changes[0].before.as_ref().unwrap().id(), "d1");
+        let done = history.take(Direction::Redo, None).unwrap();
+        assert_eq!(done.changes.len(), 1);
+        assert_eq!(done.changes[0].before.as_ref().unwrap().id(), "d2");
+    }
+
+    #[test]
+    fn test_remap_follows_references() {
+        let mut history = History::default();
+        let report = employee("e2").with_manager("e1");
+        history.record(
+            "delete",
+            vec![Change::deleted(
+                Record::Employee(employee("e1")),
+                vec![Record::Employee(report)],
+            )],
+        );
+        history.remap("e1", "e9");
+        let change = &history.undo[0].changes[0];
+        assert_eq!(change.before.as_ref().unwrap().id(), "e9");
+        match &change.references[0] {
+            Record::Employee(e) => assert_eq!(e.manager_id.as_deref(), Some("e9")),
+            other => panic!("unexpected reference {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_treats_undo_as_the_inverse_action() {
+        let head = Permissions::for_user(&AuthUser {
+            id: "me".to_string(),
+            role: "DepartmentHead".to_string(),
+            department_id: Some("d1".to_string()),
+            ..Default::default()
+        });
+        let created = HistoryEntry {
+            id: 1,
+            label: "Create".to_string(),
+            changes: vec![Change::created(Record::Employee(
+                employee("e1").with_department("d1"),
+            ))],
+        };
+        // Creating in the own department is allowed, deleting is not
+        assert!(created.check(&head, Direction::Redo).is_ok());
+        assert!(created.check(&head, Direction::Undo).is_err());
+
+        let moved = employee("e1").with_department("d2");
+        let updated = HistoryEntry {
+            id: 2,
+            label: "Move".to_string(),
+            changes: vec![Change::updated(
+                Record::Employee(employee("e1").with_department("d1")),
+                Record::Employee(moved),
+            )],
+        };
+        // The employee has left the department since
+        assert!(updated.check(&head, Direction::Undo).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undo_update_sends_snapshot_with_version() {
+        let server = MockServer::start(|req, _| {
+            if req.method == "GET" {
+                let current = department("d1", "Sales").with_version("v2");
+                MockResponse::new(200, &serde_json::to_string(&current).unwrap())
+            } else {
+                MockResponse::new(200, "{}")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let mut entry = HistoryEntry {
+            id: 1,
+            label: "Rename".to_string(),
+            changes: vec![Change::updated(
+                Record::Department(department("d1", "Sales")),
+                Record::Department(department("d1", "Marketing").with_version("v3")),
+            )],
+        };
+
+        assert!(entry.apply(&api, Direction::Undo).await.unwrap().is_empty());
+        let requests = server.recorded();
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].header("If-Match"), Some("\"v3\""));
+        assert!(requests[0].body.contains(r#""name":"Sales""#));
+        // A full snapshot clears what was empty before
+        assert!(requests[0].body.contains(r#""head_id":null"#));
+        // The refreshed copy becomes the version redo checks against
+        let before = entry.changes[0].before.as_ref().unwrap();
+        assert_eq!(before.version(), Some("v2"));
+    }
+
+    #[tokio::test]
+    async fn test_undo_delete_recreates_and_restores_references() {
+        // An older record with the same email must not be taken for the new one
+        let server = MockServer::start(|req, n| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/employees?include_inactive=true") => {
+                let mut emps = vec![employee("e5").with_email("a@example.com")];
+                if n > 0 {
+                    emps.insert(0, employee("e9").with_email("A@example.com"));
+                }
+                MockResponse::new(200, &serde_json::to_string(&emps).unwrap())
+            }
+            ("GET", _) => MockResponse::new(
+                200,
+                &serde_json::to_string(&employee("e9").with_email("a@example.com")).unwrap(),
+            ),
+            _ => MockResponse::new(200, "{}"),
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let headed = department("d1", "Sales").with_head("e1");
+        let mut entry = HistoryEntry {
+            id: 1,
+            label: "Delete".to_string(),
+            changes: vec![Change::deleted(
+                Record::Employee(employee("e1").with_email("a@example.com")),
+                vec![Record::Department(headed)],
+            )],
+        };
+
+        let remaps = entry.apply(&api, Direction::Undo).await.unwrap();
+        assert_eq!(remaps, [("e1".to_string(), "e9".to_string())]);
+        let calls: Vec<String> = server
+            .recorded()
+            .iter()
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                "GET /employees?include_inactive=true",
+                "POST /employees",
+                "GET /employees?include_inactive=true",
+                "PUT /employees/e9",
+                "PUT /departments/d1",
+                "GET /employees/e9"
+            ]
+        );
+        assert!(server.recorded()[4].body.contains(r#""head_id":"e9""#));
+        assert_eq!(entry.changes[0].before.as_ref().unwrap().id(), "e9");
+    }
+
+    #[tokio::test]
+    async fn test_restore_reference_sends_only_the_reference() {
+        let server = MockServer::start(|_, _| MockResponse::new(200, "{}")).await;
+        let api = ApiClient::with_config(server.config());
+        let recreated = Record::Department(department("d9", "Sales"));
+        let member = Record::Employee(employee("e1"));
+
+        recreated.restore_reference(&api, &member).await.unwrap();
+        let requests = server.recorded();
+        assert_eq!(requests[0].path, "/employees/e1");
+        // Other fields are left out so a concurrent edit to them survives
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({"department_id": "d9"}));
+    }
+}
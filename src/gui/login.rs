@@ -0,0 +1,310 @@
+//! Login screen shown before the tab UI and session lifecycle handling
+
+use super::PersonnelApp;
+use egui::{Context, Frame, Margin, RichText, Rounding, Stroke, Vec2};
+
+/// Progress of the login request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum LoginState {
+    #[default]
+    Idle,
+    Pending,
+    Failed(String),
+}
+
+impl PersonnelApp {
+    /// Send the entered credentials to the auth endpoint
+    pub fn submit_login(&mut self) {
+        if self.login_email.trim().is_empty() || self.login_password.is_empty() {
+            *self.login_state.lock().unwrap() =
+                LoginState::Failed("Please enter your email and password.".to_string());
+            return;
+        }
+
+        let api = self.api.clone();
+        let email = self.login_email.trim().to_string();
+        let password = std::mem::take(&mut self.login_password);
+        let state = self.login_state.clone();
+        *state.lock().unwrap() = LoginState::Pending;
+
+        self.spawn(async move {
+            let result = match api.login(&email, &password).await {
+                Ok(_) => LoginState::Idle,
+                Err(e) if e.is_unauthorized() => {
+                    LoginState::Failed("Invalid email or password.".to_string())
+                }
+                Err(e) if e.is_unreachable() => {
+                    LoginState::Failed("The server is unreachable. Please try again.".to_string())
+                }
+                Err(e) => LoginState::Failed(e.to_string()),
+            };
+            *state.lock().unwrap() = result;
+        });
+    }
+
+    /// Sign out and drop all personnel data held in memory, the draft
+    /// included
+    pub fn logout(&mut self) {
+        self.api.logout();
+        self.end_session();
+        self.draft = None;
+        *self.login_state.lock().unwrap() = LoginState::Idle;
+    }
+
+    /// React to logins and expired sessions; called once per frame
+    pub fn sync_session(&mut self) {
+        let authenticated = self.api.is_authenticated();
+        if authenticated && !self.signed_in {
+            self.signed_in = true;
+            self.login_password.clear();
+            self.resume_draft();
+            self.refresh_all();
+        } else if !authenticated && self.signed_in {
+            self.end_session();
+            *self.login_state.lock().unwrap() =
+                LoginState::Failed("Your session has expired. Please sign in again.".to_string());
+        }
+    }
+
+    fn end_session(&mut self) {
+        self.signed_in = false;
+        self.confirm_dialog = None;
+        self.edit_dialog = None;
+        self.dept_detail = None;
+        self.selected_emp = None;
+        self.bulk_selection.clear();
+        self.offboarding = None;
+        self.dept_removal = None;
+        self.grade_removal = None;
+        *self.offboarding_staff.lock().unwrap() = None;
+        *self.dept_removal_staff.lock().unwrap() = None;
+        *self.grade_removal_staff.lock().unwrap() = None;
+        *self.bulk_report.lock().unwrap() = None;
+        // Another user must not undo what this one did
+        self.history.lock().clear();
+        // The draft survives an expired session; `resume_draft` decides
+        // whether it is picked up again
+        self.draft_review_open = false;
+        self.logout_confirm = false;
+        *self.dashboard.lock().unwrap() = Default::default();
+        *self.edit_conflict.lock().unwrap() = None;
+        self.departments.clear();
+        self.employees.clear();
+        self.emp_pager.reset();
+        self.emp_search.clear();
+        self.salary_grades.clear();
+    }
+
+    /// Keep a draft left from an expired session if its owner signed in
+    /// again; another user's draft is discarded
+    fn resume_draft(&mut self) {
+        let Some(draft) = &self.draft else {
+            return;
+        };
+        let user = self.api.current_user().map(|u| u.id);
+        if user.as_deref() != Some(draft.owner.as_str()) {
+            if !draft.is_empty() {
+                self.notifier
+                    .info("The draft of the previous user has been discarded");
+            }
+            self.draft = None;
+        } else if !draft.is_empty() {
+            self.notifier.info(format!(
+                "Your draft with {} staged change(s) has been kept",
+                draft.len()
+            ));
+        }
+    }
+
+    /// Ask before signing out would discard staged draft changes
+    pub fn show_logout_confirm(&mut self, ctx: &Context) {
+        if !self.logout_confirm {
+            return;
+        }
+        let colors = self.colors;
+        let count = self.draft.as_ref().map_or(0, |d| d.len());
+        let mut cancel = false;
+        let mut sign_out = false;
+
+        egui::Window::new("Sign out")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .rounding(Rounding::same(16.0))
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(24.0)),
+            )
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "The draft has {} staged change(s) that have not been committed. Signing out discards them.",
+                        count
+                    ))
+                    .size(14.0)
+                    .color(colors.on_surface),
+                );
+                ui.add_space(16.0);
+                ui.horizontal(|ui| {
+                    let button = |text: &str, fill, color| {
+                        egui::Button::new(RichText::new(text).size(14.0).color(color))
+                            .fill(fill)
+                            .rounding(Rounding::same(8.0))
+                            .min_size(Vec2::new(100.0, 40.0))
+                    };
+                    if ui
+                        .add(button("Cancel", colors.surface_variant, colors.on_surface))
+                        .clicked()
+                    {
+                        cancel = true;
+                    }
+                    ui.add_space(12.0);
+                    if ui
+                        .add(button(
+                            "Discard and sign out",
+                            colors.error,
+                            colors.on_error,
+                        ))
+                        .clicked()
+                    {
+                        sign_out = true;
+                    }
+                });
+            });
+
+        if sign_out {
+            self.logout();
+        } else if cancel {
+            self.logout_confirm = false;
+        }
+    }
+
+    /// Show the login screen
+    pub fn show_login(&mut self, ctx: &Context) {
+        let colors = self.colors;
+        let state = self.login_state.lock().unwrap().clone();
+        let pending = state == LoginState::Pending;
+
+        egui::CentralPanel::default()
+            .frame(Frame::none().fill(colors.surface))
+            .show(ctx, |ui| {
+                ui.add_space((ui.available_height() - 420.0).max(0.0) / 2.0);
+                ui.vertical_centered(|ui| {
+                    Frame::none()
+                        .fill(colors.surface_variant)
+                        .rounding(Rounding::same(16.0))
+                        .stroke(Stroke::new(1.0, colors.outline_variant))
+                        .inner_margin(Margin::same(32.0))
+                        .show(ui, |ui| {
+                            ui.set_width(380.0);
+                            ui.vertical(|ui| {
+                                ui.label(RichText::new("🏢").size(36.0));
+                                ui.add_space(8.0);
+                                ui.label(
+                                    RichText::new("Personnel Management")
+                                        .size(24.0)
+                                        .strong()
+                                        .color(colors.on_surface),
+                                );
+                                ui.label(
+                                    RichText::new("Sign in to continue")
+                                        .size(14.0)
+                                        .color(colors.on_surface_variant),
+                                );
+                                ui.add_space(24.0);
+
+                                ui.label(
+                                    RichText::new("Email")
+                                        .size(12.0)
+                                        .color(colors.on_surface_variant),
+                                );
+                                ui.add_space(4.0);
+                                let email = Frame::none()
+                                    .fill(colors.surface)
+                                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                                    .rounding(Rounding::same(8.0))
+                                    .inner_margin(Margin::symmetric(12.0, 10.0))
+                                    .show(ui, |ui| {
+                                        ui.add_enabled(
+                                            !pending,
+                                            egui::TextEdit::singleline(&mut self.login_email)
+                                                .desired_width(f32::INFINITY)
+                                                .hint_text(
+                                                    RichText::new("name@example.com")
+                                                        .color(colors.on_surface_variant),
+                                                )
+                                                .text_color(colors.on_surface)
+                                                .frame(false),
+                                        )
+                                    })
+                                    .inner;
+
+                                ui.add_space(12.0);
+                                ui.label(
+                                    RichText::new("Password")
+                                        .size(12.0)
+                                        .color(colors.on_surface_variant),
+                                );
+                                ui.add_space(4.0);
+                                let password = Frame::none()
+                                    .fill(colors.surface)
+                                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                                    .rounding(Rounding::same(8.0))
+                                    .inner_margin(Margin::symmetric(12.0, 10.0))
+                                    .show(ui, |ui| {
+                                        ui.add_enabled(
+                                            !pending,
+                                            egui::TextEdit::singleline(&mut self.login_password)
+                                                .password(true)
+                                                .desired_width(f32::INFINITY)
+                                                .hint_text(
+                                                    RichText::new("Password")
+                                                        .color(colors.on_surface_variant),
+                                                )
+                                                .text_color(colors.on_surface)
+                                                .frame(false),
+                                        )
+                                    })
+                                    .inner;
+
+                                if let LoginState::Failed(message) = &state {
+                                    ui.add_space(12.0);
+                                    ui.label(
+                                        RichText::new(format!("⚠ {}", message))
+                                            .size(13.0)
+                                            .color(colors.error),
+                                    );
+                                }
+
+                                ui.add_space(24.0);
+                                let submitted_with_enter = (email.lost_focus()
+                                    || password.lost_focus())
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                                ui.horizontal(|ui| {
+                                    let sign_in = egui::Button::new(
+                                        RichText::new("Sign in")
+                                            .size(14.0)
+                                            .color(colors.on_primary),
+                                    )
+                                    .fill(colors.primary)
+                                    .stroke(Stroke::NONE)
+                                    .rounding(Rounding::same(20.0))
+                                    .min_size(Vec2::new(120.0, 40.0));
+
+                                    let clicked = ui.add_enabled(!pending, sign_in).clicked();
+                                    if pending {
+                                        ui.add_space(8.0);
+                                        ui.add(egui::Spinner::new().color(colors.primary));
+                                    } else if clicked || submitted_with_enter {
+                                        self.submit_login();
+                                    }
+                                });
+                            });
+                        });
+                });
+            });
+    }
+}
@@ -1,20 +1,59 @@
 //! GUI module for the Personnel Management application
 //!
 //! This module contains all the UI-related code organized into submodules:
+//! - `bulk`: Bulk actions on several selected employees
 //! - `colors`: Material 3 color palette
 //! - `components`: Reusable UI components (buttons, cards, dropdowns)
+//! - `conflicts`: Conflict dialog for updates rejected by the version check
+//! - `dates`: Validation and formatting of `YYYY-MM-DD` dates
+//! - `department_removal`: Wizard moving employees out of a department before deleting it
+//! - `drafts`: Draft mode staging changes locally until they are committed
+//! - `fixtures`: Records for the tests (test builds only)
 //! - `dialogs`: Dialog type definitions (ConfirmAction, EditDialog)
 //! - `dialog_handlers`: Dialog handling logic (confirmations, action execution)
 //! - `forms`: Form UI components for create/edit dialogs
-//! - `views`: Tab views (departments, employees, salary_grades)
+//! - `grade_removal`: Regrading affected employees before a salary grade is deleted
+//! - `hierarchy`: Validation of the reporting hierarchy (no manager loops)
+//! - `history`: Undo and redo of confirmed changes
+//! - `login`: Login screen and session lifecycle
+//! - `offboarding`: Wizard reassigning reports and departments of a leaver
+//! - `notifications`: Snackbar notifications for background request results
+//! - `pagination`: Page-wise loading of the employee list
+//! - `permissions`: Role-based permissions of the signed-in user
+//! - `requests`: Load and mutation state tracking for the entity collections
+//! - `saga`: Multi-step changes rolled back when a step fails
+//! - `settings`: UI preferences saved between sessions
+//! - `search`: Employee search with fuzzy matching and filter tokens
+//! - `store`: Shared entity collections with cheap per-frame snapshots
+//! - `views`: Tab views (dashboard, departments, employees, salary_grades, org chart)
 //! - `app`: Main application struct and eframe::App implementation
 
 pub mod app;
+pub mod bulk;
 pub mod colors;
 pub mod components;
+pub mod conflicts;
+pub mod dates;
+pub mod department_removal;
 pub mod dialog_handlers;
 pub mod dialogs;
+pub mod drafts;
+#[cfg(test)]
+pub mod fixtures;
 pub mod forms;
+pub mod grade_removal;
+pub mod hierarchy;
+pub mod history;
+pub mod login;
+pub mod notifications;
+pub mod offboarding;
+pub mod pagination;
+pub mod permissions;
+pub mod requests;
+pub mod saga;
+pub mod search;
+pub mod settings;
+pub mod store;
 pub mod views;
 
 // Re-export commonly used types
@@ -22,6 +61,8 @@ pub use app::PersonnelApp;
 pub use colors::Material3Colors;
 pub use components::{material_button, styled_dropdown};
 pub use dialogs::{ConfirmAction, EditDialog};
+pub use notifications::Notifier;
+pub use requests::RequestTracker;
 
 /// The tab navigation enum
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -29,6 +70,8 @@ pub enum Tab {
     Departments,
     Employees,
     SalaryGrades,
+    OrgChart,
+    Dashboard,
 }
 
 #[cfg(test)]
@@ -61,5 +104,7 @@ mod tests {
         assert_eq!(format!("{:?}", Tab::Departments), "Departments");
         assert_eq!(format!("{:?}", Tab::Employees), "Employees");
         assert_eq!(format!("{:?}", Tab::SalaryGrades), "SalaryGrades");
+        assert_eq!(format!("{:?}", Tab::OrgChart), "OrgChart");
+        assert_eq!(format!("{:?}", Tab::Dashboard), "Dashboard");
     }
 }
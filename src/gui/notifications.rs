@@ -0,0 +1,429 @@
+//! Snackbar notifications for background request results
+//!
+//! Spawned tasks push notifications through a cloned `Notifier` handle; the
+//! UI thread renders them as stacked Material 3 snackbars in the bottom-right
+//! corner and removes them once they expire.
+
+use super::{Material3Colors, PersonnelApp};
+use crate::api::error::ApiError;
+use egui::{Color32, Context, Frame, Margin, RichText, Rounding, Stroke, Vec2};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Maximum number of snackbars shown at the same time
+const MAX_VISIBLE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// How long a snackbar of this severity stays on screen
+    pub fn duration(&self) -> Duration {
+        match self {
+            Severity::Info | Severity::Success => Duration::from_secs(4),
+            Severity::Warning => Duration::from_secs(6),
+            Severity::Error => Duration::from_secs(10),
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ",
+            Severity::Success => "✔",
+            Severity::Warning => "⚠",
+            Severity::Error => "✖",
+        }
+    }
+
+    pub fn color(&self, colors: &Material3Colors) -> Color32 {
+        match self {
+            Severity::Info => colors.primary,
+            Severity::Success => colors.success,
+            Severity::Warning => colors.warning,
+            Severity::Error => colors.error,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u64,
+    pub severity: Severity,
+    pub message: String,
+    pub details: Option<String>,
+    /// `None` while the details are expanded, so the snackbar stays open
+    pub expires_at: Option<Instant>,
+    pub expanded: bool,
+    /// History entry the snackbar offers to undo
+    pub undo: Option<u64>,
+}
+
+/// Cloneable handle to the shared notification queue
+#[derive(Clone, Default)]
+pub struct Notifier {
+    queue: Arc<Mutex<Vec<Notification>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+#[allow(dead_code)]
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, severity: Severity, message: impl Into<String>, details: Option<String>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.queue.lock().unwrap().push(Notification {
+            id,
+            severity,
+            message: message.into(),
+            details,
+            expires_at: Some(Instant::now() + severity.duration()),
+            expanded: false,
+            undo: None,
+        });
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        self.push(Severity::Info, message, None);
+    }
+
+    pub fn success(&self, message: impl Into<String>) {
+        self.push(Severity::Success, message, None);
+    }
+
+    /// A success snackbar with an "Undo" button for history entry `entry`
+    pub fn success_with_undo(&self, message: impl Into<String>, entry: u64) {
+        self.push(Severity::Success, message, None);
+        if let Some(n) = self.queue.lock().unwrap().last_mut() {
+            n.undo = Some(entry);
+        }
+    }
+
+    pub fn warning(&self, message: impl Into<String>) {
+        self.push(Severity::Warning, message, None);
+    }
+
+    pub fn error(&self, message: impl Into<String>, details: Option<String>) {
+        self.push(Severity::Error, message, details);
+    }
+
+    /// Report a failed API call, e.g. `api_error("Could not delete employee", &e)`
+    pub fn api_error(&self, context: &str, err: &ApiError) {
+        let reason = if err.is_unauthorized() {
+            "your session has expired, please sign in again".to_string()
+        } else if err.is_not_found() {
+            "the record no longer exists".to_string()
+        } else if err.is_validation() || err.is_conflict() {
+            match err {
+                ApiError::Status { message, .. } if !message.is_empty() => message.clone(),
+                _ => "the server rejected the data".to_string(),
+            }
+        } else if err.is_server_error() {
+            "the server encountered an error".to_string()
+        } else {
+            match err {
+                ApiError::Timeout => "the server did not respond in time".to_string(),
+                ApiError::Transport(_) => "the server is unreachable".to_string(),
+                ApiError::Decode(_) => "the server sent an unexpected response".to_string(),
+                ApiError::Status { .. } => "the request failed".to_string(),
+            }
+        };
+
+        let mut details = err.to_string();
+        if let Some(body) = err.body() {
+            details.push_str("\n\n");
+            details.push_str(body);
+        }
+
+        self.error(format!("{}: {}", context, reason), Some(details));
+    }
+
+    pub fn dismiss(&self, id: u64) {
+        self.queue.lock().unwrap().retain(|n| n.id != id);
+    }
+
+    /// Expand or collapse the details of a notification. Expanded
+    /// notifications do not expire until collapsed again.
+    pub fn toggle_details(&self, id: u64) {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(n) = queue.iter_mut().find(|n| n.id == id) {
+            n.expanded = !n.expanded;
+            n.expires_at = if n.expanded {
+                None
+            } else {
+                Some(Instant::now() + n.severity.duration())
+            };
+        }
+    }
+
+    /// Keep a notification open for a little longer, e.g. while hovered
+    pub fn extend(&self, id: u64, now: Instant) {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(n) = queue.iter_mut().find(|n| n.id == id) {
+            if let Some(expires_at) = n.expires_at {
+                n.expires_at = Some(expires_at.max(now + Duration::from_secs(2)));
+            }
+        }
+    }
+
+    /// Remove every notification that expired before `now`
+    pub fn prune(&self, now: Instant) {
+        self.queue
+            .lock()
+            .unwrap()
+            .retain(|n| n.expires_at.is_none_or(|t| t > now));
+    }
+
+    /// When the next notification expires, so the UI can repaint just then
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.queue
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|n| n.expires_at)
+            .min()
+    }
+
+    /// The most recent notifications, oldest first
+    pub fn visible(&self) -> Vec<Notification> {
+        let queue = self.queue.lock().unwrap();
+        let start = queue.len().saturating_sub(MAX_VISIBLE);
+        queue[start..].to_vec()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl PersonnelApp {
+    /// Render the stacked snackbars and drop expired ones
+    pub fn show_notifications(&mut self, ctx: &Context) {
+        let colors = self.colors;
+        let now = Instant::now();
+        self.notifier.prune(now);
+        if let Some(expiry) = self.notifier.next_expiry() {
+            ctx.request_repaint_after(expiry.saturating_duration_since(now));
+        }
+
+        let visible = self.notifier.visible();
+        if visible.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("snackbar_host"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-24.0, -24.0])
+            .show(ctx, |ui| {
+                ui.set_max_width(420.0);
+                ui.spacing_mut().item_spacing.y = 8.0;
+
+                for n in &visible {
+                    let accent = n.severity.color(&colors);
+                    let response = Frame::none()
+                        .fill(colors.inverse_surface)
+                        .rounding(Rounding::same(8.0))
+                        .stroke(Stroke::new(1.0, accent))
+                        .shadow(egui::epaint::Shadow {
+                            offset: egui::vec2(0.0, 2.0),
+                            blur: 8.0,
+                            spread: 0.0,
+                            color: Color32::from_black_alpha(60),
+                        })
+                        .inner_margin(Margin::symmetric(16.0, 12.0))
+                        .show(ui, |ui| {
+                            ui.set_width(388.0);
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(n.severity.icon()).size(16.0).color(accent));
+                                ui.add_space(4.0);
+                                ui.add(
+                                    egui::Label::new(
+                                        RichText::new(&n.message)
+                                            .size(13.0)
+                                            .color(colors.inverse_on_surface),
+                                    )
+                                    .wrap(),
+                                );
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        let dismiss = egui::Button::new(
+                                            RichText::new("Dismiss")
+                                                .size(12.0)
+                                                .color(colors.inverse_on_surface),
+                                        )
+                                        .fill(Color32::TRANSPARENT)
+                                        .stroke(Stroke::NONE)
+                                        .min_size(Vec2::new(0.0, 24.0));
+                                        if ui.add(dismiss).clicked() {
+                                            self.notifier.dismiss(n.id);
+                                        }
+
+                                        if let Some(entry) = n.undo {
+                                            let undo_btn = egui::Button::new(
+                                                RichText::new("Undo").size(12.0).color(accent),
+                                            )
+                                            .fill(Color32::TRANSPARENT)
+                                            .stroke(Stroke::NONE)
+                                            .min_size(Vec2::new(0.0, 24.0));
+                                            if ui.add(undo_btn).clicked() {
+                                                self.notifier.dismiss(n.id);
+                                                self.undo(Some(entry));
+                                            }
+                                        }
+
+                                        if n.details.is_some() {
+                                            let label = if n.expanded {
+                                                "Hide details"
+                                            } else {
+                                                "Details"
+                                            };
+                                            let details_btn = egui::Button::new(
+                                                RichText::new(label).size(12.0).color(accent),
+                                            )
+                                            .fill(Color32::TRANSPARENT)
+                                            .stroke(Stroke::NONE)
+                                            .min_size(Vec2::new(0.0, 24.0));
+                                            if ui.add(details_btn).clicked() {
+                                                self.notifier.toggle_details(n.id);
+                                            }
+                                        }
+                                    },
+                                );
+                            });
+
+                            if n.expanded {
+                                if let Some(details) = &n.details {
+                                    Frame::none()
+                                        .fill(colors.inverse_on_surface.gamma_multiply(0.08))
+                                        .rounding(Rounding::same(6.0))
+                                        .inner_margin(Margin::same(8.0))
+                                        .show(ui, |ui| {
+                                            ui.label(
+                                                RichText::new(details)
+                                                    .monospace()
+                                                    .size(11.0)
+                                                    .color(colors.inverse_on_surface),
+                                            );
+                                        });
+                                }
+                            }
+                        })
+                        .response;
+
+                    if response.hovered() {
+                        self.notifier.extend(n.id, now);
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_push_and_dismiss() {
+        let notifier = Notifier::new();
+        notifier.success("Saved");
+        notifier.info("Loading");
+        assert_eq!(notifier.len(), 2);
+
+        let first = notifier.visible()[0].id;
+        notifier.dismiss(first);
+        assert_eq!(notifier.len(), 1);
+        assert_eq!(notifier.visible()[0].message, "Loading");
+    }
+
+    #[test]
+    fn test_prune_removes_expired() {
+        let notifier = Notifier::new();
+        notifier.success("Saved");
+        notifier.error("Failed", None);
+
+        notifier.prune(Instant::now() + Duration::from_secs(5));
+        let remaining = notifier.visible();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].severity, Severity::Error);
+
+        notifier.prune(Instant::now() + Duration::from_secs(11));
+        assert!(notifier.is_empty());
+    }
+
+    #[test]
+    fn test_expanded_notification_does_not_expire() {
+        let notifier = Notifier::new();
+        notifier.error("Failed", Some("details".to_string()));
+        let id = notifier.visible()[0].id;
+
+        notifier.toggle_details(id);
+        notifier.prune(Instant::now() + Duration::from_secs(60));
+        assert_eq!(notifier.len(), 1);
+        assert!(notifier.visible()[0].expanded);
+    }
+
+    #[test]
+    fn test_visible_is_limited_to_newest() {
+        let notifier = Notifier::new();
+        for i in 0..6 {
+            notifier.info(format!("n{}", i));
+        }
+        let visible = notifier.visible();
+        assert_eq!(visible.len(), MAX_VISIBLE);
+        assert_eq!(visible[0].message, "n2");
+        assert_eq!(visible[MAX_VISIBLE - 1].message, "n5");
+    }
+
+    #[test]
+    fn test_api_error_messages() {
+        let notifier = Notifier::new();
+        notifier.api_error(
+            "Could not delete employee",
+            &ApiError::from_status(StatusCode::NOT_FOUND, String::new()),
+        );
+        notifier.api_error(
+            "Could not create employee",
+            &ApiError::from_status(
+                StatusCode::BAD_REQUEST,
+                r#"{"error":"email is invalid"}"#.to_string(),
+            ),
+        );
+        notifier.api_error("Could not load departments", &ApiError::Timeout);
+
+        let visible = notifier.visible();
+        assert_eq!(
+            visible[0].message,
+            "Could not delete employee: the record no longer exists"
+        );
+        assert_eq!(
+            visible[1].message,
+            "Could not create employee: email is invalid"
+        );
+        assert!(visible[1]
+            .details
+            .as_deref()
+            .unwrap()
+            .contains(r#"{"error":"email is invalid"}"#));
+        assert_eq!(
+            visible[2].message,
+            "Could not load departments: the server did not respond in time"
+        );
+        assert!(visible.iter().all(|n| n.severity == Severity::Error));
+    }
+}
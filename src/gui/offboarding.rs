@@ -0,0 +1,903 @@
+//! Offboarding wizard for managers and department heads
+//!
+//! Deactivating or deleting someone who manages other employees or heads a
+//! department would leave those records pointing at a gone person. Instead
+//! of the plain confirmation, such employees go through this wizard: pick a
+//! new manager for every direct report, a new head for every department, then
+//! review and apply everything as one batch. The leaver is only deactivated
+//! or deleted once all reassignments went through.
+
+use super::bulk::{BulkReport, BulkRow};
+use super::components::{styled_dropdown, styled_text_input};
+use super::dates::{parse_date, today};
+use super::hierarchy::Hierarchy;
+use super::permissions::{Permissions, Role};
+use super::PersonnelApp;
+use crate::api::client::ApiClient;
+use crate::api::models::*;
+use egui::{Context, Frame, Margin, RichText, Rounding, Stroke, Ui, Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffboardingMode {
+    Deactivate,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffboardingStep {
+    Reports,
+    Departments,
+    Review,
+}
+
+impl OffboardingStep {
+    fn title(&self) -> &'static str {
+        match self {
+            OffboardingStep::Reports => "Direct reports",
+            OffboardingStep::Departments => "Departments",
+            OffboardingStep::Review => "Review",
+        }
+    }
+}
+
+/// A direct report and the manager they move to; `None` leaves them
+/// without a manager
+#[derive(Debug, Clone)]
+pub struct ReportChange {
+    pub employee: Employee,
+    pub new_manager: Option<String>,
+}
+
+/// A department headed by the leaver and its new head; `None` leaves the
+/// post vacant
+#[derive(Debug, Clone)]
+pub struct HeadChange {
+    pub department: Department,
+    pub new_head: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Offboarding {
+    pub employee: Employee,
+    pub mode: OffboardingMode,
+    pub step: OffboardingStep,
+    /// `None` until the complete employee list arrived
+    pub reports: Option<Vec<ReportChange>>,
+    pub departments: Vec<HeadChange>,
+    /// Current employees that can take over, excluding the leaver
+    pub candidates: Vec<Employee>,
+    /// The complete employee list, for checking the new reporting lines
+    pub staff: Vec<Employee>,
+    /// Leave date when deactivating, the typed name when deleting
+    pub input: String,
+}
+
+impl Offboarding {
+    pub fn new(employee: Employee, mode: OffboardingMode, departments: &[Department]) -> Self {
+        let departments = departments
+            .iter()
+            .filter(|d| d.head_id.as_deref() == Some(employee.id.as_str()))
+            .map(|d| HeadChange {
+                department: d.clone(),
+                new_head: None,
+            })
+            .collect();
+        let input = match mode {
+            OffboardingMode::Deactivate => today(),
+            OffboardingMode::Delete => String::new(),
+        };
+        Self {
+            employee,
+            mode,
+            step: OffboardingStep::Reports,
+            reports: None,
+            departments,
+            candidates: Vec::new(),
+            staff: Vec::new(),
+            input,
+        }
+    }
+
+    /// Whether `emp` needs the wizard rather than a plain confirmation,
+    /// judged by the employees loaded so far
+    pub fn is_needed(emp: &Employee, employees: &[Employee], departments: &[Department]) -> bool {
+        let id = Some(emp.id.as_str());
+        employees.iter().any(|e| e.manager_id.as_deref() == id)
+            || departments.iter().any(|d| d.head_id.as_deref() == id)
+    }
+
+    pub fn name(&self) -> String {
+        format!("{} {}", self.employee.first_name, self.employee.last_name)
+    }
+
+    /// Fill in the direct reports and candidates from the complete list
+    pub fn set_staff(&mut self, staff: &[Employee]) {
+        let id = Some(self.employee.id.as_str());
+        self.reports = Some(
+            staff
+                .iter()
+                .filter(|e| e.manager_id.as_deref() == id)
+                .map(|e| ReportChange {
+                    employee: e.clone(),
+                    new_manager: None,
+                })
+                .collect(),
+        );
+        self.candidates = staff
+            .iter()
+            .filter(|e| e.is_current() && e.id != self.employee.id)
+            .cloned()
+            .collect();
+        self.staff = staff.to_vec();
+        if !self.has_step(self.step) {
+            self.step = self.steps()[0];
+        }
+    }
+
+    fn has_step(&self, step: OffboardingStep) -> bool {
+        match step {
+            OffboardingStep::Reports => self.reports.as_ref().is_none_or(|r| !r.is_empty()),
+            OffboardingStep::Departments => !self.departments.is_empty(),
+            OffboardingStep::Review => true,
+        }
+    }
+
+    /// The steps this offboarding goes through; empty ones are skipped
+    pub fn steps(&self) -> Vec<OffboardingStep> {
+        [
+            OffboardingStep::Reports,
+            OffboardingStep::Departments,
+            OffboardingStep::Review,
+        ]
+        .into_iter()
+        .filter(|s| self.has_step(*s))
+        .collect()
+    }
+
+    pub fn next(&mut self) {
+        let steps = self.steps();
+        if let Some(i) = steps.iter().position(|s| *s == self.step) {
+            self.step = steps[(i + 1).min(steps.len() - 1)];
+        }
+    }
+
+    pub fn back(&mut self) {
+        let steps = self.steps();
+        if let Some(i) = steps.iter().position(|s| *s == self.step) {
+            self.step = steps[i.saturating_sub(1)];
+        }
+    }
+
+    /// The staff with the chosen managers of the reports applied, so
+    /// reports picking each other are caught as well
+    fn planned_staff(&self) -> Vec<Employee> {
+        let mut staff = self.staff.clone();
+        for change in self.reports.iter().flatten() {
+            if let Some(emp) = staff.iter_mut().find(|e| e.id == change.employee.id) {
+                emp.manager_id = change.new_manager.clone();
+            }
+        }
+        staff
+    }
+
+    /// Candidates `report` can move to without a loop in the hierarchy
+    pub fn manager_candidates(&self, report: &str) -> Vec<Employee> {
+        let staff = self.planned_staff();
+        let hierarchy = Hierarchy::new(&staff);
+        self.candidates
+            .iter()
+            .filter(|e| hierarchy.is_valid_manager(report, &e.id))
+            .cloned()
+            .collect()
+    }
+
+    fn candidate_name(&self, id: Option<&str>) -> String {
+        id.and_then(|id| self.candidates.iter().find(|e| e.id == id))
+            .map(|e| format!("{} {}", e.first_name, e.last_name))
+            .unwrap_or_else(|| "nobody".to_string())
+    }
+
+    /// Reasons the batch cannot be applied as planned
+    pub fn problems(&self, perms: &Permissions) -> Vec<String> {
+        let mut problems = Vec::new();
+        let dept = self.employee.department_id.as_deref();
+        let allowed = match self.mode {
+            OffboardingMode::Deactivate => perms.can_deactivate_employee(dept),
+            OffboardingMode::Delete => perms.can_delete_employee(),
+        };
+        if !allowed {
+            problems.push(format!(
+                "Your role ({}) may not offboard {}.",
+                perms.role.as_str(),
+                self.name()
+            ));
+        }
+        let staff = self.planned_staff();
+        let hierarchy = Hierarchy::new(&staff);
+        for change in self.reports.iter().flatten() {
+            let emp = &change.employee;
+            if !perms.can_edit_employee(emp.department_id.as_deref()) {
+                problems.push(format!(
+                    "You may not change the manager of {} {}.",
+                    emp.first_name, emp.last_name
+                ));
+            }
+            if let Some(manager) = &change.new_manager {
+                problems.extend(hierarchy.check(&emp.id, manager).err());
+            }
+        }
+        if !self.departments.is_empty() && !perms.can_manage_departments() {
+            problems.push("Only admins may change department heads.".to_string());
+        }
+        match self.mode {
+            OffboardingMode::Deactivate => {
+                let input = self.input.trim();
+                if !input.is_empty() && parse_date(input).is_none() {
+                    problems.push("Enter the leave date as YYYY-MM-DD.".to_string());
+                }
+            }
+            OffboardingMode::Delete => {
+                if self.input.trim() != self.name() {
+                    problems.push("Type the employee's name to confirm the deletion.".to_string());
+                }
+            }
+        }
+        problems
+    }
+
+    /// One line per change, in the order they are applied
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for change in self.reports.iter().flatten() {
+            lines.push(format!(
+                "{} {} reports to {}",
+                change.employee.first_name,
+                change.employee.last_name,
+                self.candidate_name(change.new_manager.as_deref())
+            ));
+        }
+        for change in &self.departments {
+            lines.push(match &change.new_head {
+                Some(id) => format!(
+                    "{} is headed by {}",
+                    change.department.name,
+                    self.candidate_name(Some(id))
+                ),
+                None => format!("{} is left without a head", change.department.name),
+            });
+        }
+        lines.push(match self.mode {
+            OffboardingMode::Deactivate => format!("{} is deactivated", self.name()),
+            OffboardingMode::Delete => format!("{} is permanently deleted", self.name()),
+        });
+        lines
+    }
+}
+
+/// Apply all reassignments, then deactivate or delete the leaver. If any
+/// reassignment fails, the leaver is left untouched.
+pub async fn apply_offboarding(api: &ApiClient, plan: &Offboarding) -> BulkReport {
+    let mut rows = Vec::new();
+    let mut row = |id: &str, name: String, result: Result<(), String>| {
+        rows.push(BulkRow {
+            id: id.to_string(),
+            name,
+            error: result.err(),
+        });
+    };
+
+    for change in plan.reports.iter().flatten() {
+        let emp = &change.employee;
+        let req = UpdateEmployeeRequest {
+            manager_id: Some(change.new_manager.clone()),
+            ..Default::default()
+        };
+        let result = api
+            .update_employee(&emp.id, &req, emp.updated_at.as_deref())
+            .await
+            .map_err(|e| e.to_string());
+        row(
+            &emp.id,
+            format!(
+                "{} {}: new manager {}",
+                emp.first_name,
+                emp.last_name,
+                plan.candidate_name(change.new_manager.as_deref())
+            ),
+            result,
+        );
+    }
+
+    for change in &plan.departments {
+        let dept = &change.department;
+        let req = UpdateDepartmentRequest {
+            name: Some(dept.name.clone()),
+            head_id: Some(change.new_head.clone()),
+        };
+        let mut result = api
+            .update_department(&dept.id, &req, dept.updated_at.as_deref())
+            .await
+            .map_err(|e| e.to_string());
+        let new_head = change
+            .new_head
+            .as_ref()
+            .and_then(|id| plan.candidates.iter().find(|e| &e.id == id));
+        if let (Ok(()), Some(head)) = (&result, new_head) {
+            if !matches!(
+                Role::parse(&head.role),
+                Some(Role::Admin | Role::DepartmentHead)
+            ) {
+                let promote = UpdateEmployeeRequest {
+                    role: Some("DepartmentHead".to_string()),
+                    ..Default::default()
+                };
+                result = api
+                    .update_employee(&head.id, &promote, None)
+                    .await
+                    .map_err(|e| format!("Head changed, but promotion failed: {}", e));
+            }
+        }
+        row(
+            &dept.id,
+            format!(
+                "{}: new head {}",
+                dept.name,
+                plan.candidate_name(change.new_head.as_deref())
+            ),
+            result,
+        );
+    }
+
+    let reassigned = rows.iter().all(|r| r.error.is_none());
+    let emp = &plan.employee;
+    let result = if !reassigned {
+        Err("Skipped because a reassignment failed.".to_string())
+    } else {
+        match plan.mode {
+            OffboardingMode::Deactivate => {
+                let req = UpdateEmployeeRequest {
+                    active: Some(false),
+                    leave_date: Some(plan.input.trim().to_string())
+                        .filter(|d| !d.is_empty())
+                        .map(Some),
+                    ..Default::default()
+                };
+                api.update_employee(&emp.id, &req, emp.updated_at.as_deref())
+                    .await
+            }
+            OffboardingMode::Delete => api.delete_employee(&emp.id).await,
+        }
+        .map_err(|e| e.to_string())
+    };
+    let verb = match plan.mode {
+        OffboardingMode::Deactivate => "deactivated",
+        OffboardingMode::Delete => "deleted",
+    };
+    rows.push(BulkRow {
+        id: emp.id.clone(),
+        name: format!("{} {}", plan.name(), verb),
+        error: result.err(),
+    });
+
+    BulkReport {
+        title: format!("Offboard {}", plan.name()),
+        rows,
+    }
+}
+
+impl PersonnelApp {
+    /// Whether offboarding `emp` has to go through the wizard. While pages
+    /// are still unloaded, reports may be hiding on them, so that counts too.
+    pub fn needs_offboarding(&self, emp: &Employee) -> bool {
+        self.emp_pager.has_more()
+            || Offboarding::is_needed(emp, &self.view_employees(), &self.view_departments())
+    }
+
+    /// Open the wizard for `emp` and fetch the complete employee list
+    pub fn start_offboarding(&mut self, emp: &Employee, mode: OffboardingMode) {
+        let name = format!("{} {}", emp.first_name, emp.last_name);
+        if self.refuse_wizard_in_draft(&format!("Offboarding {}", name)) {
+            return;
+        }
+        let depts = self.departments.snapshot();
+        // Reports may sit on pages that are not loaded yet, so fetch everyone
+        self.offboarding = Some(Offboarding::new(emp.clone(), mode, &depts));
+        let api = self.api.clone();
+        let staff = self.offboarding_staff.clone();
+        let notify = self.notifier.clone();
+        *staff.lock().unwrap() = None;
+        self.spawn(async move {
+            // Inactive reports still point at the leaver
+            match api.get_employees(true).await {
+                Ok(list) => *staff.lock().unwrap() = Some(list),
+                Err(e) => notify.api_error("Could not load the direct reports", &e),
+            }
+        });
+    }
+
+    fn apply_offboarding(&mut self, plan: Offboarding) {
+        if let Some(problem) = plan.problems(&self.permissions()).into_iter().next() {
+            self.notifier.warning(problem);
+            return;
+        }
+
+        let api = self.api.clone();
+        let emps_ref = self.employees.clone();
+        let emp_status = self.emp_status.clone();
+        let emp_pager = self.emp_pager.clone();
+        let include_inactive = self.emp_include_inactive;
+        let depts_ref = self.departments.clone();
+        let dept_status = self.dept_status.clone();
+        let notify = self.notifier.clone();
+        let bulk_report = self.bulk_report.clone();
+        let mutations = (emp_status.begin_mutation(), dept_status.begin_mutation());
+
+        self.spawn(async move {
+            let report = apply_offboarding(&api, &plan).await;
+            if report.failed() == 0 {
+                notify.success(format!("{} offboarded", plan.name()));
+            } else {
+                notify.warning(format!(
+                    "Offboarding {} incomplete: {} of {} step(s) failed",
+                    plan.name(),
+                    report.failed(),
+                    report.rows.len()
+                ));
+            }
+            *bulk_report.lock().unwrap() = Some(report);
+            dept_status
+                .load_into(&depts_ref, &notify, "departments", api.get_departments())
+                .await;
+            emp_pager
+                .reload(
+                    &emps_ref,
+                    &emp_status,
+                    &notify,
+                    "employees",
+                    |page, limit| api.get_employees_page(page, limit, include_inactive),
+                )
+                .await;
+            drop(mutations);
+        });
+    }
+
+    /// Show the offboarding wizard, if one is in progress
+    pub fn show_offboarding(&mut self, ctx: &Context) {
+        let colors = self.colors;
+        let Some(mut plan) = self.offboarding.take() else {
+            return;
+        };
+        if let Some(staff) = self.offboarding_staff.lock().unwrap().take() {
+            plan.set_staff(&staff);
+        }
+
+        let steps = plan.steps();
+        let position = steps.iter().position(|s| *s == plan.step).unwrap_or(0);
+        let mut open = true;
+        let mut apply = false;
+
+        egui::Window::new(format!("🚪 Offboard {}", plan.name()))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .rounding(Rounding::same(16.0))
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(24.0)),
+            )
+            .show(ctx, |ui| {
+                ui.set_min_width(520.0);
+                ui.label(
+                    RichText::new(format!(
+                        "Step {} of {}: {}",
+                        position + 1,
+                        steps.len(),
+                        plan.step.title()
+                    ))
+                    .size(12.0)
+                    .color(colors.on_surface_variant),
+                );
+                ui.add_space(12.0);
+
+                match plan.step {
+                    OffboardingStep::Reports => show_reports_step(ui, &colors, &mut plan),
+                    OffboardingStep::Departments => show_departments_step(ui, &colors, &mut plan),
+                    OffboardingStep::Review => {
+                        show_review_step(ui, &colors, &mut plan, &self.permissions())
+                    }
+                }
+
+                ui.add_space(24.0);
+                ui.horizontal(|ui| {
+                    let button = |text: &str, fill, color| {
+                        egui::Button::new(RichText::new(text).size(13.0).color(color))
+                            .fill(fill)
+                            .stroke(Stroke::new(1.0, colors.outline_variant))
+                            .rounding(Rounding::same(8.0))
+                            .min_size(Vec2::new(100.0, 40.0))
+                    };
+                    if ui
+                        .add(button("Cancel", colors.surface_variant, colors.on_surface))
+                        .clicked()
+                    {
+                        open = false;
+                    }
+                    ui.add_space(12.0);
+                    if position > 0
+                        && ui
+                            .add(button("Back", colors.surface_variant, colors.on_surface))
+                            .clicked()
+                    {
+                        plan.back();
+                    }
+                    ui.add_space(12.0);
+                    let busy = self.emp_status.is_mutating() || self.dept_status.is_mutating();
+                    if plan.step == OffboardingStep::Review {
+                        let ready = plan.problems(&self.permissions()).is_empty() && !busy;
+                        if ui
+                            .add_enabled(ready, button("Apply", colors.primary, colors.on_primary))
+                            .clicked()
+                        {
+                            apply = true;
+                        }
+                    } else if ui
+                        .add_enabled(
+                            plan.reports.is_some(),
+                            button("Next", colors.primary, colors.on_primary),
+                        )
+                        .clicked()
+                    {
+                        plan.next();
+                    }
+                });
+            });
+
+        if apply {
+            self.apply_offboarding(plan);
+        } else if open {
+            self.offboarding = Some(plan);
+        }
+    }
+}
+
+/// A dropdown of replacement candidates
+fn candidate_dropdown(
+    ui: &mut Ui,
+    colors: &super::Material3Colors,
+    id_salt: impl std::hash::Hash,
+    candidates: &[Employee],
+    none_label: &str,
+    selected: &mut Option<String>,
+) {
+    let current = selected
+        .as_ref()
+        .and_then(|id| candidates.iter().find(|e| &e.id == id))
+        .map(|e| format!("{} {}", e.first_name, e.last_name))
+        .unwrap_or_else(|| none_label.to_string());
+    styled_dropdown(ui, colors, |ui| {
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(RichText::new(current).color(colors.on_surface))
+            .width(240.0)
+            .height(300.0)
+            .show_ui(ui, |ui| {
+                let none = RichText::new(none_label).color(colors.on_surface);
+                if ui.selectable_label(selected.is_none(), none).clicked() {
+                    *selected = None;
+                }
+                ui.separator();
+                for emp in candidates {
+                    let is_selected = selected.as_deref() == Some(emp.id.as_str());
+                    let label = RichText::new(format!(
+                        "{} {} - {}",
+                        emp.first_name, emp.last_name, emp.role
+                    ))
+                    .color(colors.on_surface);
+                    if ui.selectable_label(is_selected, label).clicked() {
+                        *selected = Some(emp.id.clone());
+                    }
+                }
+            });
+    });
+}
+
+fn show_reports_step(ui: &mut Ui, colors: &super::Material3Colors, plan: &mut Offboarding) {
+    let choices: Vec<Vec<Employee>> = plan
+        .reports
+        .iter()
+        .flatten()
+        .map(|change| plan.manager_candidates(&change.employee.id))
+        .collect();
+    let Some(reports) = &mut plan.reports else {
+        ui.horizontal(|ui| {
+            ui.add(egui::Spinner::new().color(colors.primary));
+            ui.label(RichText::new("Looking up direct reports…").color(colors.on_surface_variant));
+        });
+        return;
+    };
+    ui.label(
+        RichText::new(format!(
+            "{} employee(s) report to {} {}. Choose their new manager.",
+            reports.len(),
+            plan.employee.first_name,
+            plan.employee.last_name
+        ))
+        .size(14.0)
+        .color(colors.on_surface),
+    );
+    ui.add_space(12.0);
+
+    let mut for_all = None;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Set all to").color(colors.on_surface_variant));
+        candidate_dropdown(
+            ui,
+            colors,
+            "offboard_all_reports",
+            &plan.candidates,
+            "Choose...",
+            &mut for_all,
+        );
+    });
+    if let Some(id) = for_all {
+        for change in reports.iter_mut() {
+            if change.employee.id != id {
+                change.new_manager = Some(id.clone());
+            }
+        }
+    }
+    ui.add_space(8.0);
+
+    egui::ScrollArea::vertical()
+        .max_height(300.0)
+        .show(ui, |ui| {
+            egui::Grid::new("offboard_reports_grid")
+                .num_columns(2)
+                .spacing([16.0, 8.0])
+                .show(ui, |ui| {
+                    for (i, change) in reports.iter_mut().enumerate() {
+                        ui.label(
+                            RichText::new(format!(
+                                "{} {}",
+                                change.employee.first_name, change.employee.last_name
+                            ))
+                            .color(colors.on_surface),
+                        );
+                        candidate_dropdown(
+                            ui,
+                            colors,
+                            ("offboard_report", i),
+                            &choices[i],
+                            "No manager",
+                            &mut change.new_manager,
+                        );
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn show_departments_step(ui: &mut Ui, colors: &super::Material3Colors, plan: &mut Offboarding) {
+    ui.label(
+        RichText::new(format!(
+            "{} heads {} department(s). Choose who takes over.",
+            plan.name(),
+            plan.departments.len()
+        ))
+        .size(14.0)
+        .color(colors.on_surface),
+    );
+    ui.add_space(12.0);
+    egui::Grid::new("offboard_departments_grid")
+        .num_columns(2)
+        .spacing([16.0, 8.0])
+        .show(ui, |ui| {
+            for (i, change) in plan.departments.iter_mut().enumerate() {
+                ui.label(RichText::new(&change.department.name).color(colors.on_surface));
+                candidate_dropdown(
+                    ui,
+                    colors,
+                    ("offboard_department", i),
+                    &plan.candidates,
+                    "Leave vacant",
+                    &mut change.new_head,
+                );
+                ui.end_row();
+            }
+        });
+}
+
+fn show_review_step(
+    ui: &mut Ui,
+    colors: &super::Material3Colors,
+    plan: &mut Offboarding,
+    perms: &Permissions,
+) {
+    ui.label(
+        RichText::new("The following changes are applied in this order:")
+            .size(14.0)
+            .color(colors.on_surface),
+    );
+    ui.add_space(8.0);
+    for line in plan.summary() {
+        ui.label(
+            RichText::new(format!("• {}", line))
+                .size(13.0)
+                .color(colors.on_surface),
+        );
+    }
+    ui.add_space(16.0);
+
+    let (prompt, hint) = match plan.mode {
+        OffboardingMode::Deactivate => ("Leave date (optional)", "YYYY-MM-DD".to_string()),
+        OffboardingMode::Delete => ("Type the employee's name to confirm", plan.name()),
+    };
+    ui.label(
+        RichText::new(prompt)
+            .size(12.0)
+            .color(colors.on_surface_variant),
+    );
+    ui.add_space(4.0);
+    styled_text_input(ui, colors, &mut plan.input, &hint);
+
+    for problem in plan.problems(perms) {
+        ui.label(RichText::new(problem).size(12.0).color(colors.error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+    use crate::api::models::AuthUser;
+    use crate::gui::fixtures::{department, employee};
+
+    fn admin() -> Permissions {
+        Permissions::for_user(&AuthUser {
+            role: "Admin".to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_wizard_steps_follow_what_the_leaver_holds() {
+        let boss = employee("boss");
+        let staff = vec![
+            boss.clone(),
+            employee("ann").with_manager("boss"),
+            employee("bob").with_manager("ann"),
+        ];
+        let depts = vec![
+            department("d1", "Sales").with_head("boss"),
+            department("d2", "Support"),
+        ];
+        assert!(Offboarding::is_needed(&boss, &staff, &depts));
+        assert!(!Offboarding::is_needed(&staff[2], &staff, &depts));
+
+        let mut plan = Offboarding::new(boss.clone(), OffboardingMode::Deactivate, &depts);
+        assert_eq!(plan.departments.len(), 1);
+        plan.set_staff(&staff);
+        assert_eq!(plan.reports.as_ref().unwrap().len(), 1);
+        assert_eq!(plan.candidates.len(), 2);
+        assert_eq!(
+            plan.steps(),
+            [
+                OffboardingStep::Reports,
+                OffboardingStep::Departments,
+                OffboardingStep::Review
+            ]
+        );
+
+        // Without reports the wizard starts at the departments
+        let mut plan = Offboarding::new(boss, OffboardingMode::Deactivate, &depts);
+        plan.set_staff(&[]);
+        assert_eq!(plan.step, OffboardingStep::Departments);
+        plan.next();
+        assert_eq!(plan.step, OffboardingStep::Review);
+        plan.back();
+        assert_eq!(plan.step, OffboardingStep::Departments);
+    }
+
+    #[test]
+    fn test_new_managers_must_not_loop() {
+        let boss = employee("boss");
+        let staff = vec![
+            boss.clone(),
+            employee("ann").with_manager("boss"),
+            employee("bob").with_manager("boss"),
+            employee("cat").with_manager("ann"),
+        ];
+        let mut plan = Offboarding::new(boss, OffboardingMode::Deactivate, &[]);
+        plan.set_staff(&staff);
+        let ids = |list: Vec<Employee>| list.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(plan.manager_candidates("ann")), ["bob"]);
+
+        // Each picking the other is fine one at a time, not together
+        let reports = plan.reports.as_mut().unwrap();
+        reports[0].new_manager = Some("bob".to_string());
+        reports[1].new_manager = Some("ann".to_string());
+        assert_eq!(plan.problems(&admin()).len(), 2);
+        // cat reports to ann, who now reports to bob
+        assert!(plan.manager_candidates("bob").is_empty());
+    }
+
+    #[test]
+    fn test_problems_block_apply() {
+        let depts = vec![department("d1", "Sales").with_head("boss")];
+        let mut plan = Offboarding::new(
+            employee("boss").with_department("d1"),
+            OffboardingMode::Delete,
+            &depts,
+        );
+        plan.set_staff(&[]);
+        assert_eq!(plan.problems(&admin()).len(), 1);
+        plan.input = "boss Doe".to_string();
+        assert!(plan.problems(&admin()).is_empty());
+
+        let head = Permissions::for_user(&AuthUser {
+            role: "DepartmentHead".to_string(),
+            department_id: Some("d1".to_string()),
+            ..Default::default()
+        });
+        // Heads may neither delete nor change department heads
+        assert_eq!(plan.problems(&head).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_apply_reassigns_before_deactivating() {
+        let server = MockServer::start(|_, _| MockResponse::new(200, "{}")).await;
+        let api = ApiClient::with_config(server.config());
+        let depts = vec![department("d1", "Sales").with_head("boss")];
+        let staff = vec![
+            employee("boss"),
+            employee("ann").with_manager("boss"),
+            employee("cat"),
+        ];
+        let mut plan = Offboarding::new(staff[0].clone(), OffboardingMode::Deactivate, &depts);
+        plan.set_staff(&staff);
+        plan.reports.as_mut().unwrap()[0].new_manager = Some("cat".to_string());
+        plan.departments[0].new_head = Some("cat".to_string());
+        plan.input = "2024-06-30".to_string();
+
+        let report = apply_offboarding(&api, &plan).await;
+        assert_eq!(report.failed(), 0);
+
+        let paths: Vec<String> = server
+            .recorded()
+            .iter()
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "PUT /employees/ann",
+                "PUT /departments/d1",
+                "PUT /employees/cat",
+                "PUT /employees/boss"
+            ]
+        );
+        let last = server.recorded().pop().unwrap();
+        assert!(last.body.contains(r#""leave_date":"2024-06-30""#));
+    }
+
+    #[tokio::test]
+    async fn test_failed_reassignment_keeps_the_leaver() {
+        let server = MockServer::start(|req, _| {
+            if req.path.ends_with("/ann") {
+                MockResponse::new(500, "boom")
+            } else {
+                MockResponse::new(200, "{}")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let staff = vec![employee("boss"), employee("ann").with_manager("boss")];
+        let mut plan = Offboarding::new(staff[0].clone(), OffboardingMode::Delete, &[]);
+        plan.set_staff(&staff);
+
+        let report = apply_offboarding(&api, &plan).await;
+        assert_eq!(report.failed(), 2);
+        assert!(server.recorded().iter().all(|r| r.method != "DELETE"));
+    }
+}
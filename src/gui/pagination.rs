@@ -0,0 +1,278 @@
+//! Page-wise loading of large collections
+//!
+//! A `Pager` remembers how many pages of a collection have been loaded and
+//! how many records the backend reported in total. The view asks for the next
+//! page when the user scrolls to the end of the list; refreshes after a
+//! mutation reload the pages that were already on screen.
+
+use super::store::Store;
+use super::{Notifier, RequestTracker};
+use crate::api::error::ApiError;
+use crate::api::models::Page;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Number of records requested per page
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+struct PagerInner {
+    page_size: usize,
+    loaded_pages: usize,
+    loaded_rows: usize,
+    total: Option<usize>,
+    /// Bumped on every reset, so pages requested before it are dropped
+    generation: u64,
+}
+
+/// Cloneable handle to the pagination state of one collection
+#[derive(Clone)]
+pub struct Pager {
+    inner: Arc<Mutex<PagerInner>>,
+}
+
+impl Default for Pager {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAGE_SIZE)
+    }
+}
+
+#[allow(dead_code)]
+impl Pager {
+    pub fn new(page_size: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(PagerInner {
+                page_size: page_size.max(1),
+                loaded_pages: 0,
+                loaded_rows: 0,
+                total: None,
+                generation: 0,
+            })),
+        }
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.inner.lock().unwrap().page_size
+    }
+
+    /// Total number of records reported by the backend, once a page arrived
+    pub fn total(&self) -> Option<usize> {
+        self.inner.lock().unwrap().total
+    }
+
+    pub fn loaded_rows(&self) -> usize {
+        self.inner.lock().unwrap().loaded_rows
+    }
+
+    /// True until the first page arrived or while records are missing
+    pub fn has_more(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.total.is_none_or(|total| inner.loaded_rows < total)
+    }
+
+    /// Forget the loaded pages; the next load starts again at page 1
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.loaded_pages = 0;
+        inner.loaded_rows = 0;
+        inner.total = None;
+        inner.generation += 1;
+    }
+
+    /// Fetch the next page and append it to `store`
+    pub async fn load_next<T: Clone, F, Fut>(
+        &self,
+        store: &Store<T>,
+        tracker: &RequestTracker,
+        notify: &Notifier,
+        label: &str,
+        fetch: F,
+    ) where
+        F: FnOnce(usize, usize) -> Fut,
+        Fut: Future<Output = Result<Page<T>, ApiError>>,
+    {
+        let (page, limit, generation) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.loaded_pages + 1, inner.page_size, inner.generation)
+        };
+
+        tracker.start_loading();
+        match fetch(page, limit).await {
+            Ok(result) => {
+                let mut inner = self.inner.lock().unwrap();
+                if inner.generation != generation {
+                    return;
+                }
+                if page == 1 {
+                    store.replace(result.items);
+                } else {
+                    store.update(|rows| rows.extend(result.items));
+                }
+                inner.loaded_pages = page;
+                inner.loaded_rows = store.len();
+                inner.total = Some(result.total);
+                tracker.set_loaded();
+            }
+            Err(e) => {
+                tracker.set_failed(e.to_string());
+                notify.api_error(&format!("Could not load {}", label), &e);
+            }
+        }
+    }
+
+    /// Reload every page loaded so far (at least the first one) and replace
+    /// the contents of `store` once all of them arrived
+    pub async fn reload<T, F, Fut>(
+        &self,
+        store: &Store<T>,
+        tracker: &RequestTracker,
+        notify: &Notifier,
+        label: &str,
+        fetch: F,
+    ) where
+        F: Fn(usize, usize) -> Fut,
+        Fut: Future<Output = Result<Page<T>, ApiError>>,
+    {
+        let (pages, limit, generation) = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.generation += 1;
+            (inner.loaded_pages.max(1), inner.page_size, inner.generation)
+        };
+
+        tracker.start_loading();
+        let mut rows = Vec::new();
+        let mut total = 0;
+        let mut loaded_pages = 0;
+        for page in 1..=pages {
+            match fetch(page, limit).await {
+                Ok(result) => {
+                    let done = result.items.len() < limit;
+                    rows.extend(result.items);
+                    total = result.total;
+                    loaded_pages = page;
+                    if done {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracker.set_failed(e.to_string());
+                    notify.api_error(&format!("Could not load {}", label), &e);
+                    return;
+                }
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generation != generation {
+            return;
+        }
+        inner.loaded_pages = loaded_pages;
+        inner.loaded_rows = rows.len();
+        inner.total = Some(total);
+        store.replace(rows);
+        tracker.set_loaded();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve `total` numbered records in pages
+    async fn numbers(total: usize, page: usize, limit: usize) -> Result<Page<usize>, ApiError> {
+        let start = (page - 1) * limit;
+        Ok(Page {
+            items: (start..total.min(start + limit)).collect(),
+            total,
+            page,
+            limit,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_load_next_appends_pages_until_total() {
+        let pager = Pager::new(2);
+        let tracker = RequestTracker::new();
+        let notify = Notifier::new();
+        let store = Store::new();
+        assert!(pager.has_more());
+
+        for _ in 0..2 {
+            pager
+                .load_next(&store, &tracker, &notify, "numbers", |p, l| {
+                    numbers(5, p, l)
+                })
+                .await;
+        }
+        assert_eq!(*store.snapshot(), vec![0, 1, 2, 3]);
+        assert_eq!(pager.total(), Some(5));
+        assert!(pager.has_more());
+
+        pager
+            .load_next(&store, &tracker, &notify, "numbers", |p, l| {
+                numbers(5, p, l)
+            })
+            .await;
+        assert_eq!(store.len(), 5);
+        assert!(!pager.has_more());
+    }
+
+    #[tokio::test]
+    async fn test_reload_keeps_loaded_page_count() {
+        let pager = Pager::new(2);
+        let tracker = RequestTracker::new();
+        let notify = Notifier::new();
+        let store = Store::new();
+        for _ in 0..2 {
+            pager
+                .load_next(&store, &tracker, &notify, "numbers", |p, l| {
+                    numbers(9, p, l)
+                })
+                .await;
+        }
+
+        // One record was deleted on the server in the meantime
+        pager
+            .reload(&store, &tracker, &notify, "numbers", |p, l| {
+                numbers(8, p, l)
+            })
+            .await;
+        assert_eq!(*store.snapshot(), vec![0, 1, 2, 3]);
+        assert_eq!(pager.total(), Some(8));
+        assert_eq!(pager.loaded_rows(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_page_keeps_rows_and_reports() {
+        let pager = Pager::new(2);
+        let tracker = RequestTracker::new();
+        let notify = Notifier::new();
+        let store = Store::new();
+        store.replace(vec![7]);
+
+        pager
+            .load_next(&store, &tracker, &notify, "numbers", |_, _| async {
+                Err::<Page<usize>, _>(ApiError::Timeout)
+            })
+            .await;
+        assert_eq!(*store.snapshot(), vec![7]);
+        assert!(pager.has_more());
+        assert_eq!(notify.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reset_drops_pages_in_flight() {
+        let pager = Pager::new(2);
+        let tracker = RequestTracker::new();
+        let notify = Notifier::new();
+        let store = Store::new();
+
+        pager
+            .load_next(&store, &tracker, &notify, "numbers", |p, l| {
+                pager.reset();
+                numbers(5, p, l)
+            })
+            .await;
+        assert!(store.is_empty());
+        assert_eq!(pager.total(), None);
+    }
+}
@@ -0,0 +1,324 @@
+//! Role-based permissions for the signed-in user
+//!
+//! The roles mirror the values stored in `Employee.role`. Views use these
+//! checks to hide or disable actions, and `execute_confirmed_action` checks
+//! them again before anything is sent to the backend.
+
+use super::dialogs::ConfirmAction;
+use crate::api::models::{AuthUser, Employee};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Role {
+    #[default]
+    Employee,
+    DepartmentHead,
+    DeputyHead,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Employee,
+        Role::DepartmentHead,
+        Role::DeputyHead,
+        Role::Admin,
+    ];
+
+    pub fn parse(value: &str) -> Option<Role> {
+        Role::ALL.into_iter().find(|r| r.as_str() == value)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Employee => "Employee",
+            Role::DepartmentHead => "DepartmentHead",
+            Role::DeputyHead => "DeputyHead",
+            Role::Admin => "Admin",
+        }
+    }
+}
+
+/// What the signed-in user may do. Unknown roles get the permissions of a
+/// plain `Employee`, i.e. read-only access.
+#[derive(Debug, Clone, Default)]
+pub struct Permissions {
+    pub role: Role,
+    pub department_id: Option<String>,
+}
+
+impl Permissions {
+    pub fn for_user(user: &AuthUser) -> Self {
+        Self {
+            role: Role::parse(&user.role).unwrap_or_default(),
+            department_id: user.department_id.clone(),
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    /// True if `dept_id` is the user's own department
+    fn is_own_department(&self, dept_id: Option<&str>) -> bool {
+        dept_id.is_some() && dept_id == self.department_id.as_deref()
+    }
+
+    pub fn can_manage_departments(&self) -> bool {
+        self.is_admin()
+    }
+
+    pub fn can_manage_salary_grades(&self) -> bool {
+        self.is_admin()
+    }
+
+    pub fn can_create_employees(&self) -> bool {
+        matches!(self.role, Role::Admin | Role::DepartmentHead)
+    }
+
+    /// Department heads and deputies may edit members of their own department
+    pub fn can_edit_employee(&self, employee_dept: Option<&str>) -> bool {
+        match self.role {
+            Role::Admin => true,
+            Role::DepartmentHead | Role::DeputyHead => self.is_own_department(employee_dept),
+            Role::Employee => false,
+        }
+    }
+
+    /// Deactivation is the regular way to offboard someone
+    pub fn can_deactivate_employee(&self, employee_dept: Option<&str>) -> bool {
+        match self.role {
+            Role::Admin => true,
+            Role::DepartmentHead => self.is_own_department(employee_dept),
+            Role::DeputyHead | Role::Employee => false,
+        }
+    }
+
+    /// Hard deletes remove the record for good and are reserved to admins
+    pub fn can_delete_employee(&self) -> bool {
+        self.is_admin()
+    }
+
+    /// Roles this user may give to others
+    pub fn assignable_roles(&self) -> &'static [Role] {
+        match self.role {
+            Role::Admin => &Role::ALL,
+            Role::DepartmentHead => &[Role::Employee, Role::DeputyHead],
+            Role::DeputyHead | Role::Employee => &[],
+        }
+    }
+
+    /// Whether an employee may be placed in `dept_id` by this user
+    pub fn can_assign_department(&self, dept_id: Option<&str>) -> bool {
+        self.is_admin() || self.is_own_department(dept_id)
+    }
+
+    /// Validate a create or update of an employee with the given form values.
+    /// `current` is the stored employee for updates.
+    pub fn check_employee_change(
+        &self,
+        current: Option<&Employee>,
+        new_dept: Option<&str>,
+        new_role: &str,
+    ) -> Result<(), String> {
+        match current {
+            Some(emp) if !self.can_edit_employee(emp.department_id.as_deref()) => {
+                return Err(format!(
+                    "You may not edit {} {}.",
+                    emp.first_name, emp.last_name
+                ));
+            }
+            None if !self.can_create_employees() => {
+                return Err("You may not create employees.".to_string());
+            }
+            _ => {}
+        }
+
+        let dept_unchanged = current.is_some_and(|e| e.department_id.as_deref() == new_dept);
+        if !dept_unchanged && !self.can_assign_department(new_dept) {
+            return Err("You may only assign employees to your own department.".to_string());
+        }
+
+        let role_unchanged = current.is_some_and(|e| e.role == new_role);
+        let role_assignable =
+            Role::parse(new_role).is_some_and(|r| self.assignable_roles().contains(&r));
+        if !role_unchanged && !role_assignable {
+            return Err(format!("You may not assign the role \"{}\".", new_role));
+        }
+
+        Ok(())
+    }
+
+    /// Check a confirmed action before it is executed. Employee creates and
+    /// updates are validated separately through `check_employee_change`.
+    pub fn check_action(
+        &self,
+        action: &ConfirmAction,
+        employees: &[Employee],
+    ) -> Result<(), String> {
+        let employee_dept = |id: &str| {
+            employees
+                .iter()
+                .find(|e| e.id == id)
+                .and_then(|e| e.department_id.clone())
+        };
+
+        let allowed = match action {
+            ConfirmAction::CreateDepartment { .. }
+            | ConfirmAction::UpdateDepartment { .. }
+            | ConfirmAction::DeleteDepartment { .. } => self.can_manage_departments(),
+            ConfirmAction::CreateSalaryGrade { .. }
+            | ConfirmAction::UpdateSalaryGrade { .. }
+            | ConfirmAction::DeleteSalaryGrade { .. } => self.can_manage_salary_grades(),
+            ConfirmAction::DeleteEmployee { .. } => self.can_delete_employee(),
+            ConfirmAction::DeactivateEmployee { id, .. } => {
+                self.can_deactivate_employee(employee_dept(id).as_deref())
+            }
+            ConfirmAction::CreateEmployee { .. } => self.can_create_employees(),
+            ConfirmAction::UpdateEmployee { id, .. }
+            | ConfirmAction::ReactivateEmployee { id, .. } => {
+                self.can_edit_employee(employee_dept(id).as_deref())
+            }
+            // Each employee is checked again with `BulkAction::check`
+            ConfirmAction::BulkUpdateEmployees { .. } => self.role != Role::Employee,
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(format!(
+                "Your role ({}) does not allow this action.",
+                self.role.as_str()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::employee;
+
+    fn user(role: &str, dept: Option<&str>) -> Permissions {
+        Permissions::for_user(&AuthUser {
+            id: "me".to_string(),
+            role: role.to_string(),
+            department_id: dept.map(|d| d.to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_role_parse() {
+        assert_eq!(Role::parse("DeputyHead"), Some(Role::DeputyHead));
+        assert_eq!(Role::parse("Intern"), None);
+        for role in Role::ALL {
+            assert_eq!(Role::parse(role.as_str()), Some(role));
+        }
+    }
+
+    #[test]
+    fn test_unknown_role_is_read_only() {
+        let perms = user("Intern", Some("d1"));
+        assert_eq!(perms.role, Role::Employee);
+        assert!(!perms.can_create_employees());
+        assert!(!perms.can_edit_employee(Some("d1")));
+        assert!(perms.assignable_roles().is_empty());
+    }
+
+    #[test]
+    fn test_only_admin_manages_grades_and_departments() {
+        assert!(user("Admin", None).can_manage_salary_grades());
+        assert!(user("Admin", None).can_manage_departments());
+        for role in ["Employee", "DepartmentHead", "DeputyHead"] {
+            assert!(!user(role, Some("d1")).can_manage_salary_grades());
+            assert!(!user(role, Some("d1")).can_manage_departments());
+        }
+    }
+
+    #[test]
+    fn test_department_head_is_limited_to_own_department() {
+        let head = user("DepartmentHead", Some("d1"));
+        assert!(head.can_edit_employee(Some("d1")));
+        assert!(!head.can_edit_employee(Some("d2")));
+        assert!(!head.can_edit_employee(None));
+        assert!(head.can_deactivate_employee(Some("d1")));
+        assert!(!head.can_deactivate_employee(Some("d2")));
+        assert!(!head.can_delete_employee());
+
+        let deputy = user("DeputyHead", Some("d1"));
+        assert!(deputy.can_edit_employee(Some("d1")));
+        assert!(!deputy.can_deactivate_employee(Some("d1")));
+        assert!(user("Admin", None).can_delete_employee());
+    }
+
+    #[test]
+    fn test_check_employee_change() {
+        let head = user("DepartmentHead", Some("d1"));
+        let member = employee("emp-1").with_department("d1");
+
+        assert!(head
+            .check_employee_change(Some(&member), Some("d1"), "DeputyHead")
+            .is_ok());
+        assert!(head
+            .check_employee_change(Some(&member), Some("d2"), "Employee")
+            .is_err());
+        assert!(head
+            .check_employee_change(Some(&member), Some("d1"), "Admin")
+            .is_err());
+        assert!(head
+            .check_employee_change(
+                Some(&employee("emp-1").with_department("d2")),
+                Some("d2"),
+                "Employee"
+            )
+            .is_err());
+        assert!(head
+            .check_employee_change(None, Some("d1"), "Employee")
+            .is_ok());
+
+        // Keeping a role the user could not assign is fine
+        let other_head = employee("emp-1")
+            .with_department("d1")
+            .with_role("DepartmentHead");
+        assert!(head
+            .check_employee_change(Some(&other_head), Some("d1"), "DepartmentHead")
+            .is_ok());
+
+        let admin = user("Admin", None);
+        assert!(admin.check_employee_change(None, None, "Admin").is_ok());
+    }
+
+    #[test]
+    fn test_check_action() {
+        let emps = vec![employee("emp-1").with_department("d1")];
+        let deactivate = ConfirmAction::DeactivateEmployee {
+            id: "emp-1".to_string(),
+            name: "Ada Lovelace".to_string(),
+            version: None,
+        };
+        let delete = ConfirmAction::DeleteEmployee {
+            id: "emp-1".to_string(),
+            name: "Ada Lovelace".to_string(),
+        };
+        let grade = ConfirmAction::DeleteSalaryGrade {
+            id: "g1".to_string(),
+            code: "E5".to_string(),
+            employee_count: 0,
+        };
+
+        assert!(user("DepartmentHead", Some("d1"))
+            .check_action(&deactivate, &emps)
+            .is_ok());
+        assert!(user("DepartmentHead", Some("d2"))
+            .check_action(&deactivate, &emps)
+            .is_err());
+        assert!(user("DepartmentHead", Some("d1"))
+            .check_action(&delete, &emps)
+            .is_err());
+        assert!(user("Admin", None).check_action(&delete, &emps).is_ok());
+        assert!(user("DepartmentHead", Some("d1"))
+            .check_action(&grade, &emps)
+            .is_err());
+        assert!(user("Admin", None).check_action(&grade, &emps).is_ok());
+    }
+}
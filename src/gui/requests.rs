@@ -0,0 +1,281 @@
+//! Request tracking for the entity collections
+//!
+//! Every collection (departments, employees, salary grades) owns a
+//! `RequestTracker` that records whether it is idle, loading, loaded or failed,
+//! and how many mutations against it are still in flight. Trackers are cheap
+//! to clone and are moved into the spawned tasks alongside the data they guard.
+
+use super::store::Store;
+use super::Notifier;
+use crate::api::error::ApiError;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum LoadState {
+    #[default]
+    Idle,
+    Loading,
+    Loaded(Instant),
+    Failed(String),
+}
+
+#[derive(Default)]
+struct TrackerInner {
+    state: LoadState,
+    last_loaded: Option<Instant>,
+    mutations: usize,
+    /// Bumped by every `load_into`, so only the newest load stores its result
+    generation: u64,
+}
+
+/// Cloneable handle to the request state of one collection
+#[derive(Clone, Default)]
+pub struct RequestTracker {
+    inner: Arc<Mutex<TrackerInner>>,
+}
+
+#[allow(dead_code)]
+impl RequestTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> LoadState {
+        self.inner.lock().unwrap().state.clone()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.state() == LoadState::Loading
+    }
+
+    /// When the collection was last loaded successfully, kept across later
+    /// loading or failed states
+    pub fn last_loaded(&self) -> Option<Instant> {
+        self.inner.lock().unwrap().last_loaded
+    }
+
+    pub fn start_loading(&self) {
+        self.inner.lock().unwrap().state = LoadState::Loading;
+    }
+
+    pub fn set_loaded(&self) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = LoadState::Loaded(now);
+        inner.last_loaded = Some(now);
+    }
+
+    pub fn set_failed(&self, message: impl Into<String>) {
+        self.inner.lock().unwrap().state = LoadState::Failed(message.into());
+    }
+
+    /// Mark a mutation as in flight until the returned guard is dropped
+    pub fn begin_mutation(&self) -> MutationGuard {
+        self.inner.lock().unwrap().mutations += 1;
+        MutationGuard {
+            tracker: self.clone(),
+        }
+    }
+
+    pub fn is_mutating(&self) -> bool {
+        self.inner.lock().unwrap().mutations > 0
+    }
+
+    /// True while the collection is loading or being modified
+    pub fn is_busy(&self) -> bool {
+        self.is_loading() || self.is_mutating()
+    }
+
+    /// Run a fetch, store its result and update the tracked state. Failures
+    /// are reported through `notify` as "Could not load <label>". A result
+    /// that arrives after a newer load was started is dropped.
+    pub async fn load_into<T>(
+        &self,
+        store: &Store<T>,
+        notify: &Notifier,
+        label: &str,
+        fetch: impl Future<Output = Result<Vec<T>, ApiError>>,
+    ) {
+        let generation = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.generation += 1;
+            inner.state = LoadState::Loading;
+            inner.generation
+        };
+        let result = fetch.await;
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generation != generation {
+            return;
+        }
+        match result {
+            Ok(data) => {
+                store.replace(data);
+                let now = Instant::now();
+                inner.state = LoadState::Loaded(now);
+                inner.last_loaded = Some(now);
+            }
+            Err(e) => {
+                inner.state = LoadState::Failed(e.to_string());
+                drop(inner);
+                notify.api_error(&format!("Could not load {}", label), &e);
+            }
+        }
+    }
+}
+
+/// Keeps a mutation marked as in flight for as long as it is alive
+pub struct MutationGuard {
+    tracker: RequestTracker,
+}
+
+impl Drop for MutationGuard {
+    fn drop(&mut self) {
+        let mut inner = self.tracker.inner.lock().unwrap();
+        inner.mutations = inner.mutations.saturating_sub(1);
+    }
+}
+
+/// Format the time since an event for display, e.g. "12s ago"
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 5 {
+        "just now".to_string()
+    } else if secs < 60 {
+        format!("{}s ago", secs)
+    } else if secs < 3600 {
+        format!("{} min ago", secs / 60)
+    } else {
+        format!("{} h ago", secs / 3600)
+    }
+}
+
+/// Time until `format_elapsed` shows a different text
+pub fn until_elapsed_changes(elapsed: Duration) -> Duration {
+    let secs = elapsed.as_secs();
+    let next = if secs < 5 {
+        5
+    } else if secs < 60 {
+        secs + 1
+    } else if secs < 3600 {
+        (secs / 60 + 1) * 60
+    } else {
+        (secs / 3600 + 1) * 3600
+    };
+    Duration::from_secs(next).saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_state_transitions() {
+        let tracker = RequestTracker::new();
+        assert_eq!(tracker.state(), LoadState::Idle);
+        assert!(tracker.last_loaded().is_none());
+
+        tracker.start_loading();
+        assert!(tracker.is_loading());
+
+        tracker.set_loaded();
+        assert!(matches!(tracker.state(), LoadState::Loaded(_)));
+        let loaded_at = tracker.last_loaded().unwrap();
+
+        tracker.start_loading();
+        tracker.set_failed("timeout");
+        assert_eq!(tracker.state(), LoadState::Failed("timeout".to_string()));
+        assert_eq!(tracker.last_loaded(), Some(loaded_at));
+    }
+
+    #[test]
+    fn test_mutation_guard() {
+        let tracker = RequestTracker::new();
+        let first = tracker.begin_mutation();
+        let second = tracker.clone().begin_mutation();
+        assert!(tracker.is_mutating());
+        assert!(tracker.is_busy());
+
+        drop(first);
+        assert!(tracker.is_mutating());
+        drop(second);
+        assert!(!tracker.is_mutating());
+        assert!(!tracker.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_load_into_stores_data_and_reports_failure() {
+        let tracker = RequestTracker::new();
+        let notify = Notifier::new();
+        let store = Store::new();
+
+        tracker
+            .load_into(&store, &notify, "numbers", async { Ok(vec![1, 2, 3]) })
+            .await;
+        assert_eq!(*store.snapshot(), vec![1, 2, 3]);
+        assert!(matches!(tracker.state(), LoadState::Loaded(_)));
+
+        tracker
+            .load_into(&store, &notify, "numbers", async { Err(ApiError::Timeout) })
+            .await;
+        assert_eq!(*store.snapshot(), vec![1, 2, 3]);
+        assert!(matches!(tracker.state(), LoadState::Failed(_)));
+        assert!(notify.visible()[0]
+            .message
+            .starts_with("Could not load numbers"));
+    }
+
+    #[tokio::test]
+    async fn test_load_into_drops_results_of_older_loads() {
+        let tracker = RequestTracker::new();
+        let notify = Notifier::new();
+        let store = Store::new();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+        let older = tracker.load_into(&store, &notify, "numbers", async {
+            released.await.unwrap();
+            Ok(vec![1])
+        });
+        let newer = async {
+            tokio::task::yield_now().await;
+            tracker
+                .load_into(&store, &notify, "numbers", async { Ok(vec![2]) })
+                .await;
+            release.send(()).unwrap();
+        };
+        tokio::join!(older, newer);
+
+        assert_eq!(*store.snapshot(), vec![2]);
+        assert!(matches!(tracker.state(), LoadState::Loaded(_)));
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(2)), "just now");
+        assert_eq!(format_elapsed(Duration::from_secs(42)), "42s ago");
+        assert_eq!(format_elapsed(Duration::from_secs(150)), "2 min ago");
+        assert_eq!(format_elapsed(Duration::from_secs(7300)), "2 h ago");
+    }
+
+    #[test]
+    fn test_until_elapsed_changes() {
+        assert_eq!(
+            until_elapsed_changes(Duration::from_secs(2)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            until_elapsed_changes(Duration::from_millis(42_250)),
+            Duration::from_millis(750)
+        );
+        assert_eq!(
+            until_elapsed_changes(Duration::from_secs(150)),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            until_elapsed_changes(Duration::from_secs(7300)),
+            Duration::from_secs(3500)
+        );
+    }
+}
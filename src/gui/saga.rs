@@ -0,0 +1,300 @@
+//! Multi-step changes that are rolled back when a step fails
+//!
+//! Some edits need several requests that only make sense together, e.g.
+//! changing a department's head also demotes the previous head and promotes
+//! the new one. A saga runs such steps in order and remembers how to undo
+//! each of them. If a step fails, the completed steps are compensated in
+//! reverse order, and the outcome of every step and compensation ends up in
+//! a `BulkReport`.
+
+use super::bulk::{BulkReport, BulkRow};
+use crate::api::client::ApiClient;
+use crate::api::error::ApiError;
+use crate::api::models::*;
+
+/// A single request of a saga
+#[derive(Debug, Clone)]
+pub enum SagaChange {
+    Department {
+        id: String,
+        req: UpdateDepartmentRequest,
+        version: Option<String>,
+    },
+    Role {
+        employee_id: String,
+        role: String,
+    },
+}
+
+impl SagaChange {
+    fn id(&self) -> &str {
+        match self {
+            SagaChange::Department { id, .. } => id,
+            SagaChange::Role { employee_id, .. } => employee_id,
+        }
+    }
+
+    async fn run(&self, api: &ApiClient) -> Result<(), ApiError> {
+        match self {
+            SagaChange::Department { id, req, version } => {
+                api.update_department(id, req, version.as_deref()).await
+            }
+            SagaChange::Role { employee_id, role } => {
+                let req = UpdateEmployeeRequest {
+                    role: Some(role.clone()),
+                    ..Default::default()
+                };
+                api.update_employee(employee_id, &req, None).await
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SagaStep {
+    pub label: String,
+    pub change: SagaChange,
+    /// How to revert the step, with its own label; `None` if it needs none
+    pub undo: Option<(String, SagaChange)>,
+}
+
+#[derive(Debug)]
+pub struct SagaOutcome {
+    pub report: BulkReport,
+    /// Index and error of the step that failed
+    pub failure: Option<(usize, ApiError)>,
+}
+
+/// Run `steps` in order; when one fails, undo the completed ones in reverse
+pub async fn run_saga(api: &ApiClient, title: String, steps: Vec<SagaStep>) -> SagaOutcome {
+    let mut rows = Vec::new();
+    let mut failure = None;
+    let mut done = 0;
+    for (i, step) in steps.iter().enumerate() {
+        match step.change.run(api).await {
+            Ok(()) => {
+                rows.push(BulkRow {
+                    id: step.change.id().to_string(),
+                    name: step.label.clone(),
+                    error: None,
+                });
+                done += 1;
+            }
+            Err(e) => {
+                rows.push(BulkRow {
+                    id: step.change.id().to_string(),
+                    name: step.label.clone(),
+                    error: Some(e.to_string()),
+                });
+                failure = Some((i, e));
+                break;
+            }
+        }
+    }
+
+    if failure.is_some() {
+        for step in steps[done + 1..].iter() {
+            rows.push(BulkRow {
+                id: step.change.id().to_string(),
+                name: step.label.clone(),
+                error: Some("Not attempted".to_string()),
+            });
+        }
+        for step in steps[..done].iter().rev() {
+            let Some((label, undo)) = &step.undo else {
+                continue;
+            };
+            let result = undo.run(api).await;
+            rows.push(BulkRow {
+                id: undo.id().to_string(),
+                name: format!("Rolled back: {}", label),
+                error: result.err().map(|e| format!("Rollback failed: {}", e)),
+            });
+        }
+    }
+
+    SagaOutcome {
+        report: BulkReport { title, rows },
+        failure,
+    }
+}
+
+/// Steps for updating `current` with `req`, moving the head role from
+/// `old_head` to `new_head`. Admins keep their role either way.
+pub fn department_head_saga(
+    current: &Department,
+    req: UpdateDepartmentRequest,
+    version: Option<String>,
+    old_head: Option<&Employee>,
+    new_head: Option<&Employee>,
+) -> Vec<SagaStep> {
+    let full_name = |e: &Employee| format!("{} {}", e.first_name, e.last_name);
+    let role_change = |e: &Employee, role: &str| SagaChange::Role {
+        employee_id: e.id.clone(),
+        role: role.to_string(),
+    };
+    let same_person = matches!((old_head, new_head), (Some(a), Some(b)) if a.id == b.id);
+
+    let mut steps = vec![SagaStep {
+        label: format!("Update department \"{}\"", current.name),
+        change: SagaChange::Department {
+            id: current.id.clone(),
+            req,
+            version,
+        },
+        undo: Some((
+            format!("Restore department \"{}\"", current.name),
+            SagaChange::Department {
+                id: current.id.clone(),
+                req: UpdateDepartmentRequest {
+                    name: Some(current.name.clone()),
+                    head_id: Some(current.head_id.clone()),
+                },
+                version: None,
+            },
+        )),
+    }];
+    if let Some(old) = old_head.filter(|e| !same_person && e.role == "DepartmentHead") {
+        steps.push(SagaStep {
+            label: format!("Demote {} to Employee", full_name(old)),
+            change: role_change(old, "Employee"),
+            undo: Some((
+                format!("Restore {} as {}", full_name(old), old.role),
+                role_change(old, &old.role),
+            )),
+        });
+    }
+    if let Some(new) =
+        new_head.filter(|e| !same_person && e.role != "DepartmentHead" && e.role != "Admin")
+    {
+        steps.push(SagaStep {
+            label: format!("Promote {} to DepartmentHead", full_name(new)),
+            change: role_change(new, "DepartmentHead"),
+            undo: Some((
+                format!("Restore {} as {}", full_name(new), new.role),
+                role_change(new, &new.role),
+            )),
+        });
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{MockResponse, MockServer};
+    use crate::gui::fixtures::{department, employee};
+
+    fn steps() -> Vec<SagaStep> {
+        let dept = department("d1", "Sales")
+            .with_head("old")
+            .with_version("v1");
+        let req = UpdateDepartmentRequest {
+            name: Some("Sales".to_string()),
+            head_id: Some(Some("new".to_string())),
+        };
+        department_head_saga(
+            &dept,
+            req,
+            dept.updated_at.clone(),
+            Some(&employee("old").with_role("DepartmentHead")),
+            Some(&employee("new").with_role("DeputyHead")),
+        )
+    }
+
+    #[test]
+    fn test_head_saga_skips_needless_role_changes() {
+        assert_eq!(steps().len(), 3);
+
+        let dept = department("d1", "Sales");
+        let admin = employee("boss").with_role("Admin");
+        let steps = department_head_saga(
+            &dept,
+            UpdateDepartmentRequest::default(),
+            None,
+            None,
+            Some(&admin),
+        );
+        assert_eq!(steps.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_promotion_rolls_back_in_reverse() {
+        let server = MockServer::start(|req, _| {
+            if req.path.ends_with("/new") {
+                MockResponse::new(500, "boom")
+            } else {
+                MockResponse::new(200, "{}")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+
+        let outcome = run_saga(&api, "Update Sales".to_string(), steps()).await;
+        assert_eq!(outcome.failure.as_ref().map(|(i, _)| *i), Some(2));
+        assert_eq!(outcome.report.rows.len(), 5);
+        assert_eq!(outcome.report.failed(), 1);
+
+        let requests = server.recorded();
+        let paths: Vec<&str> = requests.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "/departments/d1",
+                "/employees/old",
+                "/employees/new",
+                "/employees/old",
+                "/departments/d1"
+            ]
+        );
+        assert_eq!(requests[0].header("If-Match"), Some("\"v1\""));
+        assert!(requests[3].body.contains(r#""role":"DepartmentHead""#));
+        assert!(requests[4].body.contains(r#""head_id":"old""#));
+        assert_eq!(requests[4].header("If-Match"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_clears_a_head_that_was_empty() {
+        let server = MockServer::start(|req, _| {
+            if req.path.ends_with("/new") {
+                MockResponse::new(500, "boom")
+            } else {
+                MockResponse::new(200, "{}")
+            }
+        })
+        .await;
+        let api = ApiClient::with_config(server.config());
+        let dept = department("d1", "Sales");
+        let req = UpdateDepartmentRequest {
+            name: Some("Sales".to_string()),
+            head_id: Some(Some("new".to_string())),
+        };
+        let steps = department_head_saga(
+            &dept,
+            req,
+            None,
+            None,
+            Some(&employee("new").with_role("DeputyHead")),
+        );
+
+        let outcome = run_saga(&api, "Update Sales".to_string(), steps).await;
+        assert_eq!(outcome.failure.as_ref().map(|(i, _)| *i), Some(1));
+        let requests = server.recorded();
+        assert_eq!(requests[2].path, "/departments/d1");
+        let body: serde_json::Value = serde_json::from_str(&requests[2].body).unwrap();
+        assert_eq!(body, serde_json::json!({"name": "Sales", "head_id": null}));
+    }
+
+    #[tokio::test]
+    async fn test_first_step_failure_has_nothing_to_undo() {
+        let server = MockServer::start(|_, _| MockResponse::new(412, "")).await;
+        let api = ApiClient::with_config(server.config());
+
+        let outcome = run_saga(&api, "Update Sales".to_string(), steps()).await;
+        let (index, error) = outcome.failure.unwrap();
+        assert_eq!(index, 0);
+        assert!(error.is_edit_conflict());
+        assert_eq!(server.request_count(), 1);
+        assert_eq!(outcome.report.rows.len(), 3);
+    }
+}
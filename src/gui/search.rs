@@ -0,0 +1,320 @@
+//! Employee search with fuzzy matching and scoped filter tokens
+//!
+//! A query is a list of whitespace separated words. Plain words are matched
+//! fuzzily against the employee's name and email; `dept:`, `role:` and
+//! `grade:` tokens filter on the department name, role and grade code.
+//! Values containing spaces can be quoted, e.g. `dept:"Human Resources"`.
+//! Matched characters are reported so the card can highlight them.
+
+use super::Material3Colors;
+use crate::api::models::Employee;
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId};
+
+/// A parsed search query
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmployeeQuery {
+    terms: Vec<String>,
+    departments: Vec<String>,
+    roles: Vec<String>,
+    grades: Vec<String>,
+}
+
+/// Why an employee matched, as character indices into each displayed field
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmployeeMatch {
+    pub score: i32,
+    /// Indices into "first_name last_name"
+    pub name: Vec<usize>,
+    pub email: Vec<usize>,
+    pub role: Vec<usize>,
+    pub department: Vec<usize>,
+    pub grade: Vec<usize>,
+}
+
+/// Split the input into words, keeping quoted parts together
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+impl EmployeeQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        for token in tokenize(input) {
+            let scoped = token.split_once(':').and_then(|(key, value)| {
+                let list = match key.to_lowercase().as_str() {
+                    "dept" | "department" => &mut query.departments,
+                    "role" => &mut query.roles,
+                    "grade" => &mut query.grades,
+                    _ => return None,
+                };
+                if !value.is_empty() {
+                    list.push(value.to_lowercase());
+                }
+                Some(())
+            });
+            if scoped.is_none() {
+                query.terms.push(token.to_lowercase());
+            }
+        }
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+            && self.departments.is_empty()
+            && self.roles.is_empty()
+            && self.grades.is_empty()
+    }
+
+    /// True if results should be ordered by score rather than list order
+    pub fn is_ranked(&self) -> bool {
+        !self.terms.is_empty()
+    }
+
+    /// Match an employee, given the names of their department and grade
+    pub fn matches(
+        &self,
+        emp: &Employee,
+        department: Option<&str>,
+        grade: Option<&str>,
+    ) -> Option<EmployeeMatch> {
+        let mut result = EmployeeMatch::default();
+
+        if !self.departments.is_empty() {
+            let name = department?;
+            result.department = self
+                .departments
+                .iter()
+                .find_map(|value| find_substring(value, name))?;
+        }
+        if !self.roles.is_empty() {
+            result.role = self
+                .roles
+                .iter()
+                .find_map(|value| find_substring(value, &emp.role))?;
+        }
+        if !self.grades.is_empty() {
+            let code = grade?;
+            result.grade = self
+                .grades
+                .iter()
+                .find_map(|value| find_prefix(value, code))?;
+        }
+
+        let name = format!("{} {}", emp.first_name, emp.last_name);
+        for term in &self.terms {
+            let by_name = fuzzy_match(term, &name);
+            let by_email = fuzzy_match(term, &emp.email);
+            match (by_name, by_email) {
+                (Some((score, hits)), Some((email_score, _))) if score >= email_score => {
+                    result.score += score;
+                    result.name.extend(hits);
+                }
+                (Some((score, hits)), None) => {
+                    result.score += score;
+                    result.name.extend(hits);
+                }
+                (_, Some((score, hits))) => {
+                    result.score += score;
+                    result.email.extend(hits);
+                }
+                (None, None) => return None,
+            }
+        }
+
+        result.name.sort_unstable();
+        result.name.dedup();
+        result.email.sort_unstable();
+        result.email.dedup();
+        Some(result)
+    }
+}
+
+/// Case-insensitive substring search, returning the matched char indices
+fn find_substring(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    if pattern.len() > text.len() {
+        return None;
+    }
+    (0..=text.len() - pattern.len())
+        .find(|&start| text[start..start + pattern.len()] == pattern[..])
+        .map(|start| (start..start + pattern.len()).collect())
+}
+
+fn find_prefix(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    text.to_lowercase()
+        .starts_with(pattern)
+        .then(|| (0..pattern.chars().count()).collect())
+}
+
+/// Fuzzy match `pattern` (lowercase) against `text`.
+///
+/// Every pattern character must appear in order. Contiguous runs and
+/// matches at word starts score higher, so "ali" ranks "Alice" above
+/// "Marian Lind". Returns the score and the matched char indices.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    if pattern.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    if let Some(hits) = find_substring(pattern, text) {
+        let start = hits[0];
+        let at_word_start = start == 0
+            || text
+                .chars()
+                .nth(start - 1)
+                .is_some_and(|c| !c.is_alphanumeric());
+        let score = 100 + if at_word_start { 50 } else { 0 } - start.min(50) as i32;
+        return Some((score, hits));
+    }
+
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut hits = Vec::new();
+    let mut score = 0;
+    let mut next = 0;
+    for p in pattern.chars() {
+        let offset = text[next..].iter().position(|&c| c == p)?;
+        let index = next + offset;
+        score += 1;
+        if hits.last().is_some_and(|&last| last + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= offset.min(10) as i32;
+        hits.push(index);
+        next = index + 1;
+    }
+    Some((score, hits))
+}
+
+/// Lay out `prefix` followed by `text`, with the chars of `text` at `hits`
+/// highlighted
+pub fn highlighted(
+    prefix: &str,
+    text: &str,
+    hits: &[usize],
+    size: f32,
+    color: Color32,
+    colors: &Material3Colors,
+) -> LayoutJob {
+    let plain = TextFormat {
+        font_id: FontId::proportional(size),
+        color,
+        ..Default::default()
+    };
+    let marked = TextFormat {
+        background: colors.primary_container,
+        color: colors.on_primary_container,
+        ..plain.clone()
+    };
+
+    let mut job = LayoutJob::default();
+    job.append(prefix, 0.0, plain.clone());
+    let mut run = String::new();
+    let mut run_marked = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_hit = hits.binary_search(&i).is_ok();
+        if is_hit != run_marked && !run.is_empty() {
+            let format = if run_marked { &marked } else { &plain };
+            job.append(&std::mem::take(&mut run), 0.0, format.clone());
+        }
+        run_marked = is_hit;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        job.append(&run, 0.0, if run_marked { marked } else { plain });
+    }
+    job
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::employee;
+
+    #[test]
+    fn test_parse_scoped_tokens() {
+        let query = EmployeeQuery::parse(r#"ada dept:"Human Resources" ROLE:admin grade:E5 x:y"#);
+        assert_eq!(query.terms, ["ada", "x:y"]);
+        assert_eq!(query.departments, ["human resources"]);
+        assert_eq!(query.roles, ["admin"]);
+        assert_eq!(query.grades, ["e5"]);
+        assert!(EmployeeQuery::parse("  ").is_empty());
+        assert!(!EmployeeQuery::parse("role:").is_ranked());
+    }
+
+    #[test]
+    fn test_fuzzy_match_prefers_contiguous_word_starts() {
+        let (alice, hits) = fuzzy_match("ali", "Alice Smith").unwrap();
+        assert_eq!(hits, [0, 1, 2]);
+        let (marian, _) = fuzzy_match("ali", "Marian Lind").unwrap();
+        assert!(alice > marian);
+        assert_eq!(fuzzy_match("asm", "Alice Smith").unwrap().1, [0, 6, 7]);
+        assert!(fuzzy_match("xyz", "Alice Smith").is_none());
+    }
+
+    #[test]
+    fn test_matches_name_email_and_filters() {
+        let ada = employee("emp")
+            .with_name("Ada", "Lovelace")
+            .with_email("countess@example.com")
+            .with_role("Admin");
+
+        let hit = EmployeeQuery::parse("lovel")
+            .matches(&ada, None, None)
+            .unwrap();
+        assert_eq!(hit.name, [4, 5, 6, 7, 8]);
+
+        let hit = EmployeeQuery::parse("countess")
+            .matches(&ada, None, None)
+            .unwrap();
+        assert_eq!(hit.email, (0..8).collect::<Vec<_>>());
+
+        let query = EmployeeQuery::parse("dept:sales role:adm grade:e5");
+        let hit = query.matches(&ada, Some("Sales EMEA"), Some("E5")).unwrap();
+        assert_eq!(hit.department, [0, 1, 2, 3, 4]);
+        assert_eq!(hit.role, [0, 1, 2]);
+        assert_eq!(hit.grade, [0, 1]);
+        assert!(query.matches(&ada, Some("Finance"), Some("E5")).is_none());
+        assert!(query.matches(&ada, None, Some("E5")).is_none());
+        assert!(query.matches(&ada, Some("Sales"), Some("A1")).is_none());
+
+        assert!(EmployeeQuery::parse("ada zzz")
+            .matches(&ada, None, None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_highlighted_sections() {
+        let colors = Material3Colors::dark();
+        let job = highlighted("Role: ", "Admin", &[0, 1], 13.0, Color32::GRAY, &colors);
+        assert_eq!(job.text, "Role: Admin");
+        let marked: Vec<&str> = job
+            .sections
+            .iter()
+            .filter(|s| s.format.background == colors.primary_container)
+            .map(|s| &job.text[s.byte_range.clone()])
+            .collect();
+        assert_eq!(marked, ["Ad"]);
+    }
+}
@@ -0,0 +1,75 @@
+//! UI preferences that survive a restart
+//!
+//! The settings are stored as JSON at `Config::ui_settings_path`. A missing
+//! or unreadable file falls back to the defaults, and unknown or missing
+//! fields are ignored, so older files keep working after an update.
+
+use super::views::employee_table::TableLayout;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How the Employees tab lists employees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EmployeeView {
+    #[default]
+    Cards,
+    Table,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    pub employee_view: EmployeeView,
+    pub employee_table: TableLayout,
+}
+
+impl UiSettings {
+    /// Read the settings from `path`, using the defaults if that fails
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let mut settings: Self = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        settings.employee_table.normalize();
+        settings
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let text = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::views::employee_table::EmployeeColumn;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let path = std::env::temp_dir().join(format!("ui_settings_{}.json", std::process::id()));
+        let mut settings = UiSettings {
+            employee_view: EmployeeView::Table,
+            ..Default::default()
+        };
+        settings.employee_table.move_column(0, 2);
+        settings
+            .employee_table
+            .toggle_sort(EmployeeColumn::HireDate);
+        settings.save(&path).unwrap();
+
+        assert_eq!(UiSettings::load(&path), settings);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_or_partial_file_uses_defaults() {
+        assert_eq!(
+            UiSettings::load("/nonexistent/ui_settings.json"),
+            UiSettings::default()
+        );
+        let partial: UiSettings = serde_json::from_str(r#"{"employee_view":"Table"}"#).unwrap();
+        assert_eq!(partial.employee_view, EmployeeView::Table);
+        assert_eq!(partial.employee_table, TableLayout::default());
+    }
+}
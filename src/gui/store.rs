@@ -0,0 +1,88 @@
+//! Shared entity collections
+//!
+//! Background tasks replace a collection as a whole, while the UI only reads
+//! it. A `Store` keeps the records behind an `Arc`, so each frame takes a
+//! snapshot by bumping a reference count instead of cloning every record.
+
+use std::sync::{Arc, Mutex};
+
+/// Cloneable handle to one entity collection
+pub struct Store<T> {
+    inner: Arc<Mutex<Arc<Vec<T>>>>,
+}
+
+impl<T> Clone for Store<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Default for Store<T> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Arc::new(Vec::new()))),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T> Store<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current records; later writes do not affect a taken snapshot
+    pub fn snapshot(&self) -> Arc<Vec<T>> {
+        self.inner.lock().unwrap().clone()
+    }
+
+    pub fn replace(&self, items: Vec<T>) {
+        *self.inner.lock().unwrap() = Arc::new(items);
+    }
+
+    pub fn clear(&self) {
+        self.replace(Vec::new());
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone> Store<T> {
+    /// Modify the records in place. Copies them only if a snapshot taken
+    /// earlier is still alive.
+    pub fn update<R>(&self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        let mut inner = self.inner.lock().unwrap();
+        f(Arc::make_mut(&mut inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_is_shared_until_written() {
+        let store = Store::new();
+        store.replace(vec![1, 2, 3]);
+
+        let first = store.snapshot();
+        let second = store.snapshot();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        store.update(|items| items.push(4));
+        assert_eq!(*first, vec![1, 2, 3]);
+        assert_eq!(*store.snapshot(), vec![1, 2, 3, 4]);
+        assert_eq!(store.len(), 4);
+
+        store.clone().clear();
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+//! Dashboard view with headcount and payroll figures
+//!
+//! The figures need every employee, not just the loaded pages, so the tab
+//! fetches the complete list (inactive ones included) each time it is
+//! opened. Payroll is the sum of the base salaries of the current employees'
+//! salary grades. Employees without a department or grade are counted in
+//! their own buckets instead of being dropped. The charts are painted
+//! directly with egui's painter.
+
+use crate::api::models::*;
+use crate::gui::components::material_card;
+use crate::gui::{material_button, Material3Colors, PersonnelApp};
+use egui::{Color32, Pos2, Rect, RichText, Rounding, Sense, Shape, Stroke, Ui, Vec2};
+use std::collections::BTreeMap;
+use std::f32::consts::TAU;
+use std::sync::{Arc, Mutex};
+
+/// Label of the bucket for employees without (a known) department
+pub const NO_DEPARTMENT: &str = "No department";
+
+/// The employee list the dashboard is computed from
+#[derive(Debug, Clone, Default)]
+pub enum DashboardData {
+    /// Fetched again the next time the tab is shown
+    #[default]
+    Stale,
+    Loading,
+    Ready(Vec<Employee>),
+    Failed(String),
+}
+
+pub type SharedDashboard = Arc<Mutex<DashboardData>>;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DepartmentFigures {
+    pub name: String,
+    /// Current employees
+    pub headcount: usize,
+    /// Current employees with a known salary grade
+    pub graded: usize,
+    pub payroll: f64,
+}
+
+impl DepartmentFigures {
+    /// Average base salary of the graded employees
+    pub fn average(&self) -> Option<f64> {
+        (self.graded > 0).then(|| self.payroll / self.graded as f64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DashboardStats {
+    /// Sorted by name, with the `NO_DEPARTMENT` bucket last
+    pub departments: Vec<DepartmentFigures>,
+    pub roles: BTreeMap<String, usize>,
+    pub active: usize,
+    /// Deactivated and deleted employees
+    pub inactive: usize,
+    /// Current employees without a known salary grade
+    pub ungraded: usize,
+    pub payroll: f64,
+}
+
+impl DashboardStats {
+    pub fn new(employees: &[Employee], depts: &[Department], grades: &[SalaryGrade]) -> Self {
+        let mut stats = Self::default();
+        let mut by_dept: BTreeMap<&str, DepartmentFigures> = depts
+            .iter()
+            .map(|d| {
+                let figures = DepartmentFigures {
+                    name: d.name.clone(),
+                    ..Default::default()
+                };
+                (d.id.as_str(), figures)
+            })
+            .collect();
+        let mut unassigned = DepartmentFigures {
+            name: NO_DEPARTMENT.to_string(),
+            ..Default::default()
+        };
+
+        for emp in employees {
+            if !emp.is_current() {
+                stats.inactive += 1;
+                continue;
+            }
+            stats.active += 1;
+            *stats.roles.entry(emp.role.clone()).or_default() += 1;
+            let figures = match emp
+                .department_id
+                .as_deref()
+                .and_then(|id| by_dept.get_mut(id))
+            {
+                Some(figures) => figures,
+                None => &mut unassigned,
+            };
+            figures.headcount += 1;
+            match emp
+                .salary_grade_id
+                .as_ref()
+                .and_then(|id| grades.iter().find(|g| &g.id == id))
+            {
+                Some(grade) => {
+                    figures.graded += 1;
+                    figures.payroll += grade.base_salary;
+                    stats.payroll += grade.base_salary;
+                }
+                None => stats.ungraded += 1,
+            }
+        }
+
+        stats.departments = by_dept.into_values().collect();
+        stats.departments.sort_by(|a, b| a.name.cmp(&b.name));
+        if unassigned.headcount > 0 {
+            stats.departments.push(unassigned);
+        }
+        stats
+    }
+}
+
+/// Colors cycled through for chart series
+fn palette(colors: &Material3Colors) -> [Color32; 6] {
+    [
+        colors.primary,
+        colors.tertiary,
+        colors.secondary,
+        colors.success,
+        colors.warning,
+        colors.error,
+    ]
+}
+
+/// Horizontal bars, one row per `(label, value, caption)`
+fn bar_chart(ui: &mut Ui, colors: &Material3Colors, rows: &[(String, f64, String)], bar: Color32) {
+    const ROW: f32 = 26.0;
+    const LABEL: f32 = 150.0;
+    const CAPTION: f32 = 170.0;
+    let max = rows.iter().map(|r| r.1).fold(0.0, f64::max);
+    let width = ui.available_width().max(LABEL + CAPTION + 40.0);
+    let (rect, _) =
+        ui.allocate_exact_size(Vec2::new(width, ROW * rows.len() as f32), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let track = width - LABEL - CAPTION;
+    for (i, (label, value, caption)) in rows.iter().enumerate() {
+        let top = rect.top() + i as f32 * ROW;
+        let center = top + ROW / 2.0;
+        painter.text(
+            Pos2::new(rect.left(), center),
+            egui::Align2::LEFT_CENTER,
+            label,
+            egui::FontId::proportional(13.0),
+            colors.on_surface,
+        );
+        let length = if max > 0.0 {
+            (value / max) as f32 * track
+        } else {
+            0.0
+        };
+        let bar_rect = Rect::from_min_size(
+            Pos2::new(rect.left() + LABEL, top + 5.0),
+            Vec2::new(length.max(2.0), ROW - 10.0),
+        );
+        painter.rect_filled(bar_rect, Rounding::same(4.0), bar);
+        painter.text(
+            Pos2::new(bar_rect.right() + 8.0, center),
+            egui::Align2::LEFT_CENTER,
+            caption,
+            egui::FontId::proportional(12.0),
+            colors.on_surface_variant,
+        );
+    }
+}
+
+/// Ring split into `slices`, with a legend beside it
+fn donut_chart(ui: &mut Ui, colors: &Material3Colors, slices: &[(String, usize, Color32)]) {
+    const RADIUS: f32 = 70.0;
+    const WIDTH: f32 = 24.0;
+    let total: usize = slices.iter().map(|s| s.1).sum();
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(RADIUS * 2.0 + 8.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let center = rect.center();
+        let point = |angle: f32, radius: f32| center + Vec2::new(angle.cos(), angle.sin()) * radius;
+        if total == 0 {
+            painter.circle_stroke(
+                center,
+                RADIUS - WIDTH / 2.0,
+                Stroke::new(WIDTH, colors.outline_variant),
+            );
+        }
+        // Each slice is drawn as small quads, which are convex unlike the slice
+        let mut start = -TAU / 4.0;
+        for (_, count, color) in slices {
+            let sweep = *count as f32 / total.max(1) as f32 * TAU;
+            let steps = (sweep / 0.05).ceil().max(1.0) as usize;
+            for step in 0..steps {
+                let a = start + sweep * step as f32 / steps as f32;
+                let b = start + sweep * (step + 1) as f32 / steps as f32;
+                painter.add(Shape::convex_polygon(
+                    vec![
+                        point(a, RADIUS),
+                        point(b, RADIUS),
+                        point(b, RADIUS - WIDTH),
+                        point(a, RADIUS - WIDTH),
+                    ],
+                    *color,
+                    Stroke::NONE,
+                ));
+            }
+            start += sweep;
+        }
+        painter.text(
+            center,
+            egui::Align2::CENTER_CENTER,
+            total.to_string(),
+            egui::FontId::proportional(20.0),
+            colors.on_surface,
+        );
+
+        ui.add_space(16.0);
+        ui.vertical(|ui| {
+            for (label, count, color) in slices {
+                ui.horizontal(|ui| {
+                    let (swatch, _) = ui.allocate_exact_size(Vec2::splat(12.0), Sense::hover());
+                    ui.painter()
+                        .rect_filled(swatch, Rounding::same(3.0), *color);
+                    let share = *count as f64 * 100.0 / total.max(1) as f64;
+                    ui.label(
+                        RichText::new(format!("{}: {} ({:.0}%)", label, count, share))
+                            .size(13.0)
+                            .color(colors.on_surface),
+                    );
+                });
+            }
+        });
+    });
+}
+
+impl PersonnelApp {
+    /// Fetch the complete employee list the dashboard is computed from
+    pub fn refresh_dashboard(&mut self) {
+        *self.dashboard.lock().unwrap() = DashboardData::Loading;
+        let api = self.api.clone();
+        let slot = self.dashboard.clone();
+        self.spawn(async move {
+            let data = match api.get_employees(true).await {
+                Ok(list) => DashboardData::Ready(list),
+                Err(e) => DashboardData::Failed(e.to_string()),
+            };
+            *slot.lock().unwrap() = data;
+        });
+    }
+
+    pub fn show_dashboard(&mut self, ui: &mut Ui) {
+        let colors = self.colors;
+        let data = self.dashboard.lock().unwrap().clone();
+
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Dashboard")
+                    .size(28.0)
+                    .color(colors.on_surface)
+                    .strong(),
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let refresh = ui.add_enabled_ui(!matches!(data, DashboardData::Loading), |ui| {
+                    material_button(ui, &colors, "↻ Refresh", false)
+                });
+                if refresh.inner.clicked() {
+                    self.refresh_dashboard();
+                }
+            });
+        });
+        ui.add_space(20.0);
+
+        let employees = match data {
+            DashboardData::Stale => {
+                self.refresh_dashboard();
+                return;
+            }
+            DashboardData::Loading => {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new().color(colors.primary));
+                    ui.label(RichText::new("Loading employees…").color(colors.on_surface_variant));
+                });
+                return;
+            }
+            DashboardData::Failed(e) => {
+                ui.label(
+                    RichText::new(format!("Could not load the employees: {}", e))
+                        .color(colors.error),
+                );
+                return;
+            }
+            DashboardData::Ready(list) => match &self.draft {
+                Some(draft) => draft.employees(&list),
+                None => list,
+            },
+        };
+        let stats = DashboardStats::new(
+            &employees,
+            &self.view_departments(),
+            &self.view_salary_grades(),
+        );
+        let palette = palette(&colors);
+
+        material_card(ui, &colors, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                let figure = |ui: &mut Ui, label: &str, value: String| {
+                    ui.vertical(|ui| {
+                        ui.label(
+                            RichText::new(value)
+                                .size(22.0)
+                                .strong()
+                                .color(colors.on_surface),
+                        );
+                        ui.label(
+                            RichText::new(label)
+                                .size(12.0)
+                                .color(colors.on_surface_variant),
+                        );
+                    });
+                    ui.add_space(32.0);
+                };
+                figure(ui, "Active employees", stats.active.to_string());
+                figure(ui, "Inactive employees", stats.inactive.to_string());
+                figure(ui, "Total base payroll", format!("${:.2}", stats.payroll));
+                let graded = stats.active - stats.ungraded;
+                let average = if graded > 0 {
+                    format!("${:.2}", stats.payroll / graded as f64)
+                } else {
+                    "—".to_string()
+                };
+                figure(ui, "Average base salary", average);
+                figure(ui, "Without salary grade", stats.ungraded.to_string());
+            });
+        });
+        ui.add_space(12.0);
+
+        let section = |ui: &mut Ui, title: &str| {
+            ui.label(
+                RichText::new(title)
+                    .size(16.0)
+                    .strong()
+                    .color(colors.on_surface),
+            );
+            ui.add_space(8.0);
+        };
+
+        material_card(ui, &colors, |ui| {
+            section(ui, "Headcount per department");
+            let rows: Vec<(String, f64, String)> = stats
+                .departments
+                .iter()
+                .map(|d| (d.name.clone(), d.headcount as f64, d.headcount.to_string()))
+                .collect();
+            bar_chart(ui, &colors, &rows, colors.primary);
+        });
+        ui.add_space(12.0);
+
+        material_card(ui, &colors, |ui| {
+            section(ui, "Base payroll per department");
+            let rows: Vec<(String, f64, String)> = stats
+                .departments
+                .iter()
+                .map(|d| {
+                    let average = d
+                        .average()
+                        .map_or_else(|| "—".to_string(), |a| format!("${:.2}", a));
+                    let caption = format!("${:.2} (avg {})", d.payroll, average);
+                    (d.name.clone(), d.payroll, caption)
+                })
+                .collect();
+            bar_chart(ui, &colors, &rows, colors.tertiary);
+        });
+        ui.add_space(12.0);
+
+        ui.horizontal_wrapped(|ui| {
+            material_card(ui, &colors, |ui| {
+                section(ui, "Headcount per role");
+                let slices: Vec<(String, usize, Color32)> = stats
+                    .roles
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (role, n))| (role.clone(), *n, palette[i % palette.len()]))
+                    .collect();
+                donut_chart(ui, &colors, &slices);
+            });
+            material_card(ui, &colors, |ui| {
+                section(ui, "Active vs inactive");
+                let slices = [
+                    ("Active".to_string(), stats.active, colors.success),
+                    ("Inactive".to_string(), stats.inactive, colors.outline),
+                ];
+                donut_chart(ui, &colors, &slices);
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::{department, employee, grade};
+
+    #[test]
+    fn test_stats_join_grades_and_keep_unassigned() {
+        let depts = vec![department("d2", "Sales"), department("d1", "IT")];
+        let grades = vec![grade("g1", "G1", 3000.0), grade("g2", "G2", 5000.0)];
+        let gone = employee("x")
+            .with_department("d1")
+            .with_grade("g2")
+            .with_active(false);
+        let emps = vec![
+            employee("a").with_department("d1").with_grade("g1"),
+            employee("b")
+                .with_department("d1")
+                .with_grade("g2")
+                .with_role("DepartmentHead"),
+            employee("c").with_department("d1"),
+            employee("d").with_grade("g1"),
+            employee("e")
+                .with_department("gone")
+                .with_grade("missing")
+                .with_role("Admin"),
+            gone,
+        ];
+        let stats = DashboardStats::new(&emps, &depts, &grades);
+
+        assert_eq!(stats.active, 5);
+        assert_eq!(stats.inactive, 1);
+        assert_eq!(stats.ungraded, 2);
+        assert_eq!(stats.payroll, 11000.0);
+        assert_eq!(stats.roles["Employee"], 3);
+
+        let names: Vec<&str> = stats.departments.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["IT", "Sales", NO_DEPARTMENT]);
+        let it = &stats.departments[0];
+        assert_eq!((it.headcount, it.graded, it.payroll), (3, 2, 8000.0));
+        assert_eq!(it.average(), Some(4000.0));
+        assert_eq!(stats.departments[1].average(), None);
+        assert_eq!(stats.departments[2].headcount, 2);
+        assert_eq!(stats.departments[2].payroll, 3000.0);
+    }
+
+    #[test]
+    fn test_no_department_bucket_only_when_needed() {
+        let depts = vec![department("d1", "IT")];
+        let emps = vec![employee("a").with_department("d1")];
+        let stats = DashboardStats::new(&emps, &depts, &[]);
+        assert_eq!(stats.departments.len(), 1);
+        assert_eq!(stats.ungraded, 1);
+    }
+}
@@ -0,0 +1,397 @@
+//! Detail page of a single department, opened from its card
+//!
+//! Members are fetched with `get_employees_by_department`, so the page is
+//! complete even while the employee list has pages that are not loaded yet.
+//! Employees the app already knows about (loaded, or changed in a draft)
+//! take precedence over the fetched copy, which keeps the page current after
+//! a move without fetching again.
+
+use crate::api::models::*;
+use crate::gui::bulk::{BulkAction, BulkTarget};
+use crate::gui::components::styled_dropdown;
+use crate::gui::drafts::is_draft_id;
+use crate::gui::{material_button, ConfirmAction, EditDialog, PersonnelApp};
+use egui::{Frame, Margin, RichText, Rounding, Sense, Ui};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Fetched members of the open department; `None` while loading
+pub type MembersSlot = Arc<Mutex<Option<Result<Vec<Employee>, String>>>>;
+
+/// Members of `dept_id`: the fetched ones, updated or dropped by what is
+/// known locally, plus known employees who moved in since the fetch
+pub fn merge_members(fetched: &[Employee], known: &[Employee], dept_id: &str) -> Vec<Employee> {
+    let in_dept = |e: &Employee| e.department_id.as_deref() == Some(dept_id);
+    let mut seen = HashSet::new();
+    let mut members: Vec<Employee> = fetched
+        .iter()
+        .filter_map(|f| {
+            seen.insert(f.id.as_str());
+            match known.iter().find(|k| k.id == f.id) {
+                Some(k) => Some(k.clone()).filter(in_dept),
+                None => Some(f.clone()),
+            }
+        })
+        .collect();
+    members.extend(
+        known
+            .iter()
+            .filter(|k| in_dept(k) && !seen.contains(k.id.as_str()))
+            .cloned(),
+    );
+    members.sort_by(|a, b| (&a.last_name, &a.first_name).cmp(&(&b.last_name, &b.first_name)));
+    members
+}
+
+/// Figures shown at the top of the detail page
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DepartmentSummary {
+    /// Current members only
+    pub headcount: usize,
+    pub by_role: BTreeMap<String, usize>,
+    /// Sum of the base salaries of current members with a grade
+    pub payroll: f64,
+    /// Current members without a salary grade
+    pub ungraded: usize,
+}
+
+impl DepartmentSummary {
+    pub fn new(members: &[Employee], grades: &[SalaryGrade]) -> Self {
+        let mut summary = Self::default();
+        for emp in members.iter().filter(|e| e.is_current()) {
+            summary.headcount += 1;
+            *summary.by_role.entry(emp.role.clone()).or_default() += 1;
+            match emp
+                .salary_grade_id
+                .as_ref()
+                .and_then(|id| grades.iter().find(|g| &g.id == id))
+            {
+                Some(grade) => summary.payroll += grade.base_salary,
+                None => summary.ungraded += 1,
+            }
+        }
+        summary
+    }
+}
+
+impl PersonnelApp {
+    /// Open the detail page of `dept` and fetch its members
+    pub fn open_department_detail(&mut self, dept: &Department) {
+        self.dept_detail = Some(dept.id.clone());
+        self.refresh_department_detail();
+    }
+
+    fn refresh_department_detail(&mut self) {
+        let Some(id) = self.dept_detail.clone() else {
+            return;
+        };
+        *self.dept_detail_members.lock().unwrap() = None;
+        if is_draft_id(&id) {
+            // Only exists in the draft, so the server has no members for it
+            *self.dept_detail_members.lock().unwrap() = Some(Ok(Vec::new()));
+            return;
+        }
+        let api = self.api.clone();
+        let slot = self.dept_detail_members.clone();
+        self.spawn(async move {
+            let result = api
+                .get_employees_by_department(&id)
+                .await
+                .map_err(|e| e.to_string());
+            *slot.lock().unwrap() = Some(result);
+        });
+    }
+
+    pub fn show_department_detail(&mut self, ui: &mut Ui, dept_id: &str) {
+        let colors = self.colors;
+        let depts = self.view_departments();
+        let Some(dept) = depts.iter().find(|d| d.id == dept_id).cloned() else {
+            // Deleted meanwhile
+            self.dept_detail = None;
+            return;
+        };
+        let known = self.view_employees();
+        let grades = self.view_salary_grades();
+        let perms = self.permissions();
+        let fetched = self.dept_detail_members.lock().unwrap().clone();
+
+        ui.horizontal(|ui| {
+            if material_button(ui, &colors, "← Back", false).clicked() {
+                self.dept_detail = None;
+            }
+            ui.add_space(12.0);
+            ui.label(
+                RichText::new(format!("🏢 {}", dept.name))
+                    .size(28.0)
+                    .color(colors.on_surface)
+                    .strong(),
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let refresh = ui.add_enabled_ui(fetched.is_some(), |ui| {
+                    material_button(ui, &colors, "↻ Refresh", false)
+                });
+                if refresh.inner.clicked() {
+                    self.refresh_department_detail();
+                }
+                if perms.can_create_employees() && perms.can_assign_department(Some(&dept.id)) {
+                    ui.add_space(8.0);
+                    let add = ui.add_enabled_ui(!self.emp_status.is_mutating(), |ui| {
+                        material_button(ui, &colors, "+ Add member", true)
+                    });
+                    if add.inner.clicked() {
+                        self.clear_emp_form();
+                        self.emp_dept_id = dept.id.clone();
+                        self.edit_dialog = Some(EditDialog::CreateEmployee);
+                    }
+                }
+            });
+        });
+        ui.add_space(16.0);
+
+        let members = match fetched {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new().color(colors.primary));
+                    ui.label(RichText::new("Loading members…").color(colors.on_surface_variant));
+                });
+                return;
+            }
+            Some(Err(e)) => {
+                ui.label(
+                    RichText::new(format!("Could not load the members: {}", e)).color(colors.error),
+                );
+                return;
+            }
+            Some(Ok(fetched)) => merge_members(&fetched, &known, &dept.id),
+        };
+        let summary = DepartmentSummary::new(&members, &grades);
+        let full_name = |e: &Employee| format!("{} {}", e.first_name, e.last_name);
+        let head = dept
+            .head_id
+            .as_ref()
+            .and_then(|id| members.iter().chain(known.iter()).find(|e| &e.id == id))
+            .map(full_name)
+            .unwrap_or_else(|| "No head assigned".to_string());
+        let deputies: Vec<String> = members
+            .iter()
+            .filter(|e| e.is_current() && e.role == "DeputyHead")
+            .map(full_name)
+            .collect();
+
+        Frame::none()
+            .fill(colors.surface_variant)
+            .rounding(Rounding::same(12.0))
+            .inner_margin(Margin::same(16.0))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+                let line = |ui: &mut Ui, text: String| {
+                    ui.label(RichText::new(text).size(14.0).color(colors.on_surface));
+                };
+                line(ui, format!("👔 Head: {}", head));
+                let deputy = if deputies.is_empty() {
+                    "None".to_string()
+                } else {
+                    deputies.join(", ")
+                };
+                line(ui, format!("🤝 Deputy: {}", deputy));
+                let roles: Vec<String> = summary
+                    .by_role
+                    .iter()
+                    .map(|(role, n)| format!("{} {}", n, role))
+                    .collect();
+                line(
+                    ui,
+                    format!(
+                        "👥 {} current member(s){}",
+                        summary.headcount,
+                        if roles.is_empty() {
+                            String::new()
+                        } else {
+                            format!(": {}", roles.join(", "))
+                        }
+                    ),
+                );
+                let mut payroll = format!("💰 Base payroll: ${:.2}", summary.payroll);
+                if summary.ungraded > 0 {
+                    payroll.push_str(&format!(" ({} without salary grade)", summary.ungraded));
+                }
+                line(ui, payroll);
+            });
+        ui.add_space(16.0);
+
+        // Move someone in from another department
+        let member_ids: HashSet<&str> = members.iter().map(|e| e.id.as_str()).collect();
+        let move_in = BulkAction::MoveToDepartment {
+            id: dept.id.clone(),
+            name: dept.name.clone(),
+        };
+        let candidates: Vec<&Employee> = known
+            .iter()
+            .filter(|e| e.is_current() && !member_ids.contains(e.id.as_str()))
+            .filter(|e| move_in.check(&perms, e, &known).is_ok())
+            .collect();
+        let busy = self.emp_status.is_mutating();
+        let mut confirm = None;
+        if !candidates.is_empty() && !busy {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Move in:").color(colors.on_surface_variant));
+                styled_dropdown(ui, &colors, |ui| {
+                    egui::ComboBox::from_id_salt("dept_detail_move_in")
+                        .selected_text("Choose an employee…")
+                        .width(280.0)
+                        .height(300.0)
+                        .show_ui(ui, |ui| {
+                            for emp in &candidates {
+                                if ui.selectable_label(false, full_name(emp)).clicked() {
+                                    confirm = Some((move_in.clone(), (*emp).clone()));
+                                }
+                            }
+                        })
+                });
+            });
+            ui.add_space(12.0);
+        }
+
+        ui.label(
+            RichText::new("Members")
+                .size(16.0)
+                .color(colors.on_surface_variant),
+        );
+        if members.is_empty() {
+            ui.label(RichText::new("Nobody works here yet").color(colors.on_surface_variant));
+        }
+        let mut open = None;
+        for emp in &members {
+            Frame::none()
+                .fill(colors.surface_variant)
+                .rounding(Rounding::same(8.0))
+                .inner_margin(Margin::symmetric(16.0, 10.0))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let text = if emp.is_current() {
+                            colors.on_surface
+                        } else {
+                            colors.on_surface_variant
+                        };
+                        let grade = emp
+                            .salary_grade_id
+                            .as_ref()
+                            .and_then(|id| grades.iter().find(|g| &g.id == id))
+                            .map(|g| format!(" • {}", g.code))
+                            .unwrap_or_default();
+                        let status = if emp.is_current() { "" } else { " (inactive)" };
+                        let can_edit = perms.can_edit_employee(emp.department_id.as_deref());
+                        let name = ui.add(
+                            egui::Label::new(
+                                RichText::new(full_name(emp))
+                                    .size(14.0)
+                                    .strong()
+                                    .color(text),
+                            )
+                            .sense(Sense::click()),
+                        );
+                        if can_edit && name.on_hover_text("Click to edit").clicked() {
+                            open = Some(emp.clone());
+                        }
+                        ui.label(
+                            RichText::new(format!("{}{}{}", emp.role, grade, status))
+                                .size(12.0)
+                                .color(colors.on_surface_variant),
+                        );
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if busy || !emp.is_current() {
+                                return;
+                            }
+                            let targets: Vec<&Department> = depts
+                                .iter()
+                                .filter(|d| d.id != dept.id)
+                                .filter(|d| perms.can_assign_department(Some(&d.id)))
+                                .collect();
+                            if targets.is_empty() || !can_edit {
+                                return;
+                            }
+                            egui::ComboBox::from_id_salt(("dept_detail_move", &emp.id))
+                                .selected_text("Move to…")
+                                .width(160.0)
+                                .show_ui(ui, |ui| {
+                                    for target in targets {
+                                        if ui.selectable_label(false, &target.name).clicked() {
+                                            let action = BulkAction::MoveToDepartment {
+                                                id: target.id.clone(),
+                                                name: target.name.clone(),
+                                            };
+                                            confirm = Some((action, emp.clone()));
+                                        }
+                                    }
+                                });
+                        });
+                    });
+                });
+        }
+
+        if let Some(emp) = open {
+            self.open_employee_editor(&emp);
+        }
+        if let Some((action, emp)) = confirm {
+            match action.check(&perms, &emp, &known) {
+                Ok(()) => {
+                    self.confirm_dialog = Some(ConfirmAction::BulkUpdateEmployees {
+                        action,
+                        targets: vec![BulkTarget::new(&emp)],
+                    });
+                }
+                Err(reason) => self.notifier.warning(reason),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::{employee, grade};
+
+    #[test]
+    fn test_merge_members_prefers_known_records() {
+        let fetched = vec![
+            employee("a").with_department("d1"),
+            employee("b").with_department("d1"),
+            employee("c").with_department("d1"),
+        ];
+        let known = vec![
+            employee("a").with_department("d1").with_role("DeputyHead"),
+            employee("b").with_department("d2"),
+            employee("x").with_department("d1"),
+            employee("y").with_department("d2"),
+        ];
+        let members = merge_members(&fetched, &known, "d1");
+        let ids: Vec<&str> = members.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["a", "c", "x"]);
+        assert_eq!(members[0].role, "DeputyHead");
+    }
+
+    #[test]
+    fn test_summary_counts_current_members() {
+        let grades = vec![grade("g1", "E1", 3000.0)];
+        let gone = employee("c")
+            .with_department("d1")
+            .with_grade("g1")
+            .with_active(false);
+        let members = vec![
+            employee("a").with_department("d1").with_grade("g1"),
+            employee("b")
+                .with_department("d1")
+                .with_role("DeputyHead")
+                .with_grade("g1"),
+            employee("d").with_department("d1"),
+            gone,
+        ];
+        let summary = DepartmentSummary::new(&members, &grades);
+        assert_eq!(summary.headcount, 3);
+        assert_eq!(summary.by_role["Employee"], 2);
+        assert_eq!(summary.by_role["DeputyHead"], 1);
+        assert_eq!(summary.payroll, 6000.0);
+        assert_eq!(summary.ungraded, 1);
+    }
+}
@@ -1,13 +1,18 @@
 //! Departments view for the Departments tab
 
 use crate::api::models::*;
-use crate::gui::{material_button, ConfirmAction, EditDialog, Material3Colors, PersonnelApp};
+use crate::gui::components::{load_status_caption, skeleton_card, virtual_list};
+use crate::gui::{material_button, EditDialog, Material3Colors, PersonnelApp};
 use egui::{Button, Color32, Frame, Margin, RichText, Rounding, Stroke, Ui, Vec2};
 
 impl PersonnelApp {
     pub fn show_departments(&mut self, ui: &mut Ui) {
+        if let Some(id) = self.dept_detail.clone() {
+            self.show_department_detail(ui, &id);
+            return;
+        }
         let colors = self.colors;
-        let emps = self.employees.lock().unwrap().clone();
+        let emps = self.view_employees();
 
         ui.horizontal(|ui| {
             ui.label(
@@ -16,15 +21,25 @@ impl PersonnelApp {
                     .color(colors.on_surface)
                     .strong(),
             );
+            ui.add_space(12.0);
+            load_status_caption(ui, &colors, &self.dept_status);
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if material_button(ui, &colors, "↻ Refresh", false).clicked() {
+                let refresh = ui.add_enabled_ui(!self.dept_status.is_loading(), |ui| {
+                    material_button(ui, &colors, "↻ Refresh", false)
+                });
+                if refresh.inner.clicked() {
                     self.refresh_departments();
                 }
-                ui.add_space(8.0);
-                if material_button(ui, &colors, "+ Create", true).clicked() {
-                    self.dept_name.clear();
-                    self.dept_head_id.clear();
-                    self.edit_dialog = Some(EditDialog::CreateDepartment);
+                if self.permissions().can_manage_departments() {
+                    ui.add_space(8.0);
+                    let create = ui.add_enabled_ui(!self.dept_status.is_mutating(), |ui| {
+                        material_button(ui, &colors, "+ Create", true)
+                    });
+                    if create.inner.clicked() {
+                        self.dept_name.clear();
+                        self.dept_head_id.clear();
+                        self.edit_dialog = Some(EditDialog::CreateDepartment);
+                    }
                 }
             });
         });
@@ -37,9 +52,13 @@ impl PersonnelApp {
         );
         ui.add_space(12.0);
 
-        let depts = self.departments.lock().unwrap().clone();
+        let depts = self.view_departments();
 
-        if depts.is_empty() {
+        if depts.is_empty() && self.dept_status.is_loading() {
+            for _ in 0..3 {
+                skeleton_card(ui, &colors);
+            }
+        } else if depts.is_empty() {
             Frame::none()
                 .fill(colors.surface_variant)
                 .rounding(Rounding::same(12.0))
@@ -53,13 +72,14 @@ impl PersonnelApp {
                 });
         }
 
-        for dept in depts.iter() {
+        virtual_list(ui, "department_cards", depts.len(), 96.0, |ui, i| {
+            let dept = &depts[i];
             let emp_count = emps
                 .iter()
                 .filter(|e| e.department_id.as_ref() == Some(&dept.id))
                 .count();
             self.render_dept_card(ui, &colors, dept, &emps, emp_count);
-        }
+        });
     }
 
     fn render_dept_card(
@@ -70,7 +90,7 @@ impl PersonnelApp {
         emps: &[Employee],
         emp_count: usize,
     ) {
-        Frame::none()
+        let card = Frame::none()
             .fill(colors.surface_variant)
             .stroke(Stroke::NONE)
             .rounding(Rounding::same(12.0))
@@ -115,18 +135,18 @@ impl PersonnelApp {
                     });
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if !self.permissions().can_manage_departments() {
+                            return;
+                        }
                         let delete_btn =
                             Button::new(RichText::new("🗑 Delete").size(12.0).color(Color32::WHITE))
                                 .fill(Color32::from_rgb(220, 53, 69))
                                 .stroke(Stroke::NONE)
                                 .rounding(Rounding::same(8.0))
                                 .min_size(Vec2::new(75.0, 36.0));
-                        if ui.add(delete_btn).clicked() {
-                            self.confirm_dialog = Some(ConfirmAction::DeleteDepartment {
-                                id: dept.id.clone(),
-                                name: dept.name.clone(),
-                                employee_count: emp_count,
-                            });
+                        let busy = self.dept_status.is_mutating();
+                        if ui.add_enabled(!busy, delete_btn).clicked() {
+                            self.confirm_department_delete(dept);
                         }
                         ui.add_space(8.0);
                         let edit_btn = Button::new(
@@ -136,16 +156,21 @@ impl PersonnelApp {
                         .stroke(Stroke::NONE)
                         .rounding(Rounding::same(8.0))
                         .min_size(Vec2::new(65.0, 36.0));
-                        if ui.add(edit_btn).clicked() {
+                        if ui.add_enabled(!busy, edit_btn).clicked() {
                             self.dept_name = dept.name.clone();
                             self.dept_head_id = dept.head_id.clone().unwrap_or_default();
                             self.edit_dialog = Some(EditDialog::EditDepartment {
                                 id: dept.id.clone(),
                                 old_head_id: dept.head_id.clone(),
+                                version: dept.updated_at.clone(),
                             });
                         }
                     });
                 });
             });
+        let card = card.response.interact(egui::Sense::click());
+        if card.on_hover_text("Open details").clicked() {
+            self.open_department_detail(dept);
+        }
     }
 }
@@ -0,0 +1,280 @@
+//! Side panel with the full profile of the selected employee
+//!
+//! Opened by clicking an employee card or table row. Besides every field of
+//! the record it shows the reporting line up to the top and the direct
+//! reports; both link to the respective employee, so the hierarchy can be
+//! walked within the panel.
+
+use super::employees::employee_status;
+use super::org_chart::ancestors;
+use crate::api::models::*;
+use crate::gui::{material_button, PersonnelApp, Tab};
+use egui::{Context, Frame, Margin, RichText, Sense, Stroke, Ui};
+
+/// Employees whose `manager_id` is `id`, sorted by name
+pub fn direct_reports<'a>(employees: &'a [Employee], id: &str) -> Vec<&'a Employee> {
+    let mut reports: Vec<&Employee> = employees
+        .iter()
+        .filter(|e| e.manager_id.as_deref() == Some(id) && e.id != id)
+        .collect();
+    reports.sort_by(|a, b| (&a.last_name, &a.first_name).cmp(&(&b.last_name, &b.first_name)));
+    reports
+}
+
+/// `2024-05-01T09:30:00.123Z` as `2024-05-01 09:30:00`
+pub fn format_timestamp(value: &str) -> String {
+    let trimmed = value
+        .split('.')
+        .next()
+        .unwrap_or(value)
+        .trim_end_matches('Z');
+    trimmed.replacen('T', " ", 1)
+}
+
+impl PersonnelApp {
+    /// Open the side panel for `emp`, or close it if it already shows them
+    pub fn toggle_employee_detail(&mut self, emp: &Employee) {
+        if self.selected_emp.as_ref() == Some(&emp.id) {
+            self.selected_emp = None;
+        } else {
+            self.selected_emp = Some(emp.id.clone());
+        }
+    }
+
+    pub fn show_employee_detail(&mut self, ctx: &Context) {
+        let Some(id) = self.selected_emp.clone() else {
+            return;
+        };
+        let emps = self.view_employees();
+        let Some(emp) = emps.iter().find(|e| e.id == id).cloned() else {
+            // Deleted, or no longer loaded after a refresh
+            self.selected_emp = None;
+            return;
+        };
+        let colors = self.colors;
+        let depts = self.view_departments();
+        let grades = self.view_salary_grades();
+        let perms = self.permissions();
+        let full_name = |e: &Employee| format!("{} {}", e.first_name, e.last_name);
+        let mut navigate = None;
+        let mut close = false;
+        let mut edit = false;
+        let mut show_in_chart = false;
+
+        egui::SidePanel::right("employee_detail")
+            .resizable(true)
+            .default_width(340.0)
+            .frame(
+                Frame::none()
+                    .fill(colors.surface_variant)
+                    .stroke(Stroke::new(1.0, colors.outline_variant))
+                    .inner_margin(Margin::same(20.0)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(full_name(&emp))
+                            .size(20.0)
+                            .strong()
+                            .color(colors.on_surface),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").on_hover_text("Close").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+                let (status, color) =
+                    employee_status(&colors, &emp).unwrap_or(("Active", colors.primary));
+                ui.label(RichText::new(status).size(13.0).strong().color(color));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let can_edit = perms.can_edit_employee(emp.department_id.as_deref());
+                    if can_edit
+                        && emp.is_current()
+                        && material_button(ui, &colors, "✏ Edit", true).clicked()
+                    {
+                        edit = true;
+                    }
+                    if material_button(ui, &colors, "🌳 Org chart", false).clicked() {
+                        show_in_chart = true;
+                    }
+                });
+                ui.add_space(12.0);
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        let section = |ui: &mut Ui, title: &str| {
+                            ui.add_space(8.0);
+                            ui.label(
+                                RichText::new(title)
+                                    .size(14.0)
+                                    .strong()
+                                    .color(colors.on_surface),
+                            );
+                            ui.add_space(4.0);
+                        };
+                        let none = || "—".to_string();
+                        let dept = emp
+                            .department_id
+                            .as_ref()
+                            .map(|id| {
+                                depts
+                                    .iter()
+                                    .find(|d| &d.id == id)
+                                    .map_or_else(|| id.clone(), |d| d.name.clone())
+                            })
+                            .unwrap_or_else(none);
+                        let grade = emp
+                            .salary_grade_id
+                            .as_ref()
+                            .map(|id| {
+                                grades.iter().find(|g| &g.id == id).map_or_else(
+                                    || id.clone(),
+                                    |g| format!("{} - ${:.2}", g.code, g.base_salary),
+                                )
+                            })
+                            .unwrap_or_else(none);
+                        let stamp = |v: &Option<String>| {
+                            v.as_deref().map(format_timestamp).unwrap_or_else(none)
+                        };
+
+                        section(ui, "Profile");
+                        egui::Grid::new("employee_detail_fields")
+                            .num_columns(2)
+                            .spacing([12.0, 6.0])
+                            .show(ui, |ui| {
+                                let fields = [
+                                    ("Email", emp.email.clone()),
+                                    ("Role", emp.role.clone()),
+                                    ("Department", dept),
+                                    ("Salary grade", grade),
+                                    ("Active", if emp.active { "Yes" } else { "No" }.to_string()),
+                                    ("Hire date", emp.hire_date.clone().unwrap_or_else(none)),
+                                    ("Leave date", emp.leave_date.clone().unwrap_or_else(none)),
+                                    ("Created", stamp(&emp.created_at)),
+                                    ("Updated", stamp(&emp.updated_at)),
+                                    ("Deleted", stamp(&emp.deleted_at)),
+                                    ("ID", emp.id.clone()),
+                                ];
+                                for (label, value) in fields {
+                                    ui.label(
+                                        RichText::new(label)
+                                            .size(12.0)
+                                            .color(colors.on_surface_variant),
+                                    );
+                                    ui.label(
+                                        RichText::new(value).size(13.0).color(colors.on_surface),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+
+                        let mut link = |ui: &mut Ui, target: &Employee, prefix: &str| {
+                            let text = format!("{}{} · {}", prefix, full_name(target), target.role);
+                            let response = ui.add(
+                                egui::Label::new(
+                                    RichText::new(text).size(13.0).color(colors.primary),
+                                )
+                                .sense(Sense::click()),
+                            );
+                            if response.on_hover_text("Show this employee").clicked() {
+                                navigate = Some(target.id.clone());
+                            }
+                        };
+
+                        section(ui, "Reporting line");
+                        let chain = ancestors(&emps, &emp.id);
+                        if chain.is_empty() {
+                            ui.label(
+                                RichText::new("No manager")
+                                    .size(13.0)
+                                    .color(colors.on_surface_variant),
+                            );
+                        }
+                        for (depth, manager) in chain.iter().enumerate() {
+                            let prefix = format!("{}↑ ", "  ".repeat(depth));
+                            match emps.iter().find(|e| &e.id == manager) {
+                                Some(m) => link(ui, m, &prefix),
+                                None => {
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "{}{} (not loaded)",
+                                            prefix, manager
+                                        ))
+                                        .size(13.0)
+                                        .color(colors.on_surface_variant),
+                                    );
+                                }
+                            }
+                        }
+
+                        let reports = direct_reports(&emps, &emp.id);
+                        section(ui, &format!("Direct reports ({})", reports.len()));
+                        if reports.is_empty() {
+                            ui.label(
+                                RichText::new("None")
+                                    .size(13.0)
+                                    .color(colors.on_surface_variant),
+                            );
+                        }
+                        for report in reports {
+                            link(ui, report, "↓ ");
+                        }
+                    });
+            });
+
+        if close {
+            self.selected_emp = None;
+        } else if let Some(target) = navigate {
+            self.selected_emp = Some(target);
+        }
+        if edit {
+            self.open_employee_editor(&emp);
+        }
+        if show_in_chart {
+            for manager in ancestors(&emps, &emp.id) {
+                self.org_chart.collapsed.remove(&manager);
+            }
+            self.org_chart.focus = Some(emp.id.clone());
+            self.org_chart.center = true;
+            self.current_tab = Tab::OrgChart;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::employee;
+
+    #[test]
+    fn test_direct_reports_sorted_by_name() {
+        let emps = vec![
+            employee("boss").with_name("boss", "A").with_manager("boss"),
+            employee("x").with_name("x", "Zed").with_manager("boss"),
+            employee("y").with_name("y", "Young").with_manager("boss"),
+            employee("z").with_name("z", "Ada").with_manager("x"),
+        ];
+        let ids: Vec<&str> = direct_reports(&emps, "boss")
+            .iter()
+            .map(|e| e.id.as_str())
+            .collect();
+        assert_eq!(ids, ["y", "x"]);
+        assert!(direct_reports(&emps, "z").is_empty());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(
+            format_timestamp("2024-05-01T09:30:00.123Z"),
+            "2024-05-01 09:30:00"
+        );
+        assert_eq!(
+            format_timestamp("2024-05-01T09:30:00Z"),
+            "2024-05-01 09:30:00"
+        );
+        assert_eq!(format_timestamp("2024-05-01"), "2024-05-01");
+    }
+}
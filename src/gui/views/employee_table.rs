@@ -0,0 +1,580 @@
+//! Table mode of the Employees tab
+//!
+//! One row per employee with sortable, resizable and reorderable columns.
+//! Clicking a header cycles ascending, descending and unsorted; dragging a
+//! header onto another moves the column there, and dragging the right edge
+//! of a header resizes it. The layout is part of `UiSettings` and is saved
+//! whenever it changes.
+
+use crate::api::models::*;
+use crate::gui::components::virtual_list;
+use crate::gui::permissions::Role;
+use crate::gui::search::{highlighted, EmployeeMatch};
+use crate::gui::views::employees::employee_status;
+use crate::gui::{Material3Colors, PersonnelApp};
+use egui::{
+    Align, Align2, Button, CursorIcon, FontId, Frame, Label, Layout, Margin, RichText, Rounding,
+    Sense, Stroke, Ui, Vec2, WidgetText,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+const HEADER_HEIGHT: f32 = 32.0;
+const ROW_HEIGHT: f32 = 32.0;
+const MIN_COLUMN_WIDTH: f32 = 60.0;
+const RESIZE_HANDLE_WIDTH: f32 = 6.0;
+const ACTIONS_WIDTH: f32 = 112.0;
+const SELECT_WIDTH: f32 = 32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EmployeeColumn {
+    Name,
+    Email,
+    Role,
+    Department,
+    Manager,
+    Grade,
+    HireDate,
+    Active,
+}
+
+impl EmployeeColumn {
+    pub const ALL: [EmployeeColumn; 8] = [
+        EmployeeColumn::Name,
+        EmployeeColumn::Email,
+        EmployeeColumn::Role,
+        EmployeeColumn::Department,
+        EmployeeColumn::Manager,
+        EmployeeColumn::Grade,
+        EmployeeColumn::HireDate,
+        EmployeeColumn::Active,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            EmployeeColumn::Name => "Name",
+            EmployeeColumn::Email => "Email",
+            EmployeeColumn::Role => "Role",
+            EmployeeColumn::Department => "Department",
+            EmployeeColumn::Manager => "Manager",
+            EmployeeColumn::Grade => "Grade",
+            EmployeeColumn::HireDate => "Hire date",
+            EmployeeColumn::Active => "Active",
+        }
+    }
+
+    fn default_width(&self) -> f32 {
+        match self {
+            EmployeeColumn::Name | EmployeeColumn::Manager => 170.0,
+            EmployeeColumn::Email => 220.0,
+            EmployeeColumn::Department => 150.0,
+            EmployeeColumn::Role => 120.0,
+            EmployeeColumn::Grade | EmployeeColumn::HireDate => 100.0,
+            EmployeeColumn::Active => 80.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColumnLayout {
+    pub column: EmployeeColumn,
+    pub width: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortOrder {
+    pub column: EmployeeColumn,
+    pub ascending: bool,
+}
+
+/// Column order, widths and sort order of the employee table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableLayout {
+    pub columns: Vec<ColumnLayout>,
+    pub sort: Option<SortOrder>,
+}
+
+impl Default for TableLayout {
+    fn default() -> Self {
+        Self {
+            columns: EmployeeColumn::ALL
+                .into_iter()
+                .map(|column| ColumnLayout {
+                    column,
+                    width: column.default_width(),
+                })
+                .collect(),
+            sort: None,
+        }
+    }
+}
+
+impl TableLayout {
+    /// Repair a layout read from disk: drop duplicate columns, append the
+    /// ones it does not know yet and clamp the widths
+    pub fn normalize(&mut self) {
+        let mut seen = Vec::new();
+        self.columns.retain(|c| {
+            let first = !seen.contains(&c.column);
+            seen.push(c.column);
+            first
+        });
+        for column in EmployeeColumn::ALL {
+            if !seen.contains(&column) {
+                self.columns.push(ColumnLayout {
+                    column,
+                    width: column.default_width(),
+                });
+            }
+        }
+        for c in &mut self.columns {
+            if !c.width.is_finite() || c.width < MIN_COLUMN_WIDTH {
+                c.width = c.column.default_width();
+            }
+        }
+    }
+
+    /// Move the column at `from` so that it ends up at index `to`
+    pub fn move_column(&mut self, from: usize, to: usize) {
+        if from < self.columns.len() && to < self.columns.len() && from != to {
+            let column = self.columns.remove(from);
+            self.columns.insert(to, column);
+        }
+    }
+
+    pub fn set_width(&mut self, index: usize, width: f32) {
+        if let Some(c) = self.columns.get_mut(index) {
+            c.width = width.max(MIN_COLUMN_WIDTH);
+        }
+    }
+
+    /// Cycle `column` through ascending, descending and unsorted
+    pub fn toggle_sort(&mut self, column: EmployeeColumn) {
+        self.sort = match self.sort {
+            Some(sort) if sort.column == column && sort.ascending => Some(SortOrder {
+                column,
+                ascending: false,
+            }),
+            Some(sort) if sort.column == column => None,
+            _ => Some(SortOrder {
+                column,
+                ascending: true,
+            }),
+        };
+    }
+
+    /// Sort the row indices into `data.employees`. The sort is stable, so
+    /// rows that compare equal keep their list or search-rank order.
+    pub fn sort_rows<T>(&self, rows: &mut [(usize, T)], data: &TableData) {
+        let Some(sort) = self.sort else {
+            return;
+        };
+        rows.sort_by(|(a, _), (b, _)| {
+            let order = data.compare(sort.column, &data.employees[*a], &data.employees[*b]);
+            if sort.ascending {
+                order
+            } else {
+                order.reverse()
+            }
+        });
+    }
+}
+
+/// The collections a table row looks up names in
+pub struct TableData<'a> {
+    pub employees: &'a [Employee],
+    pub departments: &'a [Department],
+    pub salary_grades: &'a [SalaryGrade],
+}
+
+impl TableData<'_> {
+    fn department(&self, emp: &Employee) -> Option<&Department> {
+        let id = emp.department_id.as_ref()?;
+        self.departments.iter().find(|d| &d.id == id)
+    }
+
+    fn grade(&self, emp: &Employee) -> Option<&SalaryGrade> {
+        let id = emp.salary_grade_id.as_ref()?;
+        self.salary_grades.iter().find(|g| &g.id == id)
+    }
+
+    fn manager(&self, emp: &Employee) -> Option<&Employee> {
+        let id = emp.manager_id.as_ref()?;
+        self.employees.iter().find(|e| &e.id == id)
+    }
+
+    /// Text shown in the cell of `column`
+    pub fn cell_text(&self, column: EmployeeColumn, emp: &Employee) -> String {
+        match column {
+            EmployeeColumn::Name => format!("{} {}", emp.first_name, emp.last_name),
+            EmployeeColumn::Email => emp.email.clone(),
+            EmployeeColumn::Role => emp.role.clone(),
+            EmployeeColumn::Department => self
+                .department(emp)
+                .map(|d| d.name.clone())
+                .unwrap_or_else(|| "—".to_string()),
+            EmployeeColumn::Manager => match (&emp.manager_id, self.manager(emp)) {
+                (None, _) => "—".to_string(),
+                (Some(_), Some(m)) => format!("{} {}", m.first_name, m.last_name),
+                (Some(_), None) => "Unknown".to_string(),
+            },
+            EmployeeColumn::Grade => self
+                .grade(emp)
+                .map(|g| g.code.clone())
+                .unwrap_or_else(|| "—".to_string()),
+            EmployeeColumn::HireDate => emp.hire_date.clone().unwrap_or_else(|| "—".to_string()),
+            EmployeeColumn::Active if emp.deleted_at.is_some() => "Deleted".to_string(),
+            EmployeeColumn::Active => if emp.active { "Yes" } else { "No" }.to_string(),
+        }
+    }
+
+    /// Compare two employees by `column`. Grades sort by base salary and
+    /// empty cells sort last in ascending order.
+    fn compare(&self, column: EmployeeColumn, a: &Employee, b: &Employee) -> Ordering {
+        match column {
+            EmployeeColumn::Grade => {
+                let salary = |e| self.grade(e).map(|g| g.base_salary);
+                match (salary(a), salary(b)) {
+                    (Some(x), Some(y)) => x.total_cmp(&y),
+                    (x, y) => x.is_none().cmp(&y.is_none()),
+                }
+            }
+            EmployeeColumn::Active => (b.is_current(), b.active).cmp(&(a.is_current(), a.active)),
+            _ => {
+                let key = |e| {
+                    let text = self.cell_text(column, e);
+                    (text == "—", text.to_lowercase())
+                };
+                key(a).cmp(&key(b))
+            }
+        }
+    }
+}
+
+impl PersonnelApp {
+    /// Show `rows` (indices into `data.employees` with optional search hits)
+    /// as a table, sorted by the saved sort order
+    pub(crate) fn show_employee_table(
+        &mut self,
+        ui: &mut Ui,
+        colors: &Material3Colors,
+        data: &TableData,
+        mut rows: Vec<(usize, Option<&EmployeeMatch>)>,
+    ) {
+        self.ui_settings.employee_table.sort_rows(&mut rows, data);
+        let layout = self.ui_settings.employee_table.clone();
+        let width: f32 =
+            layout.columns.iter().map(|c| c.width).sum::<f32>() + SELECT_WIDTH + ACTIONS_WIDTH;
+
+        egui::ScrollArea::horizontal()
+            .id_salt("employee_table")
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                ui.set_min_width(width);
+                ui.spacing_mut().item_spacing.y = 0.0;
+                self.show_table_header(ui, colors, &layout);
+                ui.add(egui::Separator::default().spacing(0.0));
+
+                virtual_list(ui, "employee_rows", rows.len(), ROW_HEIGHT, |ui, i| {
+                    let (index, hits) = rows[i];
+                    let emp = &data.employees[index];
+                    let fill = if self.selected_emp.as_ref() == Some(&emp.id) {
+                        colors.primary_container
+                    } else if i % 2 == 1 {
+                        colors.surface_variant
+                    } else {
+                        egui::Color32::TRANSPARENT
+                    };
+                    let row = Frame::none().fill(fill).show(ui, |ui| {
+                        ui.set_min_width(width);
+                        ui.horizontal(|ui| {
+                            ui.spacing_mut().item_spacing.x = 0.0;
+                            ui.allocate_ui_with_layout(
+                                Vec2::new(SELECT_WIDTH, ROW_HEIGHT),
+                                Layout::left_to_right(Align::Center),
+                                |ui| {
+                                    ui.add_space(8.0);
+                                    self.selection_checkbox(ui, emp);
+                                },
+                            );
+                            let text_color = match employee_status(colors, emp) {
+                                Some(_) => colors.on_surface_variant,
+                                None => colors.on_surface,
+                            };
+                            for c in &layout.columns {
+                                let text = data.cell_text(c.column, emp);
+                                let hits = hits.map(|m| column_hits(c.column, m)).unwrap_or(&[]);
+                                let job = highlighted("", &text, hits, 13.0, text_color, colors);
+                                table_cell(ui, c.width, job.into());
+                            }
+                            self.show_row_actions(ui, colors, emp);
+                        });
+                    });
+                    if row.response.interact(Sense::click()).clicked() {
+                        self.toggle_employee_detail(emp);
+                    }
+                });
+            });
+    }
+
+    fn show_table_header(&mut self, ui: &mut Ui, colors: &Material3Colors, layout: &TableLayout) {
+        let id = ui.id().with("employee_table_header");
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            ui.add_space(SELECT_WIDTH);
+            for (index, c) in layout.columns.iter().enumerate() {
+                let (rect, _) =
+                    ui.allocate_exact_size(Vec2::new(c.width, HEADER_HEIGHT), Sense::hover());
+                let header =
+                    ui.interact(rect, id.with(("move", c.column)), Sense::click_and_drag());
+
+                let arrow = match layout.sort {
+                    Some(sort) if sort.column == c.column && sort.ascending => " ▲",
+                    Some(sort) if sort.column == c.column => " ▼",
+                    _ => "",
+                };
+                let title = format!("{}{}", c.column.title(), arrow);
+                let text_color = if header.hovered() {
+                    colors.on_surface
+                } else {
+                    colors.on_surface_variant
+                };
+                let painter = ui.painter_at(rect.shrink2(Vec2::new(4.0, 0.0)));
+                painter.text(
+                    rect.left_center() + Vec2::new(8.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    title,
+                    FontId::proportional(13.0),
+                    text_color,
+                );
+
+                if header.clicked() {
+                    self.ui_settings.employee_table.toggle_sort(c.column);
+                    self.settings_dirty = true;
+                }
+                if header.drag_started() {
+                    header.dnd_set_drag_payload(index);
+                }
+                if header.dragged() {
+                    ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+                }
+                if header
+                    .dnd_hover_payload::<usize>()
+                    .is_some_and(|from| *from != index)
+                {
+                    ui.painter().vline(
+                        rect.left(),
+                        rect.y_range(),
+                        Stroke::new(2.0, colors.primary),
+                    );
+                }
+                if let Some(from) = header.dnd_release_payload::<usize>() {
+                    self.ui_settings.employee_table.move_column(*from, index);
+                    self.settings_dirty = true;
+                }
+
+                let handle_rect = egui::Rect::from_min_max(
+                    egui::pos2(rect.right() - RESIZE_HANDLE_WIDTH, rect.top()),
+                    rect.right_bottom(),
+                );
+                let handle = ui.interact(handle_rect, id.with(("resize", c.column)), Sense::drag());
+                let handle_color = if handle.hovered() || handle.dragged() {
+                    ui.ctx().set_cursor_icon(CursorIcon::ResizeColumn);
+                    colors.primary
+                } else {
+                    colors.outline_variant
+                };
+                ui.painter().vline(
+                    rect.right() - 1.0,
+                    rect.shrink(8.0).y_range(),
+                    Stroke::new(1.0, handle_color),
+                );
+                if handle.dragged() {
+                    let width = self.ui_settings.employee_table.columns[index].width;
+                    self.ui_settings
+                        .employee_table
+                        .set_width(index, width + handle.drag_delta().x);
+                    self.settings_dirty = true;
+                }
+            }
+        });
+    }
+
+    /// Compact edit and delete buttons at the end of a row
+    fn show_row_actions(&mut self, ui: &mut Ui, colors: &Material3Colors, emp: &Employee) {
+        let perms = self.permissions();
+        if perms.role == Role::Employee {
+            return;
+        }
+        let emp_dept = emp.department_id.as_deref();
+        let busy = self.emp_status.is_mutating();
+        ui.allocate_ui_with_layout(
+            Vec2::new(ACTIONS_WIDTH, ROW_HEIGHT),
+            Layout::left_to_right(Align::Center),
+            |ui| {
+                ui.spacing_mut().item_spacing.x = 4.0;
+                ui.add_space(8.0);
+                let small = |icon: &str| {
+                    Button::new(RichText::new(icon).size(12.0).color(colors.on_surface))
+                        .fill(colors.surface_variant)
+                        .stroke(Stroke::new(1.0, colors.outline_variant))
+                        .rounding(Rounding::same(6.0))
+                        .min_size(Vec2::new(28.0, 24.0))
+                };
+                if !emp.is_current() {
+                    if ui
+                        .add_enabled(!busy && perms.can_edit_employee(emp_dept), small("↺"))
+                        .on_hover_text("Reactivate")
+                        .clicked()
+                    {
+                        self.confirm_employee_reactivate(emp);
+                    }
+                } else {
+                    if ui
+                        .add_enabled(!busy && perms.can_edit_employee(emp_dept), small("✏"))
+                        .on_hover_text("Edit")
+                        .clicked()
+                    {
+                        self.open_employee_editor(emp);
+                    }
+                    if ui
+                        .add_enabled(!busy && perms.can_deactivate_employee(emp_dept), small("⏻"))
+                        .on_hover_text("Deactivate")
+                        .clicked()
+                    {
+                        self.confirm_employee_deactivate(emp);
+                    }
+                }
+                if perms.can_delete_employee()
+                    && ui
+                        .add_enabled(!busy, small("🗑"))
+                        .on_hover_text("Permanently delete")
+                        .clicked()
+                {
+                    self.confirm_employee_delete(emp);
+                }
+            },
+        );
+    }
+}
+
+/// Search hits to highlight in the cell of `column`
+fn column_hits(column: EmployeeColumn, hits: &EmployeeMatch) -> &[usize] {
+    match column {
+        EmployeeColumn::Name => &hits.name,
+        EmployeeColumn::Email => &hits.email,
+        EmployeeColumn::Role => &hits.role,
+        EmployeeColumn::Department => &hits.department,
+        EmployeeColumn::Grade => &hits.grade,
+        _ => &[],
+    }
+}
+
+/// A fixed-width cell whose text is cut off with an ellipsis
+fn table_cell(ui: &mut Ui, width: f32, text: WidgetText) {
+    ui.allocate_ui_with_layout(
+        Vec2::new(width, ROW_HEIGHT),
+        Layout::left_to_right(Align::Center),
+        |ui| {
+            ui.set_width(width);
+            Frame::none()
+                .inner_margin(Margin::symmetric(8.0, 0.0))
+                .show(ui, |ui| {
+                    ui.add(Label::new(text).truncate());
+                });
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::{employee, grade};
+
+    fn sorted(layout: &TableLayout, data: &TableData) -> Vec<usize> {
+        let mut rows: Vec<(usize, ())> = (0..data.employees.len()).map(|i| (i, ())).collect();
+        layout.sort_rows(&mut rows, data);
+        rows.into_iter().map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn test_sort_by_text_grade_and_active() {
+        let emps = vec![
+            employee("a").with_name("carol", "Doe").with_grade("g2"),
+            employee("b").with_name("Alice", "Doe").with_active(false),
+            employee("c").with_name("bob", "Doe").with_grade("g1"),
+        ];
+        // The grade code order differs from the salary order on purpose
+        let grades = vec![grade("g1", "Z1", 5000.0), grade("g2", "A9", 3000.0)];
+        let data = TableData {
+            employees: &emps,
+            departments: &[],
+            salary_grades: &grades,
+        };
+        let mut layout = TableLayout::default();
+        assert_eq!(sorted(&layout, &data), [0, 1, 2]);
+
+        layout.toggle_sort(EmployeeColumn::Name);
+        assert_eq!(sorted(&layout, &data), [1, 2, 0]);
+        layout.toggle_sort(EmployeeColumn::Name);
+        assert_eq!(sorted(&layout, &data), [0, 2, 1]);
+        layout.toggle_sort(EmployeeColumn::Name);
+        assert_eq!(layout.sort, None);
+
+        layout.toggle_sort(EmployeeColumn::Grade);
+        assert_eq!(sorted(&layout, &data), [0, 2, 1]);
+
+        layout.toggle_sort(EmployeeColumn::Active);
+        assert_eq!(sorted(&layout, &data), [0, 2, 1]);
+    }
+
+    #[test]
+    fn test_move_and_resize_columns() {
+        let mut layout = TableLayout::default();
+        layout.move_column(0, 2);
+        let order: Vec<_> = layout.columns.iter().take(3).map(|c| c.column).collect();
+        assert_eq!(
+            order,
+            [
+                EmployeeColumn::Email,
+                EmployeeColumn::Role,
+                EmployeeColumn::Name
+            ]
+        );
+        layout.move_column(0, 99);
+        assert_eq!(layout.columns[0].column, EmployeeColumn::Email);
+
+        layout.set_width(1, 5.0);
+        assert_eq!(layout.columns[1].width, MIN_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn test_normalize_repairs_saved_layout() {
+        let mut layout = TableLayout {
+            columns: vec![
+                ColumnLayout {
+                    column: EmployeeColumn::Email,
+                    width: 300.0,
+                },
+                ColumnLayout {
+                    column: EmployeeColumn::Email,
+                    width: 100.0,
+                },
+                ColumnLayout {
+                    column: EmployeeColumn::Name,
+                    width: f32::NAN,
+                },
+            ],
+            sort: None,
+        };
+        layout.normalize();
+        assert_eq!(layout.columns.len(), EmployeeColumn::ALL.len());
+        assert_eq!(layout.columns[0].width, 300.0);
+        assert_eq!(layout.columns[1].column, EmployeeColumn::Name);
+        assert_eq!(
+            layout.columns[1].width,
+            EmployeeColumn::Name.default_width()
+        );
+    }
+}
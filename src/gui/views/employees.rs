@@ -1,9 +1,22 @@
 //! Employees view for the Employees tab
 
 use crate::api::models::*;
-use crate::gui::{material_button, ConfirmAction, EditDialog, Material3Colors, PersonnelApp};
+use crate::gui::bulk::{BulkAction, BulkKind, BulkTarget};
+use crate::gui::components::{load_status_caption, skeleton_card, styled_text_input, virtual_list};
+use crate::gui::dates::today;
+use crate::gui::offboarding::OffboardingMode;
+use crate::gui::permissions::Role;
+use crate::gui::requests::LoadState;
+use crate::gui::search::{highlighted, EmployeeMatch, EmployeeQuery};
+use crate::gui::settings::EmployeeView;
+use crate::gui::views::employee_table::TableData;
+use crate::gui::{
+    material_button, styled_dropdown, ConfirmAction, EditDialog, Material3Colors, PersonnelApp,
+};
 use egui::{Button, Color32, Frame, Margin, RichText, Rounding, Stroke, Ui, Vec2};
 
+const NOT_PERMITTED: &str = "Your role does not allow changing employees outside your department";
+
 impl PersonnelApp {
     pub fn show_employees(&mut self, ui: &mut Ui) {
         let colors = self.colors;
@@ -15,31 +28,153 @@ impl PersonnelApp {
                     .color(colors.on_surface)
                     .strong(),
             );
+            ui.add_space(12.0);
+            load_status_caption(ui, &colors, &self.emp_status);
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if material_button(ui, &colors, "↻ Refresh", false).clicked() {
+                let refresh = ui.add_enabled_ui(!self.emp_status.is_loading(), |ui| {
+                    material_button(ui, &colors, "↻ Refresh", false)
+                });
+                if refresh.inner.clicked() {
                     self.refresh_employees();
                 }
                 ui.add_space(8.0);
-                if material_button(ui, &colors, "+ Create", true).clicked() {
-                    self.clear_emp_form();
-                    self.edit_dialog = Some(EditDialog::CreateEmployee);
+                let (toggle_label, other_view) = match self.ui_settings.employee_view {
+                    EmployeeView::Cards => ("☰ Table", EmployeeView::Table),
+                    EmployeeView::Table => ("▦ Cards", EmployeeView::Cards),
+                };
+                if material_button(ui, &colors, toggle_label, false).clicked() {
+                    self.ui_settings.employee_view = other_view;
+                    self.settings_dirty = true;
+                }
+                let perms = self.permissions();
+                if perms.can_create_employees() {
+                    ui.add_space(8.0);
+                    let create = ui.add_enabled_ui(!self.emp_status.is_mutating(), |ui| {
+                        material_button(ui, &colors, "+ Create", true)
+                    });
+                    if create.inner.clicked() {
+                        self.clear_emp_form();
+                        if !perms.is_admin() {
+                            self.emp_dept_id = perms.department_id.clone().unwrap_or_default();
+                        }
+                        self.edit_dialog = Some(EditDialog::CreateEmployee);
+                    }
                 }
             });
         });
-        ui.add_space(20.0);
+        ui.add_space(12.0);
 
-        ui.label(
-            RichText::new("All Employees")
-                .size(16.0)
-                .color(colors.on_surface_variant),
-        );
+        ui.horizontal(|ui| {
+            styled_text_input(
+                ui,
+                &colors,
+                &mut self.emp_search,
+                "🔍 Search name or email, dept:Sales role:Admin grade:E5",
+            );
+            if !self.emp_search.is_empty() && material_button(ui, &colors, "✖", false).clicked() {
+                self.emp_search.clear();
+            }
+            ui.add_space(8.0);
+            let toggle = ui.checkbox(
+                &mut self.emp_include_inactive,
+                RichText::new("Include inactive").color(colors.on_surface_variant),
+            );
+            if toggle
+                .on_hover_text("Also list deactivated and deleted employees")
+                .changed()
+            {
+                self.refresh_employees();
+            }
+        });
         ui.add_space(12.0);
 
-        let emps = self.employees.lock().unwrap().clone();
-        let depts = self.departments.lock().unwrap().clone();
-        let grades = self.salary_grades.lock().unwrap().clone();
+        let emps = self.view_employees();
+        let depts = self.view_departments();
+        let grades = self.view_salary_grades();
+
+        if !self.bulk_selection.is_empty() {
+            self.show_bulk_bar(ui, &colors, &emps, &depts, &grades);
+            ui.add_space(12.0);
+        }
+
+        let query = EmployeeQuery::parse(&self.emp_search);
+        let results = if query.is_empty() {
+            None
+        } else {
+            // Search covers every employee, so pull in the remaining pages
+            self.load_more_employees();
+            let mut results: Vec<(usize, EmployeeMatch)> = emps
+                .iter()
+                .enumerate()
+                .filter_map(|(i, emp)| {
+                    let dept = emp
+                        .department_id
+                        .as_ref()
+                        .and_then(|id| depts.iter().find(|d| &d.id == id))
+                        .map(|d| d.name.as_str());
+                    let grade = emp
+                        .salary_grade_id
+                        .as_ref()
+                        .and_then(|id| grades.iter().find(|g| &g.id == id))
+                        .map(|g| g.code.as_str());
+                    query.matches(emp, dept, grade).map(|m| (i, m))
+                })
+                .collect();
+            if query.is_ranked() {
+                results.sort_by_key(|(_, m)| std::cmp::Reverse(m.score));
+            }
+            Some(results)
+        };
 
-        if emps.is_empty() {
+        let heading = match (&results, self.emp_pager.total()) {
+            (Some(results), Some(total)) if emps.len() < total => format!(
+                "{} matching employees (searched {} of {})",
+                results.len(),
+                emps.len(),
+                total
+            ),
+            (Some(results), _) => format!("{} matching employees", results.len()),
+            (None, Some(total)) if emps.len() < total => {
+                format!("All Employees ({} of {} loaded)", emps.len(), total)
+            }
+            (None, Some(total)) => format!("All Employees ({})", total),
+            (None, None) => "All Employees".to_string(),
+        };
+        ui.horizontal(|ui| {
+            if self.permissions().role != Role::Employee {
+                let shown: Vec<&str> = match &results {
+                    Some(results) => results.iter().map(|(i, _)| emps[*i].id.as_str()).collect(),
+                    None => emps.iter().map(|e| e.id.as_str()).collect(),
+                };
+                let mut all =
+                    !shown.is_empty() && shown.iter().all(|id| self.bulk_selection.contains(*id));
+                if ui
+                    .add_enabled(!shown.is_empty(), egui::Checkbox::without_text(&mut all))
+                    .on_hover_text("Select all shown employees")
+                    .changed()
+                {
+                    for id in shown {
+                        if all {
+                            self.bulk_selection.insert(id.to_string());
+                        } else {
+                            self.bulk_selection.remove(id);
+                        }
+                    }
+                }
+            }
+            ui.label(
+                RichText::new(heading)
+                    .size(16.0)
+                    .color(colors.on_surface_variant),
+            );
+        });
+        ui.add_space(12.0);
+
+        if emps.is_empty() && self.emp_status.is_loading() {
+            for _ in 0..3 {
+                skeleton_card(ui, &colors);
+            }
+        } else if emps.is_empty() {
             Frame::none()
                 .fill(colors.surface_variant)
                 .rounding(Rounding::same(12.0))
@@ -53,8 +188,67 @@ impl PersonnelApp {
                 });
         }
 
-        for emp in emps.iter() {
-            self.render_employee_card(ui, &colors, emp, &depts, &emps, &grades);
+        if let Some(results) = &results {
+            if results.is_empty() && !emps.is_empty() {
+                ui.label(
+                    RichText::new("No employees match the search").color(colors.on_surface_variant),
+                );
+            }
+        }
+
+        match (&results, self.ui_settings.employee_view) {
+            (_, EmployeeView::Table) if !emps.is_empty() => {
+                let rows = match &results {
+                    Some(results) => results.iter().map(|(i, m)| (*i, Some(m))).collect(),
+                    None => (0..emps.len()).map(|i| (i, None)).collect(),
+                };
+                let data = TableData {
+                    employees: &emps,
+                    departments: &depts,
+                    salary_grades: &grades,
+                };
+                self.show_employee_table(ui, &colors, &data, rows);
+            }
+            (_, EmployeeView::Table) => {}
+            (Some(results), EmployeeView::Cards) => {
+                virtual_list(ui, "employee_results", results.len(), 120.0, |ui, i| {
+                    let (index, hits) = &results[i];
+                    self.render_employee_card(
+                        ui,
+                        &colors,
+                        &emps[*index],
+                        &depts,
+                        &grades,
+                        Some(hits),
+                    );
+                });
+            }
+            (None, EmployeeView::Cards) => {
+                virtual_list(ui, "employee_cards", emps.len(), 120.0, |ui, i| {
+                    self.render_employee_card(ui, &colors, &emps[i], &depts, &grades, None);
+                });
+            }
+        }
+
+        if !emps.is_empty() && results.is_none() && self.emp_pager.has_more() {
+            self.show_load_more(ui, &colors);
+        }
+    }
+
+    /// Footer below the loaded employees. Scrolling it into view loads the
+    /// next page; the button is a fallback after a failed page.
+    fn show_load_more(&mut self, ui: &mut Ui, colors: &Material3Colors) {
+        ui.add_space(8.0);
+        let footer = ui.vertical_centered(|ui| {
+            if self.emp_status.is_loading() {
+                ui.add(egui::Spinner::new().color(colors.primary));
+            } else if material_button(ui, colors, "Load more", false).clicked() {
+                self.load_more_employees();
+            }
+        });
+        let failed = matches!(self.emp_status.state(), LoadState::Failed(_));
+        if !failed && ui.is_rect_visible(footer.response.rect) {
+            self.load_more_employees();
         }
     }
 
@@ -64,17 +258,30 @@ impl PersonnelApp {
         colors: &Material3Colors,
         emp: &Employee,
         depts: &[Department],
-        _all_emps: &[Employee],
         grades: &[SalaryGrade],
+        hits: Option<&EmployeeMatch>,
     ) {
-        Frame::none()
-            .fill(colors.surface_variant)
-            .stroke(Stroke::NONE)
+        let no_hits = EmployeeMatch::default();
+        let hits = hits.unwrap_or(&no_hits);
+        let status = employee_status(colors, emp);
+        let (fill, stroke) = match status {
+            Some((_, color)) => (colors.surface, Stroke::new(1.0, color)),
+            None => (colors.surface_variant, Stroke::NONE),
+        };
+        let stroke = if self.selected_emp.as_ref() == Some(&emp.id) {
+            Stroke::new(2.0, colors.primary)
+        } else {
+            stroke
+        };
+        let card = Frame::none()
+            .fill(fill)
+            .stroke(stroke)
             .rounding(Rounding::same(12.0))
             .inner_margin(Margin::same(16.0))
             .outer_margin(Margin::symmetric(0.0, 4.0))
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
+                    self.selection_checkbox(ui, emp);
                     Frame::none()
                         .fill(colors.primary_container)
                         .rounding(Rounding::same(8.0))
@@ -86,59 +293,133 @@ impl PersonnelApp {
                     ui.add_space(12.0);
 
                     ui.vertical(|ui| {
-                        ui.label(
-                            RichText::new(format!("{} {}", emp.first_name, emp.last_name))
-                                .size(16.0)
-                                .strong()
-                                .color(colors.on_surface),
-                        );
+                        let name_color = if status.is_some() {
+                            colors.on_surface_variant
+                        } else {
+                            colors.on_surface
+                        };
+                        ui.horizontal(|ui| {
+                            ui.label(highlighted(
+                                "",
+                                &format!("{} {}", emp.first_name, emp.last_name),
+                                &hits.name,
+                                16.0,
+                                name_color,
+                                colors,
+                            ));
+                            if let Some((label, color)) = status {
+                                ui.label(RichText::new(label).size(12.0).color(color).strong());
+                            }
+                        });
                         ui.add_space(4.0);
-                        ui.label(
-                            RichText::new(&emp.email)
-                                .size(13.0)
-                                .color(colors.on_surface_variant),
-                        );
-                        ui.label(
-                            RichText::new(format!("Role: {}", emp.role))
-                                .size(13.0)
-                                .color(colors.on_surface_variant),
-                        );
+                        ui.label(highlighted(
+                            "",
+                            &emp.email,
+                            &hits.email,
+                            13.0,
+                            colors.on_surface_variant,
+                            colors,
+                        ));
+                        ui.label(highlighted(
+                            "Role: ",
+                            &emp.role,
+                            &hits.role,
+                            13.0,
+                            colors.on_surface_variant,
+                            colors,
+                        ));
                         let dept_name = emp
                             .department_id
                             .as_ref()
                             .and_then(|id| depts.iter().find(|d| &d.id == id))
                             .map(|d| d.name.clone())
                             .unwrap_or_else(|| "No department".to_string());
-                        ui.label(
-                            RichText::new(format!("Dept: {}", dept_name))
-                                .size(13.0)
-                                .color(colors.on_surface_variant),
-                        );
+                        ui.label(highlighted(
+                            "Dept: ",
+                            &dept_name,
+                            &hits.department,
+                            13.0,
+                            colors.on_surface_variant,
+                            colors,
+                        ));
                         let grade_info = emp
                             .salary_grade_id
                             .as_ref()
                             .and_then(|id| grades.iter().find(|g| &g.id == id))
                             .map(|g| format!("{} - ${:.2}", g.code, g.base_salary))
                             .unwrap_or_else(|| "No grade".to_string());
-                        ui.label(
-                            RichText::new(format!("Grade: {}", grade_info))
-                                .size(13.0)
-                                .color(colors.on_surface_variant),
-                        );
+                        ui.label(highlighted(
+                            "Grade: ",
+                            &grade_info,
+                            &hits.grade,
+                            13.0,
+                            colors.on_surface_variant,
+                            colors,
+                        ));
                     });
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        let delete_btn =
-                            Button::new(RichText::new("🗑 Delete").size(12.0).color(Color32::WHITE))
-                                .fill(Color32::from_rgb(220, 53, 69))
-                                .stroke(Stroke::NONE)
-                                .rounding(Rounding::same(8.0))
-                                .min_size(Vec2::new(75.0, 36.0));
-                        if ui.add(delete_btn).clicked() {
-                            self.confirm_dialog = Some(ConfirmAction::DeleteEmployee {
-                                id: emp.id.clone(),
-                                name: format!("{} {}", emp.first_name, emp.last_name),
-                            });
+                        let perms = self.permissions();
+                        if perms.role == Role::Employee {
+                            return;
+                        }
+                        let emp_dept = emp.department_id.as_deref();
+                        let can_deactivate = perms.can_deactivate_employee(emp_dept);
+                        let can_edit = perms.can_edit_employee(emp_dept);
+                        let busy = self.emp_status.is_mutating();
+
+                        if perms.can_delete_employee() {
+                            let delete_btn =
+                                Button::new(RichText::new("🗑").size(12.0).color(Color32::WHITE))
+                                    .fill(Color32::from_rgb(220, 53, 69))
+                                    .stroke(Stroke::NONE)
+                                    .rounding(Rounding::same(8.0))
+                                    .min_size(Vec2::new(36.0, 36.0));
+                            if ui
+                                .add_enabled(!busy, delete_btn)
+                                .on_hover_text("Permanently delete")
+                                .clicked()
+                            {
+                                self.confirm_employee_delete(emp);
+                            }
+                            ui.add_space(8.0);
+                        }
+
+                        if status.is_some() {
+                            let reactivate_btn = Button::new(
+                                RichText::new("↺ Reactivate")
+                                    .size(12.0)
+                                    .color(colors.on_primary),
+                            )
+                            .fill(colors.primary)
+                            .stroke(Stroke::NONE)
+                            .rounding(Rounding::same(8.0))
+                            .min_size(Vec2::new(95.0, 36.0));
+                            if ui
+                                .add_enabled(!busy && can_edit, reactivate_btn)
+                                .on_disabled_hover_text(NOT_PERMITTED)
+                                .clicked()
+                            {
+                                self.confirm_employee_reactivate(emp);
+                            }
+                            return;
+                        }
+
+                        let deactivate_btn = Button::new(
+                            RichText::new("⏻ Deactivate")
+                                .size(12.0)
+                                .color(colors.on_surface),
+                        )
+                        .fill(colors.surface)
+                        .stroke(Stroke::new(1.0, colors.outline_variant))
+                        .rounding(Rounding::same(8.0))
+                        .min_size(Vec2::new(95.0, 36.0));
+                        if ui
+                            .add_enabled(!busy && can_deactivate, deactivate_btn)
+                            .on_disabled_hover_text(NOT_PERMITTED)
+                            .clicked()
+                        {
+                            self.confirm_employee_deactivate(emp);
                         }
                         ui.add_space(8.0);
                         let edit_btn = Button::new(
@@ -148,23 +429,228 @@ impl PersonnelApp {
                         .stroke(Stroke::NONE)
                         .rounding(Rounding::same(8.0))
                         .min_size(Vec2::new(65.0, 36.0));
-                        if ui.add(edit_btn).clicked() {
-                            self.emp_first_name = emp.first_name.clone();
-                            self.emp_last_name = emp.last_name.clone();
-                            self.emp_email = emp.email.clone();
-                            self.emp_role = emp.role.clone();
-                            self.emp_dept_id = emp.department_id.clone().unwrap_or_default();
-                            self.emp_manager_id = emp.manager_id.clone().unwrap_or_default();
-                            self.emp_salary_grade_id =
-                                emp.salary_grade_id.clone().unwrap_or_default();
-                            self.edit_dialog =
-                                Some(EditDialog::EditEmployee { id: emp.id.clone() });
+                        if ui
+                            .add_enabled(!busy && can_edit, edit_btn)
+                            .on_disabled_hover_text(NOT_PERMITTED)
+                            .clicked()
+                        {
+                            self.open_employee_editor(emp);
+                        }
+                    });
+                });
+            });
+        let card = card.response.interact(egui::Sense::click());
+        if card.on_hover_text("Show details").clicked() {
+            self.toggle_employee_detail(emp);
+        }
+    }
+
+    /// Checkbox adding `emp` to the bulk selection; hidden for read-only roles
+    pub(crate) fn selection_checkbox(&mut self, ui: &mut Ui, emp: &Employee) {
+        if self.permissions().role == Role::Employee {
+            return;
+        }
+        let mut selected = self.bulk_selection.contains(&emp.id);
+        if ui.checkbox(&mut selected, "").changed() {
+            if selected {
+                self.bulk_selection.insert(emp.id.clone());
+            } else {
+                self.bulk_selection.remove(&emp.id);
+            }
+        }
+    }
+
+    /// Bar above the list offering one change for all selected employees
+    fn show_bulk_bar(
+        &mut self,
+        ui: &mut Ui,
+        colors: &Material3Colors,
+        emps: &[Employee],
+        depts: &[Department],
+        grades: &[SalaryGrade],
+    ) {
+        let perms = self.permissions();
+        let options: Vec<(String, String)> = match self.bulk_kind {
+            BulkKind::Department => depts
+                .iter()
+                .filter(|d| perms.can_assign_department(Some(&d.id)))
+                .map(|d| (d.id.clone(), d.name.clone()))
+                .collect(),
+            BulkKind::SalaryGrade => grades
+                .iter()
+                .map(|g| (g.id.clone(), format!("{} - ${:.2}", g.code, g.base_salary)))
+                .collect(),
+            BulkKind::Manager => emps
+                .iter()
+                .filter(|e| e.is_current())
+                .map(|e| (e.id.clone(), format!("{} {}", e.first_name, e.last_name)))
+                .collect(),
+            BulkKind::Role => perms
+                .assignable_roles()
+                .iter()
+                .map(|r| (r.as_str().to_string(), r.as_str().to_string()))
+                .collect(),
+            BulkKind::Deactivate => Vec::new(),
+        };
+
+        Frame::none()
+            .fill(colors.primary_container)
+            .rounding(Rounding::same(12.0))
+            .inner_margin(Margin::symmetric(16.0, 8.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("{} selected", self.bulk_selection.len()))
+                            .size(14.0)
+                            .color(colors.on_primary_container)
+                            .strong(),
+                    );
+                    ui.add_space(12.0);
+
+                    styled_dropdown(ui, colors, |ui| {
+                        egui::ComboBox::from_id_salt("bulk_kind_dropdown")
+                            .selected_text(
+                                RichText::new(self.bulk_kind.label()).color(colors.on_surface),
+                            )
+                            .width(180.0)
+                            .show_ui(ui, |ui| {
+                                for kind in BulkKind::ALL {
+                                    let label =
+                                        RichText::new(kind.label()).color(colors.on_surface);
+                                    if ui.selectable_label(self.bulk_kind == kind, label).clicked()
+                                        && self.bulk_kind != kind
+                                    {
+                                        self.bulk_kind = kind;
+                                        self.bulk_value.clear();
+                                    }
+                                }
+                            });
+                    });
+
+                    if self.bulk_kind != BulkKind::Deactivate {
+                        let current = options
+                            .iter()
+                            .find(|(id, _)| *id == self.bulk_value)
+                            .map(|(_, label)| label.clone())
+                            .unwrap_or_else(|| "Select...".to_string());
+                        styled_dropdown(ui, colors, |ui| {
+                            egui::ComboBox::from_id_salt("bulk_value_dropdown")
+                                .selected_text(RichText::new(current).color(colors.on_surface))
+                                .width(220.0)
+                                .height(300.0)
+                                .show_ui(ui, |ui| {
+                                    for (id, label) in &options {
+                                        let text = RichText::new(label).color(colors.on_surface);
+                                        if ui
+                                            .selectable_label(self.bulk_value == *id, text)
+                                            .clicked()
+                                        {
+                                            self.bulk_value = id.clone();
+                                        }
+                                    }
+                                });
+                        });
+                    }
+
+                    let label = options
+                        .iter()
+                        .find(|(id, _)| *id == self.bulk_value)
+                        .map(|(_, label)| label.clone());
+                    let action = match (self.bulk_kind, label) {
+                        (BulkKind::Deactivate, _) => Some(BulkAction::Deactivate),
+                        (_, None) => None,
+                        (BulkKind::Department, Some(name)) => Some(BulkAction::MoveToDepartment {
+                            id: self.bulk_value.clone(),
+                            name,
+                        }),
+                        (BulkKind::SalaryGrade, Some(_)) => Some(BulkAction::SetSalaryGrade {
+                            id: self.bulk_value.clone(),
+                            code: grades
+                                .iter()
+                                .find(|g| g.id == self.bulk_value)
+                                .map(|g| g.code.clone())
+                                .unwrap_or_default(),
+                        }),
+                        (BulkKind::Manager, Some(name)) => Some(BulkAction::SetManager {
+                            id: self.bulk_value.clone(),
+                            name,
+                        }),
+                        (BulkKind::Role, Some(role)) => Some(BulkAction::ChangeRole { role }),
+                    };
+
+                    ui.add_space(8.0);
+                    let busy = self.emp_status.is_mutating();
+                    let apply = ui.add_enabled_ui(action.is_some() && !busy, |ui| {
+                        material_button(ui, colors, "Apply…", true)
+                    });
+                    if let (true, Some(action)) = (apply.inner.clicked(), action) {
+                        let targets = emps
+                            .iter()
+                            .filter(|e| self.bulk_selection.contains(&e.id))
+                            .map(BulkTarget::new)
+                            .collect();
+                        self.confirm_dialog =
+                            Some(ConfirmAction::BulkUpdateEmployees { action, targets });
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if material_button(ui, colors, "Clear selection", false).clicked() {
+                            self.bulk_selection.clear();
                         }
                     });
                 });
             });
     }
 
+    /// Fill the employee form from `emp` and open the edit dialog
+    pub fn open_employee_editor(&mut self, emp: &Employee) {
+        self.emp_first_name = emp.first_name.clone();
+        self.emp_last_name = emp.last_name.clone();
+        self.emp_email = emp.email.clone();
+        self.emp_role = emp.role.clone();
+        self.emp_dept_id = emp.department_id.clone().unwrap_or_default();
+        self.emp_manager_id = emp.manager_id.clone().unwrap_or_default();
+        self.emp_salary_grade_id = emp.salary_grade_id.clone().unwrap_or_default();
+        self.edit_dialog = Some(EditDialog::EditEmployee {
+            id: emp.id.clone(),
+            version: emp.updated_at.clone(),
+        });
+    }
+
+    pub fn confirm_employee_deactivate(&mut self, emp: &Employee) {
+        if self.needs_offboarding(emp) {
+            self.start_offboarding(emp, OffboardingMode::Deactivate);
+            return;
+        }
+        self.confirm_input = today();
+        self.confirm_dialog = Some(ConfirmAction::DeactivateEmployee {
+            id: emp.id.clone(),
+            name: format!("{} {}", emp.first_name, emp.last_name),
+            version: emp.updated_at.clone(),
+        });
+    }
+
+    /// Ask for a hard delete; the dialog wants the name typed in
+    pub fn confirm_employee_delete(&mut self, emp: &Employee) {
+        if self.needs_offboarding(emp) {
+            self.start_offboarding(emp, OffboardingMode::Delete);
+            return;
+        }
+        self.confirm_input.clear();
+        self.confirm_dialog = Some(ConfirmAction::DeleteEmployee {
+            id: emp.id.clone(),
+            name: format!("{} {}", emp.first_name, emp.last_name),
+        });
+    }
+
+    pub fn confirm_employee_reactivate(&mut self, emp: &Employee) {
+        self.confirm_dialog = Some(ConfirmAction::ReactivateEmployee {
+            id: emp.id.clone(),
+            name: format!("{} {}", emp.first_name, emp.last_name),
+            version: emp.updated_at.clone(),
+        });
+    }
+
     pub fn clear_emp_form(&mut self) {
         self.emp_first_name.clear();
         self.emp_last_name.clear();
@@ -175,3 +661,17 @@ impl PersonnelApp {
         self.emp_salary_grade_id.clear();
     }
 }
+
+/// Badge text and color for employees that are no longer current
+pub(crate) fn employee_status(
+    colors: &Material3Colors,
+    emp: &Employee,
+) -> Option<(&'static str, Color32)> {
+    if emp.deleted_at.is_some() {
+        Some(("Deleted", colors.error))
+    } else if !emp.active {
+        Some(("Inactive", colors.warning))
+    } else {
+        None
+    }
+}
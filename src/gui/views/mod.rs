@@ -1,12 +1,22 @@
 //! Views module containing the tab views
 //!
 //! Each tab in the application has its own submodule:
+//! - `dashboard`: Headcount and payroll figures with charts
 //! - `departments`: Department management view
+//! - `department_detail`: Members and figures of a single department
+//! - `employee_detail`: Side panel with the profile of the selected employee
 //! - `employees`: Employee management view  
+//! - `employee_table`: Table mode of the employee view
+//! - `org_chart`: Reporting hierarchy drawn as a pannable tree
 //! - `salary_grades`: Salary grade management view
 //!
 //! Each view is implemented as methods on PersonnelApp via impl blocks.
 
+pub mod dashboard;
+pub mod department_detail;
 pub mod departments;
+pub mod employee_detail;
+pub mod employee_table;
 pub mod employees;
+pub mod org_chart;
 pub mod salary_grades;
@@ -0,0 +1,544 @@
+//! Org chart view for the Org Chart tab
+//!
+//! The tree follows `Employee.manager_id`; employees without a manager, or
+//! whose manager is not loaded, are roots. A reporting cycle is broken at
+//! the first employee of the cycle that is reached, which then becomes a
+//! root as well. Department heads are labelled with their department.
+
+use crate::api::models::*;
+use crate::gui::components::{load_status_caption, styled_text_input};
+use crate::gui::search::fuzzy_match;
+use crate::gui::{material_button, PersonnelApp};
+use egui::{Align2, FontId, Pos2, Rect, RichText, Rounding, Sense, Stroke, Ui, Vec2};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Size of a node and distance between nodes at zoom 1.0
+const NODE_SIZE: Vec2 = Vec2::new(180.0, 56.0);
+const X_PITCH: f32 = 200.0;
+const Y_PITCH: f32 = 100.0;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 2.5;
+
+/// Pan, zoom and folding of the org chart, kept while switching tabs
+#[derive(Debug, Clone)]
+pub struct OrgChartState {
+    pub offset: Vec2,
+    pub zoom: f32,
+    /// Employees whose reports are hidden
+    pub collapsed: BTreeSet<String>,
+    pub search: String,
+    /// Highlighted employee, centered once when `center` is set
+    pub focus: Option<String>,
+    pub center: bool,
+}
+
+impl Default for OrgChartState {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::new(24.0, 24.0),
+            zoom: 1.0,
+            collapsed: BTreeSet::new(),
+            search: String::new(),
+            focus: None,
+            center: false,
+        }
+    }
+}
+
+/// An employee placed in the chart, in slots of `X_PITCH` and `Y_PITCH`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrgNode {
+    /// Index into the employee list the chart was built from
+    pub employee: usize,
+    pub parent: Option<usize>,
+    pub depth: usize,
+    pub x: f32,
+    /// Number of direct reports, shown or not
+    pub reports: usize,
+}
+
+/// Lay out the reporting tree of `employees`; reports of `collapsed`
+/// employees are left out. Parents are centered over their reports.
+pub fn layout(employees: &[Employee], collapsed: &BTreeSet<String>) -> Vec<OrgNode> {
+    let index: HashMap<&str, usize> = employees
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.id.as_str(), i))
+        .collect();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); employees.len()];
+    let mut roots = Vec::new();
+    for (i, emp) in employees.iter().enumerate() {
+        match emp
+            .manager_id
+            .as_deref()
+            .and_then(|m| index.get(m))
+            .filter(|&&m| m != i)
+        {
+            Some(&manager) => children[manager].push(i),
+            None => roots.push(i),
+        }
+    }
+    let by_name = |a: &usize, b: &usize| {
+        let name = |i: &usize| (&employees[*i].last_name, &employees[*i].first_name);
+        name(a).cmp(&name(b))
+    };
+    roots.sort_by(by_name);
+    for list in &mut children {
+        list.sort_by(by_name);
+    }
+
+    struct Walk<'a> {
+        employees: &'a [Employee],
+        children: &'a [Vec<usize>],
+        collapsed: &'a BTreeSet<String>,
+        visited: HashSet<usize>,
+        nodes: Vec<OrgNode>,
+        next_slot: f32,
+    }
+
+    impl Walk<'_> {
+        fn place(&mut self, emp: usize, parent: Option<usize>, depth: usize) -> f32 {
+            let node = self.nodes.len();
+            self.visited.insert(emp);
+            self.nodes.push(OrgNode {
+                employee: emp,
+                parent,
+                depth,
+                x: 0.0,
+                reports: self.children[emp].len(),
+            });
+            let hidden = self.collapsed.contains(&self.employees[emp].id);
+            let mut xs = Vec::new();
+            for &child in &self.children[emp] {
+                if self.visited.contains(&child) {
+                    continue;
+                }
+                if hidden {
+                    self.hide(child);
+                } else {
+                    xs.push(self.place(child, Some(node), depth + 1));
+                }
+            }
+            let x = match (xs.first(), xs.last()) {
+                (Some(first), Some(last)) => (first + last) / 2.0,
+                _ => {
+                    self.next_slot += 1.0;
+                    self.next_slot - 1.0
+                }
+            };
+            self.nodes[node].x = x;
+            x
+        }
+
+        /// Skip a folded subtree so it is not mistaken for a cycle
+        fn hide(&mut self, emp: usize) {
+            if self.visited.insert(emp) {
+                for &child in &self.children[emp] {
+                    self.hide(child);
+                }
+            }
+        }
+    }
+
+    let mut walk = Walk {
+        employees,
+        children: &children,
+        collapsed,
+        visited: HashSet::new(),
+        nodes: Vec::new(),
+        next_slot: 0.0,
+    };
+    for root in roots {
+        walk.place(root, None, 0);
+    }
+    // Whoever is left sits in a reporting cycle
+    for emp in 0..employees.len() {
+        if !walk.visited.contains(&emp) {
+            walk.place(emp, None, 0);
+        }
+    }
+    walk.nodes
+}
+
+/// Managers of `id` up to the root, nearest first
+pub fn ancestors(employees: &[Employee], id: &str) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut current = employees.iter().find(|e| e.id == id);
+    while let Some(manager) = current.and_then(|e| e.manager_id.clone()) {
+        if manager == id || chain.contains(&manager) {
+            break;
+        }
+        current = employees.iter().find(|e| e.id == manager);
+        chain.push(manager);
+    }
+    chain
+}
+
+/// Best fuzzy match for `query` by full name or email
+pub fn find_employee<'a>(employees: &'a [Employee], query: &str) -> Option<&'a Employee> {
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+    employees
+        .iter()
+        .filter_map(|e| {
+            let name = format!("{} {}", e.first_name, e.last_name);
+            let score = [name.as_str(), e.email.as_str()]
+                .iter()
+                .filter_map(|text| fuzzy_match(query, text).map(|(score, _)| score))
+                .max()?;
+            Some((score, e))
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, e)| e)
+}
+
+impl PersonnelApp {
+    pub fn show_org_chart(&mut self, ui: &mut Ui) {
+        let colors = self.colors;
+
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Org Chart")
+                    .size(28.0)
+                    .color(colors.on_surface)
+                    .strong(),
+            );
+            ui.add_space(12.0);
+            load_status_caption(ui, &colors, &self.emp_status);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let refresh = ui.add_enabled_ui(!self.emp_status.is_loading(), |ui| {
+                    material_button(ui, &colors, "↻ Refresh", false)
+                });
+                if refresh.inner.clicked() {
+                    self.refresh_employees();
+                }
+            });
+        });
+        ui.add_space(12.0);
+
+        let emps = self.view_employees();
+        let depts = self.view_departments();
+
+        ui.horizontal(|ui| {
+            let search =
+                styled_text_input(ui, &colors, &mut self.org_chart.search, "🔍 Find a person");
+            let submitted = search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if material_button(ui, &colors, "Focus", false).clicked() || submitted {
+                match find_employee(&emps, &self.org_chart.search) {
+                    Some(emp) => {
+                        for manager in ancestors(&emps, &emp.id) {
+                            self.org_chart.collapsed.remove(&manager);
+                        }
+                        self.org_chart.focus = Some(emp.id.clone());
+                        self.org_chart.center = true;
+                    }
+                    None => self.notifier.info("Nobody matches the search"),
+                }
+            }
+            ui.add_space(12.0);
+            if material_button(ui, &colors, "−", false).clicked() {
+                self.org_chart.zoom = (self.org_chart.zoom / 1.25).max(MIN_ZOOM);
+            }
+            ui.label(
+                RichText::new(format!("{:.0}%", self.org_chart.zoom * 100.0))
+                    .color(colors.on_surface_variant),
+            );
+            if material_button(ui, &colors, "+", false).clicked() {
+                self.org_chart.zoom = (self.org_chart.zoom * 1.25).min(MAX_ZOOM);
+            }
+            if material_button(ui, &colors, "Reset view", false).clicked() {
+                let collapsed = std::mem::take(&mut self.org_chart.collapsed);
+                self.org_chart = Default::default();
+                self.org_chart.collapsed = collapsed;
+            }
+            if material_button(ui, &colors, "Expand all", false).clicked() {
+                self.org_chart.collapsed.clear();
+            }
+        });
+
+        if self.emp_pager.has_more() {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "Only {} loaded employees are shown; reports on later pages are missing.",
+                        emps.len()
+                    ))
+                    .color(colors.on_surface_variant),
+                );
+                if !self.emp_status.is_loading()
+                    && material_button(ui, &colors, "Load more", false).clicked()
+                {
+                    self.load_more_employees();
+                }
+            });
+        }
+        ui.add_space(8.0);
+
+        let heads: HashMap<&str, Vec<&str>> = depts.iter().fold(HashMap::new(), |mut map, d| {
+            if let Some(head) = d.head_id.as_deref() {
+                map.entry(head).or_default().push(d.name.as_str());
+            }
+            map
+        });
+        let dept_names: HashMap<&str, &str> = depts
+            .iter()
+            .map(|d| (d.id.as_str(), d.name.as_str()))
+            .collect();
+        let nodes = layout(&emps, &self.org_chart.collapsed);
+
+        let size = Vec2::new(ui.available_width(), 600.0_f32.max(ui.available_height()));
+        let (canvas, response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+        let painter = ui.painter_at(canvas);
+        painter.rect(
+            canvas,
+            Rounding::same(12.0),
+            colors.surface_variant,
+            Stroke::new(1.0, colors.outline_variant),
+        );
+        if nodes.is_empty() {
+            painter.text(
+                canvas.center(),
+                Align2::CENTER_CENTER,
+                "No employees to show",
+                FontId::proportional(16.0),
+                colors.on_surface_variant,
+            );
+            return;
+        }
+
+        // Pan by dragging, zoom with pinch or Ctrl + scroll around the pointer
+        let state = &mut self.org_chart;
+        if response.dragged() {
+            state.offset += response.drag_delta();
+        }
+        if response.hovered() {
+            let factor = ui.input(|i| i.zoom_delta());
+            if factor != 1.0 {
+                let zoom = (state.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+                if let Some(pointer) = response.hover_pos() {
+                    let anchor = pointer - canvas.min;
+                    state.offset = anchor - (anchor - state.offset) * (zoom / state.zoom);
+                }
+                state.zoom = zoom;
+            }
+        }
+        let zoom = state.zoom;
+        let slot = |node: &OrgNode| Vec2::new(node.x * X_PITCH, node.depth as f32 * Y_PITCH);
+        if state.center {
+            state.center = false;
+            if let Some(node) = nodes
+                .iter()
+                .find(|n| Some(&emps[n.employee].id) == state.focus.as_ref())
+            {
+                state.offset = canvas.size() / 2.0 - (slot(node) + NODE_SIZE / 2.0) * zoom;
+            }
+        }
+        let origin = canvas.min + state.offset;
+        let node_rect =
+            |node: &OrgNode| Rect::from_min_size(origin + slot(node) * zoom, NODE_SIZE * zoom);
+
+        let line = Stroke::new(1.5, colors.outline);
+        for node in &nodes {
+            let Some(parent) = node.parent else {
+                continue;
+            };
+            let from = node_rect(&nodes[parent]).center_bottom();
+            let to = node_rect(node).center_top();
+            let mid = (from.y + to.y) / 2.0;
+            painter.line_segment([from, Pos2::new(from.x, mid)], line);
+            painter.line_segment([Pos2::new(from.x, mid), Pos2::new(to.x, mid)], line);
+            painter.line_segment([Pos2::new(to.x, mid), to], line);
+        }
+
+        let perms = self.permissions();
+        let mut toggle = None;
+        let mut open = None;
+        for (i, node) in nodes.iter().enumerate() {
+            let rect = node_rect(node);
+            if !canvas.intersects(rect.expand(16.0 * zoom)) {
+                continue;
+            }
+            let emp = &emps[node.employee];
+            let focused = self.org_chart.focus.as_ref() == Some(&emp.id);
+            let head_of = heads.get(emp.id.as_str());
+            let (fill, text) = if head_of.is_some() {
+                (colors.primary_container, colors.on_primary_container)
+            } else {
+                (colors.surface, colors.on_surface)
+            };
+            let stroke = if focused {
+                Stroke::new(3.0, colors.primary)
+            } else {
+                Stroke::new(1.0, colors.outline_variant)
+            };
+
+            let hit = ui.interact(rect, ui.id().with(("org_node", i)), Sense::click());
+            let fill = if hit.hovered() {
+                fill.gamma_multiply(0.85)
+            } else {
+                fill
+            };
+            painter.rect(rect, Rounding::same(8.0 * zoom), fill, stroke);
+
+            let name = format!("{} {}", emp.first_name, emp.last_name);
+            let detail = match head_of {
+                Some(names) => format!("Head of {}", names.join(", ")),
+                None => {
+                    let dept = emp
+                        .department_id
+                        .as_deref()
+                        .and_then(|d| dept_names.get(d).copied())
+                        .unwrap_or("No department");
+                    format!("{} • {}", emp.role, dept)
+                }
+            };
+            let alpha = if emp.active { 1.0 } else { 0.5 };
+            let clip = painter.with_clip_rect(rect.shrink(4.0 * zoom).intersect(canvas));
+            clip.text(
+                rect.left_top() + Vec2::new(10.0, 10.0) * zoom,
+                Align2::LEFT_TOP,
+                name,
+                FontId::proportional(14.0 * zoom),
+                text.gamma_multiply(alpha),
+            );
+            clip.text(
+                rect.left_top() + Vec2::new(10.0, 32.0) * zoom,
+                Align2::LEFT_TOP,
+                detail,
+                FontId::proportional(11.0 * zoom),
+                colors.on_surface_variant.gamma_multiply(alpha),
+            );
+
+            let can_edit = perms.can_edit_employee(emp.department_id.as_deref());
+            let hint = if can_edit {
+                format!("{}\nClick to edit", emp.email)
+            } else {
+                emp.email.clone()
+            };
+            if hit.on_hover_text(hint).clicked() && can_edit {
+                open = Some(emp.clone());
+            }
+
+            if node.reports > 0 {
+                let collapsed = self.org_chart.collapsed.contains(&emp.id);
+                let knob = Rect::from_center_size(rect.center_bottom(), Vec2::splat(18.0 * zoom));
+                let knob_hit = ui.interact(knob, ui.id().with(("org_knob", i)), Sense::click());
+                painter.circle(
+                    knob.center(),
+                    9.0 * zoom,
+                    if knob_hit.hovered() {
+                        colors.primary
+                    } else {
+                        colors.surface_variant
+                    },
+                    Stroke::new(1.0, colors.outline),
+                );
+                let label = if collapsed {
+                    format!("+{}", node.reports)
+                } else {
+                    "−".to_string()
+                };
+                painter.text(
+                    knob.center(),
+                    Align2::CENTER_CENTER,
+                    label,
+                    FontId::proportional(10.0 * zoom),
+                    colors.on_surface,
+                );
+                let hover = if collapsed {
+                    "Show reports"
+                } else {
+                    "Hide reports"
+                };
+                if knob_hit.on_hover_text(hover).clicked() {
+                    toggle = Some(emp.id.clone());
+                }
+            }
+        }
+
+        if let Some(id) = toggle {
+            if !self.org_chart.collapsed.remove(&id) {
+                self.org_chart.collapsed.insert(id);
+            }
+        }
+        if let Some(emp) = open {
+            self.open_employee_editor(&emp);
+        }
+        if response.dragged() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        } else if response.hovered() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::fixtures::employee;
+
+    fn placed<'a>(nodes: &[OrgNode], emps: &'a [Employee]) -> Vec<(&'a str, usize, f32)> {
+        nodes
+            .iter()
+            .map(|n| (emps[n.employee].id.as_str(), n.depth, n.x))
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_centers_managers_over_reports() {
+        let emps = vec![
+            employee("b").with_manager("a"),
+            employee("a"),
+            employee("c").with_manager("a"),
+            employee("d").with_manager("c"),
+            employee("e").with_manager("ghost"),
+        ];
+        let nodes = layout(&emps, &BTreeSet::new());
+        assert_eq!(
+            placed(&nodes, &emps),
+            [
+                ("a", 0, 0.5),
+                ("b", 1, 0.0),
+                ("c", 1, 1.0),
+                ("d", 2, 1.0),
+                ("e", 0, 2.0)
+            ]
+        );
+        assert_eq!(nodes[2].parent, Some(0));
+        assert_eq!(nodes[0].reports, 2);
+
+        let collapsed = BTreeSet::from(["a".to_string()]);
+        let nodes = layout(&emps, &collapsed);
+        assert_eq!(placed(&nodes, &emps), [("a", 0, 0.0), ("e", 0, 1.0)]);
+    }
+
+    #[test]
+    fn test_layout_breaks_reporting_cycles() {
+        let emps = vec![
+            employee("x").with_manager("y"),
+            employee("y").with_manager("x"),
+            employee("z").with_manager("z"),
+        ];
+        let nodes = layout(&emps, &BTreeSet::new());
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(placed(&nodes, &emps)[0].0, "z");
+        assert_eq!(nodes.iter().filter(|n| n.parent.is_none()).count(), 2);
+        assert_eq!(ancestors(&emps, "x"), ["y"]);
+    }
+
+    #[test]
+    fn test_find_employee_prefers_best_match() {
+        let emps = vec![
+            employee("e1").with_name("Anna", "Doe"),
+            employee("e2").with_name("Hannah", "Doe"),
+        ];
+        assert_eq!(
+            find_employee(&emps, "anna d").map(|e| e.id.as_str()),
+            Some("e1")
+        );
+        assert!(find_employee(&emps, "  ").is_none());
+        assert!(find_employee(&emps, "zzz").is_none());
+    }
+}
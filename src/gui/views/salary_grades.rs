@@ -1,7 +1,8 @@
 //! Salary Grades view for the Salary Grades tab
 
 use crate::api::models::*;
-use crate::gui::{material_button, ConfirmAction, EditDialog, Material3Colors, PersonnelApp};
+use crate::gui::components::{load_status_caption, skeleton_card, virtual_list};
+use crate::gui::{material_button, EditDialog, Material3Colors, PersonnelApp};
 use egui::{Button, Color32, Frame, Margin, RichText, Rounding, Stroke, Ui, Vec2};
 
 impl PersonnelApp {
@@ -15,14 +16,24 @@ impl PersonnelApp {
                     .color(colors.on_surface)
                     .strong(),
             );
+            ui.add_space(12.0);
+            load_status_caption(ui, &colors, &self.grade_status);
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if material_button(ui, &colors, "↻ Refresh", false).clicked() {
+                let refresh = ui.add_enabled_ui(!self.grade_status.is_loading(), |ui| {
+                    material_button(ui, &colors, "↻ Refresh", false)
+                });
+                if refresh.inner.clicked() {
                     self.refresh_salary_grades();
                 }
-                ui.add_space(8.0);
-                if material_button(ui, &colors, "+ Create", true).clicked() {
-                    self.clear_grade_form();
-                    self.edit_dialog = Some(EditDialog::CreateSalaryGrade);
+                if self.permissions().can_manage_salary_grades() {
+                    ui.add_space(8.0);
+                    let create = ui.add_enabled_ui(!self.grade_status.is_mutating(), |ui| {
+                        material_button(ui, &colors, "+ Create", true)
+                    });
+                    if create.inner.clicked() {
+                        self.clear_grade_form();
+                        self.edit_dialog = Some(EditDialog::CreateSalaryGrade);
+                    }
                 }
             });
         });
@@ -35,10 +46,14 @@ impl PersonnelApp {
         );
         ui.add_space(12.0);
 
-        let grades = self.salary_grades.lock().unwrap().clone();
-        let employees = self.employees.lock().unwrap().clone();
+        let grades = self.view_salary_grades();
+        let employees = self.view_employees();
 
-        if grades.is_empty() {
+        if grades.is_empty() && self.grade_status.is_loading() {
+            for _ in 0..3 {
+                skeleton_card(ui, &colors);
+            }
+        } else if grades.is_empty() {
             Frame::none()
                 .fill(colors.surface_variant)
                 .rounding(Rounding::same(12.0))
@@ -52,13 +67,14 @@ impl PersonnelApp {
                 });
         }
 
-        for grade in grades.iter() {
+        virtual_list(ui, "salary_grade_cards", grades.len(), 96.0, |ui, i| {
+            let grade = &grades[i];
             let employee_count = employees
                 .iter()
                 .filter(|e| e.salary_grade_id.as_deref() == Some(&grade.id))
                 .count();
             self.render_grade_card(ui, &colors, grade, employee_count);
-        }
+        });
     }
 
     fn render_grade_card(
@@ -122,18 +138,18 @@ impl PersonnelApp {
                     });
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if !self.permissions().can_manage_salary_grades() {
+                            return;
+                        }
                         let delete_btn =
                             Button::new(RichText::new("🗑 Delete").size(12.0).color(Color32::WHITE))
                                 .fill(Color32::from_rgb(220, 53, 69))
                                 .stroke(Stroke::NONE)
                                 .rounding(Rounding::same(8.0))
                                 .min_size(Vec2::new(75.0, 36.0));
-                        if ui.add(delete_btn).clicked() {
-                            self.confirm_dialog = Some(ConfirmAction::DeleteSalaryGrade {
-                                id: grade.id.clone(),
-                                code: grade.code.clone(),
-                                employee_count,
-                            });
+                        let busy = self.grade_status.is_mutating();
+                        if ui.add_enabled(!busy, delete_btn).clicked() {
+                            self.confirm_grade_delete(grade);
                         }
                         ui.add_space(8.0);
                         let edit_btn = Button::new(
@@ -143,12 +159,13 @@ impl PersonnelApp {
                         .stroke(Stroke::NONE)
                         .rounding(Rounding::same(8.0))
                         .min_size(Vec2::new(65.0, 36.0));
-                        if ui.add(edit_btn).clicked() {
+                        if ui.add_enabled(!busy, edit_btn).clicked() {
                             self.grade_code = grade.code.clone();
                             self.grade_salary = grade.base_salary.to_string();
                             self.grade_desc = grade.description.clone().unwrap_or_default();
                             self.edit_dialog = Some(EditDialog::EditSalaryGrade {
                                 id: grade.id.clone(),
+                                version: grade.updated_at.clone(),
                             });
                         }
                     });
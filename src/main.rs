@@ -75,7 +75,9 @@ fn main() -> eframe::Result<()> {
             visuals.window_rounding = Rounding::same(12.0);
             visuals.window_shadow = Shadow::NONE;
             cc.egui_ctx.set_visuals(visuals);
-            Ok(Box::new(PersonnelApp::new()))
+            Ok(Box::new(
+                PersonnelApp::new().with_egui_context(cc.egui_ctx.clone()),
+            ))
         }),
     )
 }
@@ -152,6 +152,7 @@ fn test_employee_creation() {
         manager_id: None,
         salary_grade_id: Some("grade-1".to_string()),
         hire_date: None,
+        leave_date: None,
         created_at: None,
         updated_at: None,
         deleted_at: None,
@@ -163,6 +164,34 @@ fn test_employee_creation() {
     assert_eq!(emp.department_id, Some("dept-1".to_string()));
 }
 
+#[test]
+fn test_employee_is_current() {
+    let mut emp = Employee {
+        id: "emp-1".to_string(),
+        first_name: "John".to_string(),
+        last_name: "Doe".to_string(),
+        email: "john.doe@example.com".to_string(),
+        role: "Employee".to_string(),
+        active: true,
+        department_id: None,
+        manager_id: None,
+        salary_grade_id: None,
+        hire_date: None,
+        leave_date: None,
+        created_at: None,
+        updated_at: None,
+        deleted_at: None,
+    };
+    assert!(emp.is_current());
+
+    emp.active = false;
+    assert!(!emp.is_current());
+
+    emp.active = true;
+    emp.deleted_at = Some("2024-05-01T00:00:00Z".to_string());
+    assert!(!emp.is_current());
+}
+
 #[test]
 fn test_employee_full_name() {
     let emp = Employee {
@@ -176,6 +205,7 @@ fn test_employee_full_name() {
         manager_id: None,
         salary_grade_id: None,
         hire_date: None,
+        leave_date: None,
         created_at: None,
         updated_at: None,
         deleted_at: None,
@@ -224,6 +254,7 @@ fn test_update_employee_request() {
         manager_id: None,
         salary_grade_id: None,
         hire_date: None,
+        leave_date: None,
     };
 
     assert_eq!(req.first_name, Some("Updated".to_string()));
@@ -241,6 +272,7 @@ fn test_salary_grade_creation() {
         base_salary: 50000.0,
         description: Some("Entry level".to_string()),
         created_at: None,
+        updated_at: None,
     };
 
     assert_eq!(grade.code, "A1");
@@ -256,6 +288,7 @@ fn test_salary_grade_formatting() {
         base_salary: 75000.50,
         description: None,
         created_at: None,
+        updated_at: None,
     };
 
     let formatted = format!("{} (${:.2})", grade.code, grade.base_salary);
@@ -288,7 +321,7 @@ fn test_update_salary_grade_request() {
     let req = UpdateSalaryGradeRequest {
         code: Some("D4".to_string()),
         base_salary: Some(120000.0),
-        description: Some("Director level".to_string()),
+        description: Some(Some("Director level".to_string())),
     };
 
     assert_eq!(req.code, Some("D4".to_string()));
@@ -306,6 +339,9 @@ fn test_config_default() {
     assert_eq!(config.route_departments, "/departments");
     assert_eq!(config.route_employees, "/employees");
     assert_eq!(config.route_salary_grades, "/salary-grades");
+    assert_eq!(config.request_timeout_secs, 30);
+    assert_eq!(config.retry_attempts, 3);
+    assert_eq!(config.ui_settings_path, "ui_settings.json");
 }
 
 #[test]
@@ -410,6 +446,7 @@ fn test_count_employees_in_department() {
             manager_id: None,
             salary_grade_id: None,
             hire_date: None,
+            leave_date: None,
             created_at: None,
             updated_at: None,
             deleted_at: None,
@@ -425,6 +462,7 @@ fn test_count_employees_in_department() {
             manager_id: None,
             salary_grade_id: None,
             hire_date: None,
+            leave_date: None,
             created_at: None,
             updated_at: None,
             deleted_at: None,
@@ -440,6 +478,7 @@ fn test_count_employees_in_department() {
             manager_id: None,
             salary_grade_id: None,
             hire_date: None,
+            leave_date: None,
             created_at: None,
             updated_at: None,
             deleted_at: None,
@@ -492,6 +531,7 @@ fn test_find_department_head() {
         manager_id: None,
         salary_grade_id: None,
         hire_date: None,
+        leave_date: None,
         created_at: None,
         updated_at: None,
         deleted_at: None,
@@ -560,6 +600,7 @@ fn test_employee_json_serialization() {
         manager_id: None,
         salary_grade_id: None,
         hire_date: None,
+        leave_date: None,
         created_at: None,
         updated_at: None,
         deleted_at: None,
@@ -578,6 +619,7 @@ fn test_salary_grade_json_serialization() {
         base_salary: 50000.0,
         description: Some("Entry level".to_string()),
         created_at: None,
+        updated_at: None,
     };
 
     let json = serde_json::to_string(&grade).unwrap();
@@ -585,6 +627,31 @@ fn test_salary_grade_json_serialization() {
     assert!(json.contains("\"base_salary\":50000.0"));
 }
 
+#[test]
+fn test_update_request_leaves_out_unchanged_fields() {
+    let req = UpdateEmployeeRequest {
+        role: Some("Employee".to_string()),
+        manager_id: Some(None),
+        ..Default::default()
+    };
+
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"role": "Employee", "manager_id": null})
+    );
+}
+
+#[test]
+fn test_update_request_keeps_explicit_null_apart() {
+    let req: UpdateDepartmentRequest = serde_json::from_str(r#"{"head_id":null}"#).unwrap();
+    assert_eq!(req.head_id, Some(None));
+    assert!(req.name.is_none());
+
+    let req: UpdateDepartmentRequest = serde_json::from_str(r#"{"name":"Sales"}"#).unwrap();
+    assert!(req.head_id.is_none());
+}
+
 // ========== Color32 Tests ==========
 
 #[test]